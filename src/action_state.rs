@@ -3,10 +3,13 @@
 //! from the treasure deck cards and finally the appropriate amount of flood
 //! cards depending on the current [water level](water_level::WaterLevel).
 
+use serde::{Deserialize, Serialize};
+
 /// The current action state. Contains the three phases and holds an [u8](u8)
 /// for the number of actions the player can still take in case of
 /// [PlayerAction](ActionState::PlayerAction) or the amount of cards that have
 /// to be drawn in case of the other options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ActionState {
     PlayerAction(u8),
     DrawArtefactCards(u8),
@@ -1,4 +1,6 @@
 use super::*;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 macro_rules! call_func {
     ($a:expr, $f:ident) => {
@@ -13,7 +15,7 @@ macro_rules! call_func {
     };
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AdventurerType {
     Courier = 0,
     Diver,
@@ -24,6 +26,16 @@ pub enum AdventurerType {
 }
 
 impl AdventurerType {
+    /// All adventurers of the standard game.
+    pub const ALL: [AdventurerType; 6] = [
+        Self::Courier,
+        Self::Diver,
+        Self::Engineer,
+        Self::Explorer,
+        Self::Navigator,
+        Self::Pilot
+    ];
+
     /// Create a new adventurer corresponding to this adventurer type
     pub fn create(self) -> Box<dyn Adventurer> {
         match self {
@@ -45,3 +57,16 @@ impl AdventurerType {
     /// Returns if the player is in principle capable of moving others.
     fn can_move_others(self) -> bool { call_func!(&self, can_move_others) }
 }
+
+impl FromStr for AdventurerType {
+    type Err = String;
+
+    /// Parse the adventurer type from its name, ignoring the case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|t| format!("{:?}", t).eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("Unknown adventurer: {}", s))
+    }
+}
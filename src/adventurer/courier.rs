@@ -7,7 +7,7 @@
 use super::{Adventurer, AdventurerInfo};
 use crate::positionable::Positionable;
 
-#[derive(Positionable)]
+#[derive(Clone, Positionable)]
 pub struct Courier {
     pos: FieldPos
 }
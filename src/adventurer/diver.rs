@@ -11,7 +11,7 @@ use crate::bfs::bfs;
 use crate::map::Full as MapFull;
 use crate::positionable::Positionable;
 
-#[derive(Positionable)]
+#[derive(Clone, Positionable)]
 pub struct Diver {
     pos: FieldPos
 }
@@ -57,11 +57,7 @@ impl AdventurerInfo for Diver {
             &map,
             Some(self.pos),
             StartPos,
-            |(from, &source_marker), (_, tile)| {
-                println!(
-                    "Marking from {:?} ({:?}) to {:?}",
-                    from, source_marker, tile
-                );
+            |(_, &source_marker), (_, tile)| {
                 if let (Some(source_marker), Some(tile)) = (source_marker, tile) {
                     match (source_marker, tile.state()) {
                         // Coming from a water tile to a solid island, the diver may move here.
@@ -70,14 +66,12 @@ impl AdventurerInfo for Diver {
                         // an adventurer can stand, the diver may move here and continue moving, if
                         // they want to.
                         (StartPos, Flooded) | (EndPos, Flooded) | (SwimThroughOnly, Flooded) => {
-                            println!("Marking endpos");
                             Some(EndPos)
                         }
                         // Coming from a water tile or the starting position to a gone tile, the
                         // diver may move through the water to any adjacent tile, but cannot end
                         // here.
                         (StartPos, Gone) | (EndPos, Gone) | (SwimThroughOnly, Gone) => {
-                            println!("Marking swimthrough");
                             Some(SwimThroughOnly)
                         }
                         _ => None
@@ -94,16 +88,7 @@ impl AdventurerInfo for Diver {
         )
         .expect("Unable to perform bfs for Diver");
 
-        bfs.iter()
-            .filter_map(|(pos, &reachable)| {
-                if reachable {
-                    Some(pos)
-                }
-                else {
-                    None
-                }
-            })
-            .collect()
+        bfs.filter_positions(|&reachable| reachable).collect()
     }
 }
 
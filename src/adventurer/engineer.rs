@@ -8,7 +8,7 @@ use super::{Adventurer, AdventurerInfo};
 use crate::map::Full as MapFull;
 use crate::positionable::Positionable;
 
-#[derive(Positionable)]
+#[derive(Clone, Positionable)]
 pub struct Engineer {
     pos: FieldPos,
     extra_drain: bool
@@ -56,6 +56,11 @@ impl Adventurer for Engineer {
             self.extra_drain = true;
         }
     }
+
+    /// The second drain can not be saved for the next turn.
+    fn on_turn_end(&mut self) { self.extra_drain = false; }
+
+    fn has_extra_action(&self) -> bool { self.extra_drain }
 }
 
 impl AdventurerInfo for Engineer {}
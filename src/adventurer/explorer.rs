@@ -7,7 +7,7 @@ use super::{Adventurer, AdventurerInfo};
 use crate::map::{Full as MapFull, MapExt};
 use crate::positionable::Positionable;
 
-#[derive(Default, Positionable)]
+#[derive(Clone, Default, Positionable)]
 pub struct Explorer {
     pos: FieldPos
 }
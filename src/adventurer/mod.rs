@@ -35,7 +35,7 @@ use crate::positionable::Positionable;
 ///
 /// For the specific implementations, please see the corresponding adventurer's
 /// module for further information.
-pub trait Adventurer: AdventurerInfo + Send + Sync {
+pub trait Adventurer: AdventurerInfo + AdventurerClone + Send + Sync {
     /// Checks if the adventurer still has something they can do this turn and
     /// returns true if so, false if they cannot do anything except end
     /// their turn.
@@ -95,6 +95,32 @@ pub trait Adventurer: AdventurerInfo + Send + Sync {
             false
         }
     }
+
+    /// Called when the turn of the adventurer is over. If the adventurer has
+    /// extra actions left over from their last action point, they are lost
+    /// here.
+    fn on_turn_end(&mut self) {}
+
+    /// Checks if the adventurer has an extra action left over from their last
+    /// action point, like the second drain of the Engineer.
+    fn has_extra_action(&self) -> bool { false }
+}
+
+/// Makes boxed adventurers cloneable, so that complete game states can be
+/// copied. Implemented automatically for all adventurers that are [Clone].
+pub trait AdventurerClone {
+    fn clone_box(&self) -> Box<dyn Adventurer>;
+}
+
+impl<T> AdventurerClone for T
+where
+    T: 'static + Adventurer + Clone
+{
+    fn clone_box(&self) -> Box<dyn Adventurer> { Box::new(self.clone()) }
+}
+
+impl Clone for Box<dyn Adventurer> {
+    fn clone(&self) -> Self { self.clone_box() }
 }
 
 /// Information about the adventurer, that is known and true no matter the
@@ -145,7 +171,7 @@ mod test {
     use crate::map::{IslandTile, IslandTileInfo, IslandTileState};
 
     // Boring adventurer without any special abilities
-    #[derive(Positionable)]
+    #[derive(Clone, Positionable)]
     struct CaptainAwesome {
         pos: FieldPos
     }
@@ -10,7 +10,7 @@
 use super::{Adventurer, AdventurerInfo};
 use crate::positionable::Positionable;
 
-#[derive(Positionable)]
+#[derive(Clone, Positionable)]
 pub struct Navigator {
     pos: FieldPos,
     extra_push: bool
@@ -50,6 +50,11 @@ impl Adventurer for Navigator {
     /// If any extra movement of another adventurer was possible, it is reset
     /// when the navigator moves.
     fn on_move(&mut self) { self.extra_push = false; }
+
+    /// The second push can not be saved for the next turn.
+    fn on_turn_end(&mut self) { self.extra_push = false; }
+
+    fn has_extra_action(&self) -> bool { self.extra_push }
 }
 
 impl AdventurerInfo for Navigator {}
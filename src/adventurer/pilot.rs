@@ -9,7 +9,7 @@ use super::{Adventurer, AdventurerInfo};
 use crate::map::{Full as MapFull, MapExt};
 use crate::positionable::Positionable;
 
-#[derive(Positionable)]
+#[derive(Clone, Positionable)]
 pub struct Pilot {
     pos: FieldPos
 }
@@ -28,15 +28,8 @@ impl AdventurerInfo for Pilot {
     /// The pilot may fly anywhere on the map where they can stand when they use
     /// their special ability.
     fn special_moves(&self, map: &MapFull) -> Vec<FieldPos> {
-        map.iter()
-            .filter_map(|(pos, _)| {
-                if map.is_standable(pos) && pos != self.pos() {
-                    Some(pos)
-                }
-                else {
-                    None
-                }
-            })
+        map.positions()
+            .filter(|&pos| map.is_standable(pos) && pos != self.pos())
             .collect()
     }
 }
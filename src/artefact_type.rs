@@ -1,6 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ArtefactType {
     Air,
     Earth,
     Fire,
     Water
 }
+
+impl ArtefactType {
+    /// All artefacts, that must be collected to win the game.
+    pub const ALL: [ArtefactType; 4] = [Self::Air, Self::Earth, Self::Fire, Self::Water];
+}
@@ -1,8 +1,9 @@
 //! Implementation of a special Breadth First Search algorithm specialised on
 //! the applications in the forbidden island.
 
-use crate::map::{BlackWhite as BlackWhiteMap, FieldPos, Map, MapExt};
+use crate::map::{FieldPos, Map, MapCell, MapExt};
 use crate::math::Vec2;
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 pub enum Error {
@@ -14,26 +15,27 @@ pub enum Error {
 /// provided starting position.
 ///
 /// # Arguments
-/// `data` - Black and white map of the island that should be tested
+/// `data` - Map of the island that should be tested. Only standable cells can
+/// be walked on.
 /// `start_pos` - The position from which to start, or `None` if any position
 /// will suffice
 ///
 /// # Returns
-/// Map where all tiles which are island tiles and can be reached are `true` and
+/// Map where all tiles which are standable and can be reached are `true` and
 /// the others `false`
-pub fn reachable_positions(
-    data: &BlackWhiteMap,
+pub fn reachable_positions<T: MapCell>(
+    data: &Map<T>,
     start_pos: Option<FieldPos>
 ) -> Result<Map<bool>, Error> {
-    // Mark the island tiles (`true`) which have been reached with a Some. Every
-    // reached tile marked in such a way will then produce a `true` in the
-    // output, all others `false`.
+    // Mark the standable tiles which have been reached from an already marked
+    // tile with a Some. Every reached tile marked in such a way will then
+    // produce a `true` in the output, all others `false`.
     bfs(
         data,
         start_pos,
         (),
-        |_, (_, &tile)| {
-            if tile {
+        |(_, from), (_, tile)| {
+            if from.is_some() && tile.is_standable() {
                 Some(())
             }
             else {
@@ -44,6 +46,33 @@ pub fn reachable_positions(
     )
 }
 
+/// Find the amount of normal moves needed to walk from the starting position
+/// to every other position.
+///
+/// # Returns
+/// Map containing the amount of steps for every standable tile that can be
+/// reached from the starting position and `None` for all others
+pub fn distances<T: MapCell>(data: &Map<T>, start_pos: FieldPos) -> Map<Option<u32>> {
+    let mut distances = Map::new(data.size(), None);
+    if data.get(start_pos).is_none() {
+        return distances;
+    }
+
+    distances.set(start_pos, Some(0));
+    let mut queue = VecDeque::new();
+    queue.push_back((start_pos, 0));
+    while let Some((pos, distance)) = queue.pop_front() {
+        for (nb, tile) in data.neighbours(pos) {
+            if tile.is_standable() && distances.get(nb).unwrap().is_none() {
+                distances.set(nb, Some(distance + 1));
+                queue.push_back((nb, distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
 /// # Breadth first search
 /// Generic general purpose implementation of the breadth first search
 /// algorithm. Can be used as a basis for other algorithms that use breadth
@@ -101,21 +130,13 @@ where
 
         // Go through the map and mark all positions that result from the marking of the
         // last iteration.
-        for y in 0..data.height() {
-            for x in 0..data.width() {
-                let pos = Vec2::from_values(x, y);
-                // Look in the four primary directions
-                for nb in pos.neighbours(None) {
-                    let target_point_data = match data.get(nb) {
-                        Some(e) => e,
-                        None => break
-                    };
-                    let to_assign =
-                        marker((pos, &marked.get(pos).unwrap()), (nb, target_point_data));
-                    if to_assign.is_some() && &to_assign != marked.get(nb).unwrap() {
-                        marked.set(nb, to_assign);
-                        something_changed = true;
-                    }
+        for pos in data.positions() {
+            // Look in the four primary directions
+            for (nb, target_point_data) in data.neighbours(pos) {
+                let to_assign = marker((pos, &marked.get(pos).unwrap()), (nb, target_point_data));
+                if to_assign.is_some() && &to_assign != marked.get(nb).unwrap() {
+                    marked.set(nb, to_assign);
+                    something_changed = true;
                 }
             }
         }
@@ -128,29 +149,17 @@ where
     }
 
     // Convert the markers into the requested output format.
-    let mut out_map = Vec::with_capacity(data.height() as usize);
-    for y in 0..data.height() {
-        let mut out_line = Vec::with_capacity(data.width() as usize);
-        for x in 0..data.width() {
-            let pos = Vec2::from_values(x, y);
-            out_line.push(output(pos, marked.get(pos).unwrap()));
-        }
-        out_map.push(out_line);
-    }
-
-    Ok(out_map.into())
+    Ok(marked.map(|pos, marker| output(pos, marker)))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::math::Rect;
-    use std::ops::Deref;
 
-    impl MapExt for Map<Option<u32>> {
-        fn is_standable(&self, pos: FieldPos) -> bool { self.get(pos).unwrap().is_some() }
+    impl MapCell for Option<u32> {
+        fn is_island(&self) -> bool { self.is_some() }
 
-        fn limit_rect(&self) -> Rect<u8> { Rect::from_slice([0, 0, self.width(), self.height()]) }
+        fn is_standable(&self) -> bool { self.is_some() }
     }
 
     #[test]
@@ -178,12 +187,51 @@ mod test {
         )
         .unwrap();
 
-        let expected = vec![
+        let expected: Map<bool> = vec![
             vec![false, false, true, false, false, false],
             vec![false, true, true, true, false, false],
             vec![false, false, true, false, false, false],
-        ];
+        ]
+        .into();
+
+        assert_eq!(expected, bfs);
+    }
+
+    #[test]
+    fn reachable_positions_split_island() {
+        let data: Map<bool> = vec![
+            vec![true, true, false, true],
+            vec![false, true, false, true],
+        ]
+        .into();
 
-        assert_eq!(&expected, bfs.deref());
+        let reachable = reachable_positions(&data, Some(FieldPos::new())).unwrap();
+
+        let expected: Map<bool> = vec![
+            vec![true, true, false, false],
+            vec![false, true, false, false],
+        ]
+        .into();
+        assert_eq!(expected, reachable);
+    }
+
+    #[test]
+    fn distances_around_water() {
+        let data: Map<bool> = vec![
+            vec![true, true, true],
+            vec![true, false, true],
+            vec![true, false, false],
+        ]
+        .into();
+
+        let distances = distances(&data, FieldPos::from_values(0, 2));
+
+        let expected: Map<Option<u32>> = vec![
+            vec![Some(2), Some(3), Some(4)],
+            vec![Some(1), None, Some(5)],
+            vec![Some(0), None, None],
+        ]
+        .into();
+        assert_eq!(expected, distances);
     }
 }
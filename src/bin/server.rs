@@ -0,0 +1,111 @@
+//! Host a game that players on other machines can join over the network.
+//!
+//! # Usage
+//! `server [--address 0.0.0.0:4242] [--difficulty normal] [--map FILE]
+//! [--scenario FILE] [--seed N] [--spectator-delay TURNS]`
+//!
+//! The players meet in a lobby, where they choose their adventurers and can
+//! change the difficulty and map given on the command line, until everyone is
+//! ready. A scenario is started right away instead, with the seed provided,
+//! and its adventurers are the seats, which are taken by the clients in the
+//! order they connect. Spectators may watch the game, but only see what
+//! happened the given number of turns ago. The messages they exchange with the
+//! server are described in [java_game::net::protocol].
+
+use java_game::difficulty::Difficulty;
+use java_game::map::{loader, MapChoice};
+use java_game::net::{Lobby, Server, DEFAULT_PORT};
+use java_game::scenario;
+use std::env;
+use std::process;
+
+const USAGE: &str = "Usage: server [--address 0.0.0.0:4242] [--difficulty normal] [--map FILE] \
+                     [--scenario FILE] [--seed N] [--spectator-delay TURNS]";
+
+struct Options {
+    address: String,
+    difficulty: Difficulty,
+    map: MapChoice,
+    scenario: Option<String>,
+    seed: u64,
+    spectator_delay: u32
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let server = match &options.scenario {
+        Some(path) => {
+            let state = scenario::load_scenario(path)
+                .and_then(|scenario| scenario.start(options.seed))
+                .unwrap_or_else(|err| {
+                    eprintln!("Unable to start scenario {}: {}", path, err);
+                    process::exit(1);
+                });
+            Server::bind(&options.address, state)
+        }
+        None => {
+            let lobby = Lobby::new(options.difficulty, options.map.clone());
+            Server::bind_lobby(&options.address, lobby)
+        }
+    };
+    let server = server
+        .unwrap_or_else(|err| {
+            eprintln!("Unable to listen on {}: {}", options.address, err);
+            process::exit(1);
+        })
+        .with_spectator_delay(options.spectator_delay);
+    println!("Waiting for players on {}", options.address);
+
+    if let Err(err) = server.run() {
+        eprintln!("The server stopped: {}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        address: format!("0.0.0.0:{}", DEFAULT_PORT),
+        difficulty: Difficulty::Normal,
+        map: MapChoice::Random,
+        scenario: None,
+        seed: rand::random(),
+        spectator_delay: 0
+    };
+
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        match arg.as_str() {
+            "--address" => options.address = value,
+            "--difficulty" => options.difficulty = value.parse()?,
+            "--map" => {
+                options.map = MapChoice::Custom(
+                    loader::load_map(&value)
+                        .map_err(|err| format!("Unable to load map {}: {}", value, err))?
+                )
+            }
+            "--scenario" => options.scenario = Some(value),
+            "--seed" => {
+                options.seed = value
+                    .parse()
+                    .map_err(|_| "The seed must be a number".to_string())?
+            }
+            "--spectator-delay" => {
+                options.spectator_delay = value
+                    .parse()
+                    .map_err(|_| "The delay must be a number of turns".to_string())?
+            }
+            _ => return Err(format!("Unknown option: {}", arg))
+        }
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,155 @@
+//! Play many games without any graphics and print statistics about them.
+//!
+//! # Usage
+//! `simulate [--games N] [--players pilot,diver] [--difficulty normal]
+//! [--map FILE] [--bot heuristic] [--budget 200|50ms] [--seed N] [--max-turns
+//! N]`
+//!
+//! The budget is the time or the number of iterations the search based bot may
+//! use for every action.
+
+use java_game::adventurer::AdventurerType;
+use java_game::bot::{self, Bot, Budget, Mcts};
+use java_game::difficulty::Difficulty;
+use java_game::game_state::GameState;
+use java_game::map::{loader, MapChoice};
+use java_game::simulation::{self, Statistics};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::env;
+use std::process;
+
+const USAGE: &str = "Usage: simulate [--games N] [--players pilot,diver] [--difficulty normal] \
+                     [--map FILE] [--bot heuristic|mcts|random] [--budget N|Nms] [--seed N] \
+                     [--max-turns N]";
+
+struct Options {
+    games: u32,
+    players: Vec<AdventurerType>,
+    difficulty: Difficulty,
+    map: MapChoice,
+    bot: String,
+    budget: Option<Budget>,
+    seed: Option<u64>,
+    max_turns: u32
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            process::exit(1);
+        }
+    };
+
+    let mut rng = match options.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy()
+    };
+
+    let mut statistics = Statistics::new();
+    for _ in 0..options.games {
+        let map = options.map.create(&mut rng);
+        let state = GameState::with_seed(map, &options.players, options.difficulty, rng.gen());
+
+        let mut bots: Vec<Box<dyn Bot>> = options
+            .players
+            .iter()
+            .map(|_| match options.budget {
+                Some(budget) if options.bot == "mcts" => {
+                    Box::new(Mcts::with_seed(budget, rng.gen())) as Box<dyn Bot>
+                }
+                _ => bot::from_name(&options.bot, rng.gen()).unwrap()
+            })
+            .collect();
+        let report = simulation::play(
+            state,
+            |state| bot::next_action(state, &mut bots),
+            options.max_turns
+        );
+        statistics.add(&report);
+    }
+
+    println!(
+        "{:?} on {:?} played by the {} bot\n",
+        options.players, options.difficulty, options.bot
+    );
+    print!("{}", statistics);
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        games: 100,
+        players: vec![AdventurerType::Pilot, AdventurerType::Diver],
+        difficulty: Difficulty::Normal,
+        map: MapChoice::Random,
+        bot: "heuristic".to_string(),
+        budget: None,
+        seed: None,
+        max_turns: 1000
+    };
+
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {}", arg))?;
+        let number = || {
+            value
+                .parse()
+                .map_err(|_| format!("The value of {} must be a number", arg))
+        };
+        match arg.as_str() {
+            "--games" => options.games = number()?,
+            "--players" => {
+                options.players = value.split(',').map(str::parse).collect::<Result<_, _>>()?
+            }
+            "--difficulty" => options.difficulty = value.parse()?,
+            "--map" => {
+                options.map = MapChoice::Custom(
+                    loader::load_map(&value)
+                        .map_err(|err| format!("Unable to load map {}: {}", value, err))?
+                )
+            }
+            "--bot" => {
+                if bot::from_name(&value, 0).is_none() {
+                    return Err(format!("Unknown bot: {}", value));
+                }
+                options.bot = value.to_lowercase()
+            }
+            "--budget" => options.budget = Some(value.parse()?),
+            "--seed" => {
+                options.seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| "The seed must be a number".to_string())?
+                )
+            }
+            "--max-turns" => options.max_turns = number()?,
+            _ => return Err(format!("Unknown option: {}", arg))
+        }
+    }
+
+    if options.players.is_empty() || options.players.len() > 4 {
+        return Err("The game needs one to four players".to_string());
+    }
+    if let Some((_, adventurer)) = options
+        .players
+        .iter()
+        .enumerate()
+        .find(|(i, adventurer)| options.players[..*i].contains(adventurer))
+    {
+        return Err(format!("The {:?} can only be played once", adventurer));
+    }
+    if let Some(adventurer) = options
+        .players
+        .iter()
+        .find(|&&adventurer| !options.map.has_spawn_point(adventurer))
+    {
+        return Err(format!(
+            "The map has no spawn tile for the {:?}",
+            adventurer
+        ));
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,477 @@
+//! Terminal frontend of the game. It does not need a graphics card, so the
+//! complete rules can be played on any machine with a terminal.
+//!
+//! # Usage
+//! `tui [--players pilot,diver] [--difficulty normal] [--map FILE] [--seed N]`
+//! `tui --scenario FILE [--seed N]`
+//!
+//! Actions are chosen by typing the number in front of them and pressing
+//! enter. `?` shows the names of all tiles, `h` suggests an action and `q`
+//! quits the game.
+
+use java_game::adventurer::AdventurerType;
+use java_game::bot;
+use java_game::difficulty::Difficulty;
+use java_game::game_state::{GameAction, GameState, GameView, Outcome};
+use java_game::map::{loader, FieldPos, IslandTileInfo, IslandTileState, MapChoice};
+use java_game::positionable::Positionable;
+use java_game::scenario::{self, Objective, ObjectiveStatus};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+const RESET: &str = "\x1b[0m";
+const DRY: &str = "\x1b[30;42m";
+const FLOODED: &str = "\x1b[37;44m";
+const GONE: &str = "\x1b[37;100m";
+const HIGHLIGHT: &str = "\x1b[30;43m";
+
+struct Options {
+    players: Vec<AdventurerType>,
+    difficulty: Difficulty,
+    map: Option<String>,
+    scenario: Option<String>,
+    seed: Option<u64>
+}
+
+fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "Usage: tui [--players pilot,diver] [--difficulty normal] [--map FILE] [--seed N]"
+            );
+            eprintln!("       tui --scenario FILE [--seed N]");
+            process::exit(1);
+        }
+    };
+
+    let (mut state, objective) = match &options.scenario {
+        Some(path) => start_scenario(path, options.seed),
+        None => (start_game(&options), None)
+    };
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock().lines();
+    while !state.is_over() {
+        let actions = state.legal_actions();
+        print!("{}", render(&state, &[]));
+
+        let action = match choose_action(&state, actions, &mut input) {
+            Some(action) => action,
+            None => return
+        };
+        match state.apply(&action) {
+            Ok(events) => {
+                for event in events {
+                    println!("  {:?}", event);
+                }
+            }
+            Err(err) => println!("{}", err)
+        }
+
+        if let Some(objective) = objective {
+            let status = objective.status(&state);
+            if status != ObjectiveStatus::Open {
+                print!("{}", render(&state, &[]));
+                match status {
+                    ObjectiveStatus::Achieved => println!("Scenario solved: {}", objective),
+                    _ => println!("Scenario failed: {}", objective)
+                }
+                return;
+            }
+        }
+    }
+
+    print!("{}", render(&state, &[]));
+    match state.outcome() {
+        Some(Outcome::Won) => println!("The adventurers escaped with all artefacts!"),
+        Some(Outcome::Lost(reason)) => println!("The game is lost: {:?}", reason),
+        None => {}
+    }
+}
+
+fn start_game(options: &Options) -> GameState {
+    let map = match &options.map {
+        Some(path) => MapChoice::Custom(loader::load_map(path).unwrap_or_else(|err| {
+            eprintln!("Unable to load map {}: {}", path, err);
+            process::exit(1);
+        })),
+        None => MapChoice::Random
+    };
+    if let Some(adventurer) = options
+        .players
+        .iter()
+        .find(|&&adventurer| !map.has_spawn_point(adventurer))
+    {
+        eprintln!("The map has no spawn tile for the {:?}", adventurer);
+        process::exit(1);
+    }
+    let map = map.create(&mut rand::thread_rng());
+
+    match options.seed {
+        Some(seed) => GameState::with_seed(map, &options.players, options.difficulty, seed),
+        None => GameState::new(map, &options.players, options.difficulty)
+    }
+}
+
+fn start_scenario(path: &str, seed: Option<u64>) -> (GameState, Option<Objective>) {
+    let scenario = scenario::load_scenario(path).unwrap_or_else(|err| {
+        eprintln!("Unable to load scenario {}: {}", path, err);
+        process::exit(1);
+    });
+    let state = scenario
+        .start(seed.unwrap_or_else(rand::random))
+        .unwrap_or_else(|err| {
+            eprintln!("Unable to start scenario {}: {}", path, err);
+            process::exit(1);
+        });
+
+    println!("{}", scenario.name);
+    if !scenario.description.is_empty() {
+        println!("{}", scenario.description);
+    }
+    println!("Objective: {}", scenario.objective);
+
+    (state, Some(scenario.objective))
+}
+
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        players: vec![AdventurerType::Pilot, AdventurerType::Diver],
+        difficulty: Difficulty::Normal,
+        map: None,
+        scenario: None,
+        seed: None
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--players" => {
+                options.players = value()?
+                    .split(',')
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?
+            }
+            "--difficulty" => options.difficulty = value()?.parse()?,
+            "--map" => options.map = Some(value()?),
+            "--scenario" => options.scenario = Some(value()?),
+            "--seed" => {
+                options.seed = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "The seed must be a number".to_string())?
+                )
+            }
+            _ => return Err(format!("Unknown option: {}", arg))
+        }
+    }
+
+    if options.players.is_empty() || options.players.len() > 4 {
+        return Err("The game needs one to four players".to_string());
+    }
+    if let Some((_, adventurer)) = options
+        .players
+        .iter()
+        .enumerate()
+        .find(|(i, adventurer)| options.players[..*i].contains(adventurer))
+    {
+        return Err(format!("The {:?} can only be played once", adventurer));
+    }
+
+    Ok(options)
+}
+
+/// Two letter abbreviation of the tile, used to show it on the board.
+fn abbreviation(info: IslandTileInfo) -> String {
+    let words: Vec<&str> = info
+        .name()
+        .split_whitespace()
+        .filter(|w| w.starts_with(char::is_uppercase))
+        .collect();
+
+    if words.len() > 1 {
+        words.iter().filter_map(|w| w.chars().next()).collect()
+    }
+    else {
+        info.name().chars().take(2).collect()
+    }
+}
+
+/// The letter the adventurer is shown with on the board.
+fn adventurer_letter(adventurer_type: AdventurerType) -> char {
+    match adventurer_type {
+        AdventurerType::Explorer => 'X',
+        other => format!("{:?}", other).chars().next().unwrap()
+    }
+}
+
+/// Render the board and all public information of the game. Tiles at the
+/// highlighted positions are drawn in a different colour.
+fn render(state: &GameState, highlighted: &[FieldPos]) -> String {
+    let map = state.map();
+    let mut out = String::new();
+
+    out.push_str("\n     ");
+    for x in 0..map.width() {
+        out.push_str(&format!(" {:^4} ", x));
+    }
+    out.push('\n');
+
+    for y in 0..map.height() {
+        let mut tiles = format!("{:>3}  ", y);
+        let mut players = String::from("     ");
+        for x in 0..map.width() {
+            let pos = FieldPos::from_values(x, y);
+            match map.get(pos).unwrap() {
+                Some(tile) => {
+                    let colour = if highlighted.contains(&pos) {
+                        HIGHLIGHT
+                    }
+                    else {
+                        match tile.state() {
+                            IslandTileState::Dry => DRY,
+                            IslandTileState::Flooded => FLOODED,
+                            IslandTileState::Gone => GONE
+                        }
+                    };
+                    let marker = match tile.info().hidden_artefact() {
+                        Some(_) => '*',
+                        None => ' '
+                    };
+                    tiles.push_str(&format!(
+                        "{} {:<2}{} {} ",
+                        colour,
+                        abbreviation(*tile.info()),
+                        marker,
+                        RESET
+                    ));
+                }
+                None => tiles.push_str("      ")
+            }
+
+            let on_tile: String = state
+                .characters()
+                .iter()
+                .filter(|c| c.pos() == pos)
+                .map(|c| adventurer_letter(c.adventurer_type()))
+                .collect();
+            players.push_str(&format!(" {:<4} ", on_tile));
+        }
+        out.push_str(&tiles);
+        out.push('\n');
+        out.push_str(&players);
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "{} dry {} {} flooded {} {} gone {}  * artefact tile\n\n",
+        DRY, RESET, FLOODED, RESET, GONE, RESET
+    ));
+
+    for (i, character) in state.characters().iter().enumerate() {
+        let active = if i == state.active_player() { '>' } else { ' ' };
+        out.push_str(&format!(
+            "{} {} {:?} ({}) on {}: {:?}\n",
+            active,
+            i,
+            character.adventurer_type(),
+            adventurer_letter(character.adventurer_type()),
+            tile_name(state, character.pos()),
+            character.hand()
+        ));
+    }
+
+    out.push_str(&format!(
+        "\nWater level {} ({} flood cards per turn)   Artefacts: {:?}\n",
+        state.water_level().level(),
+        state.water_level().draw_amount(),
+        state.artefacts()
+    ));
+    out.push_str(&format!(
+        "Treasure deck {} (discarded {})   Flood deck {} (discarded {})\n",
+        state.treasure_cards().draw_stack_size(),
+        state.treasure_cards().discard_stack_size(),
+        state.flood_cards().draw_stack_size(),
+        state.flood_cards().discard_stack_size()
+    ));
+    out.push_str(&format!(
+        "Turn {}: {:?}\n",
+        state.turns_played(),
+        state.action_state()
+    ));
+
+    out
+}
+
+fn tile_name(state: &GameState, pos: FieldPos) -> String {
+    match state.map().get(pos) {
+        Some(Some(tile)) => format!("{} ({})", tile.info().name(), abbreviation(*tile.info())),
+        _ => format!("{:?}", pos)
+    }
+}
+
+/// The category an action is listed under in the first selection step.
+fn category(action: &GameAction) -> &'static str {
+    match action {
+        GameAction::Move(_) => "Move",
+        GameAction::SpecialMove(_) => "Special move",
+        GameAction::Drain(_) => "Drain",
+        GameAction::Navigate { .. } => "Navigate another adventurer",
+        GameAction::GiveCard { .. } => "Give a card",
+        GameAction::CollectArtefact => "Collect the artefact",
+        GameAction::EndActions => "End actions",
+        GameAction::DrawTreasureCard => "Draw a treasure card",
+        GameAction::DrawFloodCard => "Draw a flood card",
+        GameAction::Discard { .. } => "Discard a card",
+        GameAction::Sandbags { .. } => "Play sandbags",
+        GameAction::Helicopter { .. } => "Play a helicopter",
+        GameAction::LiftOff { .. } => "Lift off from the island"
+    }
+}
+
+fn describe(state: &GameState, action: &GameAction) -> String {
+    let adventurer = |player: usize| format!("{:?}", state.characters()[player].adventurer_type());
+    match action {
+        GameAction::Move(to) | GameAction::SpecialMove(to) | GameAction::Drain(to) => {
+            tile_name(state, *to)
+        }
+        GameAction::Navigate { player, to } => {
+            format!("{} to {}", adventurer(*player), tile_name(state, *to))
+        }
+        GameAction::GiveCard { to, card } => format!("{:?} to {}", card, adventurer(*to)),
+        GameAction::Discard { player, card } => format!("{:?} of {}", card, adventurer(*player)),
+        GameAction::Sandbags { player, pos } => {
+            format!("{} drains {}", adventurer(*player), tile_name(state, *pos))
+        }
+        GameAction::Helicopter {
+            player,
+            passengers,
+            to
+        } => {
+            let passengers: Vec<String> = passengers.iter().map(|&p| adventurer(p)).collect();
+            format!(
+                "{} flies {} to {}",
+                adventurer(*player),
+                passengers.join(", "),
+                tile_name(state, *to)
+            )
+        }
+        GameAction::LiftOff { player } => format!("with the card of {}", adventurer(*player)),
+        other => category(other).to_string()
+    }
+}
+
+// The tile an action targets, so it can be highlighted when choosing.
+fn target(action: &GameAction) -> Option<FieldPos> {
+    match action {
+        GameAction::Move(pos)
+        | GameAction::SpecialMove(pos)
+        | GameAction::Drain(pos)
+        | GameAction::Navigate { to: pos, .. }
+        | GameAction::Sandbags { pos, .. }
+        | GameAction::Helicopter { to: pos, .. } => Some(*pos),
+        _ => None
+    }
+}
+
+/// Let the user choose one of the actions. First the kind of action is chosen,
+/// then the candidate, with the candidate tiles highlighted on the board.
+/// Returns `None` if the user wants to quit.
+fn choose_action<I>(
+    state: &GameState,
+    actions: Vec<GameAction>,
+    input: &mut I
+) -> Option<GameAction>
+where
+    I: Iterator<Item = io::Result<String>>
+{
+    let mut categories: Vec<&'static str> = Vec::new();
+    for action in &actions {
+        if !categories.contains(&category(action)) {
+            categories.push(category(action));
+        }
+    }
+
+    loop {
+        let names: Vec<String> = categories.iter().map(|c| c.to_string()).collect();
+        let chosen = categories[select(state, &names, input)?];
+
+        let candidates: Vec<&GameAction> =
+            actions.iter().filter(|a| category(a) == chosen).collect();
+        if candidates.len() == 1 {
+            return Some(candidates[0].clone());
+        }
+
+        let highlighted: Vec<FieldPos> = candidates.iter().filter_map(|a| target(a)).collect();
+        print!("{}", render(state, &highlighted));
+        println!("{}:", chosen);
+        let mut names: Vec<String> = candidates.iter().map(|a| describe(state, a)).collect();
+        names.push("Back".to_string());
+        let index = select(state, &names, input)?;
+        if index < candidates.len() {
+            return Some(candidates[index].clone());
+        }
+    }
+}
+
+/// Print the numbered options and read the choice of the user. Returns `None`
+/// if the input has ended or the user wants to quit.
+fn select<I>(state: &GameState, options: &[String], input: &mut I) -> Option<usize>
+where
+    I: Iterator<Item = io::Result<String>>
+{
+    loop {
+        for (i, option) in options.iter().enumerate() {
+            println!("{:>3}) {}", i, option);
+        }
+        print!("> ");
+        io::stdout().flush().ok()?;
+
+        let line = input.next()?.ok()?;
+        match line.trim() {
+            "q" => return None,
+            "?" => print_legend(state),
+            "h" => print_hint(state),
+            // Choosing is not necessary, if there is only one option
+            "" if options.len() == 1 => return Some(0),
+            choice => match choice.parse::<usize>() {
+                Ok(index) if index < options.len() => return Some(index),
+                _ => println!("Please enter a number between 0 and {}", options.len() - 1)
+            }
+        }
+    }
+}
+
+fn print_legend(state: &GameState) {
+    for (pos, tile) in state.map().iter() {
+        if let Some(tile) = tile {
+            let artefact = match tile.info().hidden_artefact() {
+                Some(artefact) => format!(", {:?}", artefact),
+                None => String::new()
+            };
+            println!(
+                "{:<2} {} at {}/{} ({:?}{})",
+                abbreviation(*tile.info()),
+                tile.info().name(),
+                pos.x,
+                pos.y,
+                tile.state(),
+                artefact
+            );
+        }
+    }
+}
+
+// Suggest an action to the deciding player and highlight the tiles it is about.
+fn print_hint(state: &GameState) {
+    let view = GameView::new(state, state.deciding_player());
+    if let Some(hint) = bot::hint(&view) {
+        print!("{}", render(state, &hint.tiles(&view)));
+        println!("Hint: {}", hint);
+    }
+}
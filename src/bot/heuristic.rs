@@ -0,0 +1,351 @@
+//! A simple rule based bot. It does not look ahead, but rates every allowed
+//! action by a few rules of thumb:
+//!
+//! - Drain flooded tiles of artefacts that have not been collected yet and keep
+//!   Fools' Landing dry.
+//! - Walk to the tile of an artefact when holding enough cards to collect it.
+//! - Give cards to teammates who are closer to a complete set.
+//! - Fly everyone to Fools' Landing and leave once all artefacts are collected.
+
+use super::Bot;
+use crate::artefact_type::ArtefactType;
+use crate::bfs;
+use crate::game_state::{GameAction, GameView};
+use crate::map::{FieldPos, IslandTileInfo, IslandTileState, MapExt};
+use crate::positionable::Positionable;
+use crate::treasure_card::{TreasureCard, CARDS_PER_ARTEFACT};
+
+/// The baseline bot, which rates all actions and chooses the best one.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Heuristic;
+
+impl Bot for Heuristic {
+    fn choose_action(&mut self, view: &GameView) -> GameAction {
+        let actions = view.legal_actions();
+        let mut best: Option<(i32, &GameAction)> = None;
+        for action in &actions {
+            let score = Self::score(view, &actions, action);
+            if best.map_or(true, |(best, _)| score > best) {
+                best = Some((score, action));
+            }
+        }
+
+        best.expect("The bot has been asked for an action, but there is nothing it can do")
+            .1
+            .clone()
+    }
+}
+
+impl Heuristic {
+    /// Rate one of the legal actions of the view. The higher the score, the
+    /// better the action. Negative scores mean the action should rather not be
+    /// taken at all.
+    pub fn score(view: &GameView, actions: &[GameAction], action: &GameAction) -> i32 {
+        match action {
+            GameAction::LiftOff { .. } => 1000,
+            GameAction::CollectArtefact => 900,
+            GameAction::DrawTreasureCard | GameAction::DrawFloodCard => 500,
+            GameAction::Drain(pos) => 200 + importance(view, *pos),
+            GameAction::Sandbags { pos, .. } => {
+                // Sandbags are rare, so only use them if an important tile is in
+                // danger and cannot be drained otherwise.
+                let importance = importance(view, *pos);
+                if importance >= ENDANGERED && !actions.contains(&GameAction::Drain(*pos)) {
+                    600 + importance
+                }
+                else {
+                    -1
+                }
+            }
+            GameAction::Move(to) | GameAction::SpecialMove(to) => {
+                score_move(view, view.player(), *to)
+            }
+            // Pushing others costs the Navigator's actions, so it is a bit worse
+            // than the other adventurer walking there on their own.
+            GameAction::Navigate { player, to } => match score_move(view, *player, *to) {
+                score if score > 0 => score - 10,
+                score => score
+            },
+            GameAction::GiveCard {
+                to,
+                card: TreasureCard::Artefact(artefact)
+            } => {
+                let own = count(view.character().hand(), *artefact) as i32;
+                let theirs = count(view.characters()[*to].hand(), *artefact) as i32;
+                if theirs >= own && theirs < CARDS_PER_ARTEFACT as i32 {
+                    150 + theirs * 10
+                }
+                else {
+                    -1
+                }
+            }
+            GameAction::Discard { card, .. } => 100 - value(view, *card),
+            GameAction::Helicopter { passengers, to, .. } => {
+                let fools_landing = view.tile_pos(IslandTileInfo::FoolsLanding);
+                let all_collected = view.artefacts().len() == ArtefactType::ALL.len();
+                if all_collected && Some(*to) == fools_landing {
+                    400 + passengers.len() as i32
+                }
+                else {
+                    -1
+                }
+            }
+            GameAction::EndActions => 0,
+            _ => -1
+        }
+    }
+}
+
+/// Importance of tiles the bot will use sandbags on.
+pub(crate) const ENDANGERED: i32 = 80;
+
+// Score of moves that bring the adventurer closer to collecting an artefact or
+// leaving the island. These are more important than draining most tiles.
+const URGENT: i32 = 270;
+
+/// How important it is to keep the tile at the position from sinking.
+pub(crate) fn importance(view: &GameView, pos: FieldPos) -> i32 {
+    let info = match view.map().get(pos) {
+        Some(Some(tile)) => *tile.info(),
+        _ => return 0
+    };
+
+    if info == IslandTileInfo::FoolsLanding {
+        return 100;
+    }
+
+    match info.hidden_artefact() {
+        Some(artefact) if !view.artefacts().contains(&artefact) => {
+            // If the other tile of the artefact is already gone, this is the
+            // last chance to get it.
+            let other_left = IslandTileInfo::ALL.iter().any(|&other| {
+                other != info
+                    && other.hidden_artefact() == Some(artefact)
+                    && view
+                        .tile_pos(other)
+                        .map_or(false, |pos| view.map().is_standable(pos))
+            });
+            if other_left {
+                60
+            }
+            else {
+                90
+            }
+        }
+        _ => 30
+    }
+}
+
+/// Why an adventurer is heading somewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Purpose {
+    /// All artefacts are collected, so everyone gathers on Fools' Landing.
+    LeaveIsland,
+    /// The adventurer holds enough cards to collect the artefact.
+    Collect(ArtefactType),
+    /// Meet the player to hand over cards of the artefact.
+    Meet {
+        player:   usize,
+        artefact: ArtefactType
+    },
+    /// Get close enough to drain important flooded tiles.
+    Drain
+}
+
+/// Where an adventurer should go and why.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Goal {
+    pub purpose: Purpose,
+    pub tiles: Vec<FieldPos>,
+    /// How many steps away from one of the tiles is close enough.
+    pub close_enough: u32,
+    pub urgency: i32
+}
+
+impl Goal {
+    fn new(purpose: Purpose, tiles: Vec<FieldPos>, close_enough: u32, urgency: i32) -> Self {
+        Self {
+            purpose,
+            tiles,
+            close_enough,
+            urgency
+        }
+    }
+
+    // The amount of normal moves from the position to the closest tile of the
+    // goal.
+    fn distance(&self, view: &GameView, from: FieldPos) -> Option<u32> {
+        let distances = bfs::distances(view.map(), from);
+        self.tiles
+            .iter()
+            .filter_map(|&tile| *distances.get(tile).unwrap())
+            .min()
+    }
+}
+
+// Moves are only good if they bring the adventurer closer to where they need to
+// be.
+fn score_move(view: &GameView, player: usize, to: FieldPos) -> i32 {
+    let goal = match goal(view, player) {
+        Some(goal) => goal,
+        None => return -1
+    };
+
+    match (
+        goal.distance(view, view.characters()[player].pos()),
+        goal.distance(view, to)
+    ) {
+        (Some(current), Some(new)) if current > goal.close_enough && new < current => {
+            goal.urgency + 10 * (current - new) as i32
+        }
+        (None, Some(_)) => goal.urgency,
+        _ => -1
+    }
+}
+
+/// Where the adventurer of the player should go next, if anywhere.
+pub(crate) fn goal(view: &GameView, player: usize) -> Option<Goal> {
+    let map = view.map();
+    let standable = |info: IslandTileInfo| view.tile_pos(info).filter(|&pos| map.is_standable(pos));
+
+    if view.artefacts().len() == ArtefactType::ALL.len() {
+        let tiles: Vec<FieldPos> = standable(IslandTileInfo::FoolsLanding)
+            .into_iter()
+            .collect();
+        return Some(Goal::new(Purpose::LeaveIsland, tiles, 0, URGENT));
+    }
+
+    let hand = view.characters()[player].hand();
+    for &artefact in ArtefactType::ALL.iter() {
+        if !view.artefacts().contains(&artefact) && count(hand, artefact) >= CARDS_PER_ARTEFACT {
+            let tiles = IslandTileInfo::ALL
+                .iter()
+                .filter(|info| info.hidden_artefact() == Some(artefact))
+                .filter_map(|&info| standable(info))
+                .collect();
+            return Some(Goal::new(Purpose::Collect(artefact), tiles, 0, URGENT));
+        }
+    }
+
+    // Meet a teammate who is collecting the same artefact to hand over cards.
+    for &artefact in ArtefactType::ALL.iter() {
+        if view.artefacts().contains(&artefact) || count(hand, artefact) == 0 {
+            continue;
+        }
+
+        let own = count(hand, artefact);
+        let teammate = view
+            .characters()
+            .iter()
+            .enumerate()
+            .filter(|&(i, other)| {
+                let theirs = count(other.hand(), artefact);
+                i != player && theirs >= own && theirs + own >= CARDS_PER_ARTEFACT
+            })
+            .max_by_key(|(_, other)| count(other.hand(), artefact));
+        if let Some((teammate, other)) = teammate {
+            let purpose = Purpose::Meet {
+                player: teammate,
+                artefact
+            };
+            return Some(Goal::new(purpose, vec![other.pos()], 0, 150));
+        }
+    }
+
+    // Otherwise go where tiles need to be drained, which can be done from the
+    // neighbouring tiles.
+    let flooded: Vec<FieldPos> = map
+        .iter()
+        .filter(|(pos, tile)| {
+            tile.map_or(false, |tile| tile.state() == IslandTileState::Flooded)
+                && importance(view, *pos) > 30
+        })
+        .map(|(pos, _)| pos)
+        .collect();
+    if flooded.is_empty() {
+        None
+    }
+    else {
+        Some(Goal::new(Purpose::Drain, flooded, 1, 100))
+    }
+}
+
+// How much the player would miss the card, if it were discarded.
+fn value(view: &GameView, card: TreasureCard) -> i32 {
+    match card {
+        TreasureCard::Artefact(artefact) if view.artefacts().contains(&artefact) => 0,
+        TreasureCard::Artefact(artefact) => {
+            10 + 10 * count(view.character().hand(), artefact) as i32
+        }
+        TreasureCard::Sandbags => 50,
+        TreasureCard::Helicopter => 60,
+        TreasureCard::WatersRise => 0
+    }
+}
+
+pub(crate) fn count(hand: &[TreasureCard], artefact: ArtefactType) -> usize {
+    hand.iter()
+        .filter(|&&card| card == TreasureCard::Artefact(artefact))
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::bot::{self, Random};
+    use crate::difficulty::Difficulty;
+    use crate::game_state::GameState;
+    use crate::map::generate;
+    use crate::simulation::{self, Statistics};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn statistics(bot: fn(u64) -> Box<dyn Bot>) -> Statistics {
+        let players = [AdventurerType::Pilot, AdventurerType::Engineer];
+        let mut statistics = Statistics::new();
+        for seed in 0..30 {
+            let map = generate::filled_island_with(
+                &generate::classic_shape(),
+                &mut StdRng::seed_from_u64(seed)
+            );
+            let state = GameState::with_seed(map, &players, Difficulty::Novice, seed);
+            let mut bots: Vec<Box<dyn Bot>> = players.iter().map(|_| bot(seed)).collect();
+            let report = simulation::play(state, |state| bot::next_action(state, &mut bots), 1000);
+            statistics.add(&report);
+        }
+
+        statistics
+    }
+
+    #[test]
+    fn plays_better_than_random() {
+        let heuristic = statistics(|_| Box::new(Heuristic));
+        let random = statistics(|seed| Box::new(Random::with_seed(seed)));
+
+        assert_eq!(0, heuristic.unfinished());
+        assert!(heuristic.average_turns() > random.average_turns());
+        assert!(heuristic.wins() >= random.wins());
+    }
+
+    #[test]
+    fn keeps_fools_landing_dry() {
+        let map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(0));
+        let mut state = GameState::with_seed(map, &[AdventurerType::Pilot], Difficulty::Novice, 0);
+        let fools_landing = state.tile_pos(IslandTileInfo::FoolsLanding).unwrap();
+        state.set_tile_state(fools_landing, IslandTileState::Flooded);
+
+        let action = Heuristic.choose_action(&GameView::new(&state, 0));
+        assert_eq!(GameAction::Drain(fools_landing), action);
+        state.apply(&action).unwrap();
+        assert_eq!(
+            Some(IslandTileState::Dry),
+            state
+                .map()
+                .get(fools_landing)
+                .copied()
+                .flatten()
+                .map(|tile| tile.state())
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Hints for human players. A bot chooses the action it would take in the
+//! position of the player, which is then explained in a short sentence, so new
+//! players can learn why it is a good idea.
+
+use super::heuristic::{self, Goal, Purpose};
+use super::{Bot, Heuristic};
+use crate::adventurer::AdventurerType;
+use crate::artefact_type::ArtefactType;
+use crate::game_state::{GameAction, GameView};
+use crate::map::{FieldPos, IslandTileInfo, MapExt};
+use crate::positionable::Positionable;
+use crate::treasure_card::{TreasureCard, CARDS_PER_ARTEFACT};
+use rand::{rngs::StdRng, SeedableRng};
+use std::fmt;
+
+/// A suggested action together with the reason for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub action: GameAction,
+    pub reason: String
+}
+
+impl Hint {
+    /// The tiles that should be highlighted on the board to show the hint.
+    pub fn tiles(&self, view: &GameView) -> Vec<FieldPos> {
+        match &self.action {
+            GameAction::Move(pos)
+            | GameAction::SpecialMove(pos)
+            | GameAction::Drain(pos)
+            | GameAction::Sandbags { pos, .. }
+            | GameAction::Helicopter { to: pos, .. } => vec![*pos],
+            GameAction::Navigate { player, to } => vec![view.characters()[*player].pos(), *to],
+            GameAction::GiveCard { to, .. } => vec![view.characters()[*to].pos()],
+            GameAction::CollectArtefact => vec![view.character().pos()],
+            _ => Vec::new()
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.reason) }
+}
+
+/// Suggest what the player of the view should do, using the heuristic bot.
+///
+/// # Returns
+/// The hint or `None`, if the player does not have to decide anything right
+/// now.
+pub fn hint(view: &GameView) -> Option<Hint> { hint_with(view, &mut Heuristic) }
+
+/// Suggest what the player of the view should do, according to the bot
+/// provided. Stronger bots give better hints, but might take longer to think.
+pub fn hint_with(view: &GameView, bot: &mut dyn Bot) -> Option<Hint> {
+    if !view.is_deciding() || view.legal_actions().is_empty() {
+        return None;
+    }
+
+    let action = bot.choose_action(view);
+    let reason = explain(view, &action);
+    Some(Hint { action, reason })
+}
+
+/// Explain in a short sentence why the action would be taken.
+pub fn explain(view: &GameView, action: &GameAction) -> String {
+    let adventurer = view.character().adventurer_type();
+    match action {
+        GameAction::LiftOff { .. } => {
+            "lift off with the helicopter, all artefacts have been collected".to_string()
+        }
+        GameAction::CollectArtefact => format!(
+            "collect the {:?} artefact with your {} cards",
+            view.collectable_artefact().unwrap(),
+            CARDS_PER_ARTEFACT
+        ),
+        GameAction::Drain(pos) => {
+            let mut reason = format!("drain {}, {}", tile_name(view, *pos), why_keep(view, *pos));
+            if adventurer == AdventurerType::Engineer {
+                reason.push_str(&engineer_drain(view, action));
+            }
+            reason
+        }
+        GameAction::Sandbags { pos, .. } => format!(
+            "use sandbags on {}, {}",
+            tile_name(view, *pos),
+            why_keep(view, *pos)
+        ),
+        GameAction::Move(to) => {
+            format!(
+                "move to {}{}",
+                tile_name(view, *to),
+                why_go(view, view.player())
+            )
+        }
+        GameAction::SpecialMove(to) => format!(
+            "{} to {}{}",
+            special_move_verb(adventurer),
+            tile_name(view, *to),
+            why_go(view, view.player())
+        ),
+        GameAction::Navigate { player, to } => format!(
+            "push the {:?} to {}{}, the Navigator can move others two tiles for one action",
+            view.characters()[*player].adventurer_type(),
+            tile_name(view, *to),
+            why_go(view, *player)
+        ),
+        GameAction::GiveCard { to, card } => {
+            let receiver = &view.characters()[*to];
+            let mut reason = format!(
+                "give {} to the {:?}",
+                card_name(*card),
+                receiver.adventurer_type()
+            );
+            if let TreasureCard::Artefact(artefact) = card {
+                reason.push_str(&format!(
+                    ", who then has {} of {} {:?} cards",
+                    heuristic::count(receiver.hand(), *artefact) + 1,
+                    CARDS_PER_ARTEFACT,
+                    artefact
+                ));
+            }
+            reason
+        }
+        GameAction::Discard { card, .. } => {
+            format!("discard {}, you will miss it the least", card_name(*card))
+        }
+        GameAction::Helicopter { to, .. } => format!(
+            "fly to {} with the helicopter to leave the island",
+            tile_name(view, *to)
+        ),
+        GameAction::EndActions => "nothing else helps right now, end your actions".to_string(),
+        GameAction::DrawTreasureCard => "draw your treasure cards".to_string(),
+        GameAction::DrawFloodCard => "draw the flood cards".to_string()
+    }
+}
+
+fn tile_name(view: &GameView, pos: FieldPos) -> String {
+    match view.map().get(pos) {
+        Some(Some(tile)) => tile.info().name().to_string(),
+        _ => format!("{}/{}", pos.x, pos.y)
+    }
+}
+
+fn card_name(card: TreasureCard) -> String {
+    match card {
+        TreasureCard::Artefact(artefact) => format!("a {:?} card", artefact),
+        other => format!("the {:?} card", other)
+    }
+}
+
+fn special_move_verb(adventurer: AdventurerType) -> &'static str {
+    match adventurer {
+        AdventurerType::Pilot => "fly",
+        AdventurerType::Diver => "swim",
+        AdventurerType::Explorer => "move diagonally",
+        _ => "move"
+    }
+}
+
+// Why the tile at the position should not sink.
+fn why_keep(view: &GameView, pos: FieldPos) -> String {
+    let info = match view.map().get(pos) {
+        Some(Some(tile)) => *tile.info(),
+        _ => return "before it sinks".to_string()
+    };
+
+    if info == IslandTileInfo::FoolsLanding {
+        return "without it nobody can leave the island".to_string();
+    }
+
+    match info.hidden_artefact() {
+        Some(artefact) if !view.artefacts().contains(&artefact) => {
+            if is_last_tile(view, info, artefact) {
+                format!("it is the last {:?} tile", artefact)
+            }
+            else {
+                format!("the {:?} artefact can be collected there", artefact)
+            }
+        }
+        _ => "before it sinks".to_string()
+    }
+}
+
+fn is_last_tile(view: &GameView, info: IslandTileInfo, artefact: ArtefactType) -> bool {
+    !IslandTileInfo::ALL.iter().any(|&other| {
+        other != info
+            && other.hidden_artefact() == Some(artefact)
+            && view
+                .tile_pos(other)
+                .map_or(false, |pos| view.map().is_standable(pos))
+    })
+}
+
+// Why the adventurer of the player should go somewhere.
+fn why_go(view: &GameView, player: usize) -> String {
+    match heuristic::goal(view, player) {
+        Some(Goal {
+            purpose: Purpose::LeaveIsland,
+            ..
+        }) => ", everyone has to gather on Fools' Landing to leave".to_string(),
+        Some(Goal {
+            purpose: Purpose::Collect(artefact),
+            ..
+        }) => format!(", towards the {:?} artefact", artefact),
+        Some(Goal {
+            purpose: Purpose::Meet { player, artefact },
+            ..
+        }) => format!(
+            ", towards the {:?} to trade {:?} cards",
+            view.characters()[player].adventurer_type(),
+            artefact
+        ),
+        Some(Goal {
+            purpose: Purpose::Drain,
+            ..
+        }) => ", closer to the tiles that need to be drained".to_string(),
+        None => String::new()
+    }
+}
+
+// The Engineer drains two tiles for one action. Explain which one of the two
+// the drain is.
+fn engineer_drain(view: &GameView, action: &GameAction) -> String {
+    let mut after = view.determinise(&mut StdRng::seed_from_u64(0));
+    let before = after.action_state();
+    if after.apply(action).is_err() {
+        return String::new();
+    }
+
+    if after.action_state() == before {
+        "; this is the Engineer's second drain, so it is free".to_string()
+    }
+    else if after.legal_actions().iter().any(|a| match a {
+        GameAction::Drain(_) => true,
+        _ => false
+    }) {
+        "; the Engineer can drain another tile for the same action".to_string()
+    }
+    else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::game_state::GameState;
+    use crate::map::{generate, IslandTileState};
+
+    fn game(adventurers: &[AdventurerType]) -> GameState {
+        let map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(5));
+        let mut state = GameState::with_seed(map, adventurers, Difficulty::Normal, 5);
+        let positions: Vec<FieldPos> = state.map().positions().collect();
+        for pos in positions {
+            state.set_tile_state(pos, IslandTileState::Dry);
+        }
+        state
+    }
+
+    #[test]
+    fn keep_fools_landing_dry() {
+        let mut state = game(&[AdventurerType::Pilot]);
+        let fools_landing = state.tile_pos(IslandTileInfo::FoolsLanding).unwrap();
+        state.set_tile_state(fools_landing, IslandTileState::Flooded);
+
+        let view = GameView::new(&state, 0);
+        let hint = hint(&view).unwrap();
+        assert_eq!(GameAction::Drain(fools_landing), hint.action);
+        assert_eq!(
+            "drain Fools' Landing, without it nobody can leave the island",
+            hint.reason
+        );
+        assert_eq!(vec![fools_landing], hint.tiles(&view));
+    }
+
+    #[test]
+    fn last_artefact_tile() {
+        let mut state = game(&[AdventurerType::Pilot]);
+        let sun = state.tile_pos(IslandTileInfo::TempleOfTheSun).unwrap();
+        let moon = state.tile_pos(IslandTileInfo::TempleOfTheMoon).unwrap();
+        state.set_tile_state(sun, IslandTileState::Gone);
+        state.set_tile_state(moon, IslandTileState::Flooded);
+
+        let view = GameView::new(&state, 0);
+        assert_eq!(
+            "drain Temple of the Moon, it is the last Earth tile",
+            explain(&view, &GameAction::Drain(moon))
+        );
+    }
+
+    #[test]
+    fn engineer_double_drain() {
+        let mut state = game(&[AdventurerType::Engineer]);
+        let pos = state.characters()[0].pos();
+        let flooded: Vec<FieldPos> = state
+            .map()
+            .neighbours(pos)
+            .filter(|(_, tile)| tile.is_some())
+            .map(|(nb, _)| nb)
+            .take(2)
+            .collect();
+        assert_eq!(2, flooded.len());
+        for &nb in &flooded {
+            state.set_tile_state(nb, IslandTileState::Flooded);
+        }
+
+        let reason = explain(&GameView::new(&state, 0), &GameAction::Drain(flooded[0]));
+        assert!(reason.ends_with("the Engineer can drain another tile for the same action"));
+
+        state.apply(&GameAction::Drain(flooded[0])).unwrap();
+        let reason = explain(&GameView::new(&state, 0), &GameAction::Drain(flooded[1]));
+        assert!(reason.ends_with("this is the Engineer's second drain, so it is free"));
+    }
+
+    #[test]
+    fn no_hint_for_waiting_players() {
+        let state = game(&[AdventurerType::Pilot, AdventurerType::Diver]);
+
+        assert!(hint(&GameView::new(&state, 1)).is_none());
+        assert!(hint(&GameView::new(&state, 0)).is_some());
+    }
+}
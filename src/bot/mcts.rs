@@ -0,0 +1,393 @@
+//! Bot that searches for good actions with Monte Carlo tree search.
+//!
+//! The order of the cards in the decks is hidden, so every iteration of the
+//! search plays on a different determinisation of the game, in which the
+//! unseen cards have been shuffled. The tree is shared between all of them and
+//! its nodes are identified by the actions leading to them. Because an action
+//! might not be possible in every determinisation, the exploration of a node
+//! depends on how often it was available instead of how often its parent was
+//! visited.
+
+use super::{Bot, Heuristic};
+use crate::artefact_type::ArtefactType;
+use crate::game_state::{GameAction, GameState, GameView, Outcome};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+/// How long the bot may think about a single action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Budget {
+    /// Run the number of iterations of the search.
+    Iterations(u32),
+    /// Search until the time is up. At least one iteration is run.
+    Time(Duration)
+}
+
+impl FromStr for Budget {
+    type Err = String;
+
+    /// Parse a number of iterations like `500` or a time in milliseconds or
+    /// seconds like `250ms` or `2s`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || format!("Invalid budget: {}", s);
+        if s.ends_with("ms") {
+            let millis = s[..s.len() - 2].parse().map_err(|_| invalid())?;
+            Ok(Budget::Time(Duration::from_millis(millis)))
+        }
+        else if s.ends_with('s') {
+            let secs = s[..s.len() - 1].parse().map_err(|_| invalid())?;
+            Ok(Budget::Time(Duration::from_secs(secs)))
+        }
+        else {
+            s.parse().map(Budget::Iterations).map_err(|_| invalid())
+        }
+    }
+}
+
+/// Monte Carlo tree search bot.
+pub struct Mcts {
+    budget: Budget,
+    exploration: f64,
+    rollout_depth: u32,
+    rng: StdRng
+}
+
+impl Mcts {
+    /// The exploration constant used unless set otherwise.
+    pub const DEFAULT_EXPLORATION: f64 = 0.7;
+    /// The number of actions played in a rollout unless set otherwise.
+    pub const DEFAULT_ROLLOUT_DEPTH: u32 = 100;
+
+    pub fn new(budget: Budget) -> Self { Self::with_rng(budget, StdRng::from_entropy()) }
+
+    /// Create a bot which always searches the same way for the same situation.
+    pub fn with_seed(budget: Budget, seed: u64) -> Self {
+        Self::with_rng(budget, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(budget: Budget, rng: StdRng) -> Self {
+        Self {
+            budget,
+            exploration: Self::DEFAULT_EXPLORATION,
+            rollout_depth: Self::DEFAULT_ROLLOUT_DEPTH,
+            rng
+        }
+    }
+
+    /// Set how much the search prefers trying out rarely visited actions over
+    /// looking deeper into the ones that have been good so far.
+    pub fn exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Set how many actions are played at most after leaving the tree before
+    /// the game is rated.
+    pub fn rollout_depth(mut self, rollout_depth: u32) -> Self {
+        self.rollout_depth = rollout_depth;
+        self
+    }
+
+    pub fn budget(&self) -> Budget { self.budget }
+
+    // One iteration of the search: select and expand a node on a new
+    // determinisation, play the game from there and propagate the result back.
+    fn iterate(&mut self, view: &GameView, tree: &mut Vec<Node>) {
+        let mut state = view.determinise(&mut self.rng);
+        let mut path = vec![0];
+        let mut node = 0;
+
+        while !state.is_over() {
+            let actions = candidate_actions(&GameView::new(&state, state.deciding_player()));
+
+            // Everything that can be done in this determinisation counts as
+            // available, whether it is chosen or not.
+            let mut untried = Vec::new();
+            for action in &actions {
+                let existing = tree[node]
+                    .children
+                    .iter()
+                    .cloned()
+                    .find(|&child| tree[child].action.as_ref() == Some(action));
+                match existing {
+                    Some(child) => tree[child].availability += 1,
+                    None => untried.push(action)
+                }
+            }
+
+            if let Some(&action) = untried.choose(&mut self.rng) {
+                let child = tree.len();
+                tree.push(Node::new(Some(action.clone())));
+                tree[child].availability = 1;
+                tree[node].children.push(child);
+                state.apply(action).unwrap();
+                path.push(child);
+                break;
+            }
+
+            let exploration = self.exploration;
+            let child = tree[node]
+                .children
+                .iter()
+                .cloned()
+                .filter(|&child| actions.contains(tree[child].action.as_ref().unwrap()))
+                .max_by(|&a, &b| {
+                    tree[a]
+                        .ucb(exploration)
+                        .partial_cmp(&tree[b].ucb(exploration))
+                        .unwrap()
+                })
+                .expect("No action is available, but the game is not over");
+            state.apply(tree[child].action.as_ref().unwrap()).unwrap();
+            path.push(child);
+            node = child;
+        }
+
+        let reward = self.rollout(state, view.turn());
+        for node in path {
+            tree[node].visits += 1;
+            tree[node].reward += reward;
+        }
+    }
+
+    // Play the game with a mostly heuristic policy and rate the result.
+    fn rollout(&mut self, mut state: GameState, start_turn: u32) -> f64 {
+        for _ in 0..self.rollout_depth {
+            if state.is_over() {
+                break;
+            }
+
+            let action = if self.rng.gen_bool(0.2) {
+                deciding_actions(&state)
+                    .choose(&mut self.rng)
+                    .unwrap()
+                    .clone()
+            }
+            else {
+                let player = state.deciding_player();
+                Heuristic.choose_action(&GameView::new(&state, player))
+            };
+            state.apply(&action).unwrap();
+        }
+
+        evaluate(&state, start_turn)
+    }
+}
+
+impl Bot for Mcts {
+    fn choose_action(&mut self, view: &GameView) -> GameAction {
+        let actions = candidate_actions(view);
+        assert!(
+            !actions.is_empty(),
+            "The bot has been asked for an action, but there is nothing it can do"
+        );
+        if actions.len() == 1 {
+            return actions[0].clone();
+        }
+
+        let mut tree = vec![Node::new(None)];
+        match self.budget {
+            Budget::Iterations(iterations) => {
+                for _ in 0..iterations.max(1) {
+                    self.iterate(view, &mut tree);
+                }
+            }
+            Budget::Time(time) => {
+                let start = Instant::now();
+                loop {
+                    self.iterate(view, &mut tree);
+                    if start.elapsed() >= time {
+                        break;
+                    }
+                }
+            }
+        }
+
+        // The action that has been visited the most is the most robust one,
+        // since its result has been confirmed by the most determinisations.
+        tree[0]
+            .children
+            .iter()
+            .map(|&child| &tree[child])
+            .filter(|node| actions.contains(node.action.as_ref().unwrap()))
+            .max_by_key(|node| node.visits)
+            .and_then(|node| node.action.clone())
+            .unwrap_or_else(|| actions[0].clone())
+    }
+}
+
+struct Node {
+    action: Option<GameAction>,
+    children: Vec<usize>,
+    visits: u32,
+    availability: u32,
+    reward: f64
+}
+
+impl Node {
+    fn new(action: Option<GameAction>) -> Self {
+        Self {
+            action,
+            children: Vec::new(),
+            visits: 0,
+            availability: 0,
+            reward: 0.
+        }
+    }
+
+    // Upper confidence bound of the reward of the node.
+    fn ucb(&self, exploration: f64) -> f64 {
+        let visits = f64::from(self.visits.max(1));
+        self.reward / visits
+            + exploration * (f64::from(self.availability.max(1)).ln() / visits).sqrt()
+    }
+}
+
+// The actions the player who is deciding right now can take.
+fn deciding_actions(state: &GameState) -> Vec<GameAction> {
+    GameView::new(state, state.deciding_player()).legal_actions()
+}
+
+// The actions worth searching. Actions the heuristic advises against, like
+// flying around with helicopters for no reason, would make the tree too wide to
+// search through. If the heuristic does not like anything, everything is tried.
+fn candidate_actions(view: &GameView) -> Vec<GameAction> {
+    let actions = view.legal_actions();
+    let candidates: Vec<GameAction> = actions
+        .iter()
+        .filter(|action| Heuristic::score(view, &actions, action) >= 0)
+        .cloned()
+        .collect();
+
+    if candidates.is_empty() {
+        actions
+    }
+    else {
+        candidates
+    }
+}
+
+// Rate how good the state is for the adventurers between 0 and 1. Games that
+// are not over yet or have been lost are rated by the collected artefacts and
+// how long the island has been kept afloat, so the search still has something
+// to go by if it never finds a win.
+fn evaluate(state: &GameState, start_turn: u32) -> f64 {
+    if state.outcome() == Some(Outcome::Won) {
+        return 1.;
+    }
+
+    let progress = state.artefacts().len() as f64 / ArtefactType::ALL.len() as f64;
+    let survived = f64::from(state.turn() - start_turn) / f64::from(SURVIVAL_TURNS);
+    let alive = if state.is_over() { 0. } else { 0.1 };
+    0.5 * progress + 0.3 * survived.min(1.) + alive
+}
+
+// The amount of turns after which surviving longer does not count anymore.
+const SURVIVAL_TURNS: u32 = 10;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::difficulty::Difficulty;
+    use crate::game_state::DebugCommand;
+    use crate::map::generate;
+    use crate::treasure_card::TreasureCard;
+
+    fn classic_game(seed: u64) -> GameState {
+        let map = generate::filled_island_with(
+            &generate::classic_shape(),
+            &mut StdRng::seed_from_u64(seed)
+        );
+        GameState::with_seed(
+            map,
+            &[AdventurerType::Diver, AdventurerType::Navigator],
+            Difficulty::Normal,
+            seed
+        )
+    }
+
+    #[test]
+    fn chooses_legal_action() {
+        let state = classic_game(1);
+        let view = GameView::new(&state, 0);
+
+        let action = Mcts::with_seed(Budget::Iterations(30), 1)
+            .rollout_depth(20)
+            .choose_action(&view);
+        assert!(view.legal_actions().contains(&action));
+    }
+
+    #[test]
+    fn same_seed_same_action() {
+        let state = classic_game(2);
+        let view = GameView::new(&state, 0);
+
+        let mut first = Mcts::with_seed(Budget::Iterations(30), 7).rollout_depth(20);
+        let mut second = Mcts::with_seed(Budget::Iterations(30), 7).rollout_depth(20);
+        assert_eq!(first.choose_action(&view), second.choose_action(&view));
+    }
+
+    #[test]
+    fn time_budget() {
+        let state = classic_game(3);
+        let view = GameView::new(&state, 0);
+
+        let start = Instant::now();
+        Mcts::with_seed(Budget::Time(Duration::from_millis(50)), 3).choose_action(&view);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn parse_budget() {
+        assert_eq!(Ok(Budget::Iterations(500)), "500".parse());
+        assert_eq!(
+            Ok(Budget::Time(Duration::from_millis(250))),
+            "250ms".parse()
+        );
+        assert_eq!(Ok(Budget::Time(Duration::from_secs(2))), "2s".parse());
+        assert!("fast".parse::<Budget>().is_err());
+    }
+
+    #[test]
+    fn determinisation_keeps_observable_state() {
+        let state = classic_game(4);
+        let view = GameView::new(&state, 0);
+
+        let sampled = view.determinise(&mut StdRng::seed_from_u64(0));
+        assert_eq!(state.map(), sampled.map());
+        assert_eq!(state.legal_actions(), sampled.legal_actions());
+        assert_eq!(
+            state.treasure_cards().draw_stack_size(),
+            sampled.treasure_cards().draw_stack_size()
+        );
+        assert_eq!(
+            state.flood_cards().discard_stack(),
+            sampled.flood_cards().discard_stack()
+        );
+    }
+
+    #[test]
+    fn determinisation_keeps_flood_cards_shuffled_back_on_top() {
+        let mut state = classic_game(4);
+        state
+            .apply_debug(DebugCommand::StackTreasure(TreasureCard::WatersRise))
+            .unwrap();
+        state.apply(&GameAction::EndActions).unwrap();
+        state.apply(&GameAction::DrawTreasureCard).unwrap();
+
+        let known = 6;
+        let top = |state: &GameState| {
+            let deck = state.flood_cards().draw_stack();
+            let mut top = deck[deck.len() - known..].to_vec();
+            top.sort_by_key(|&info| info as usize);
+            top
+        };
+        for seed in 0..10 {
+            let sampled = GameView::new(&state, 0).determinise(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(top(&state), top(&sampled));
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Computer controlled players. They can fill empty seats of a game and are
+//! used by the simulation to play many games without anyone at the keyboard.
+
+pub mod heuristic;
+pub use self::heuristic::*;
+
+pub mod hint;
+pub use self::hint::*;
+
+pub mod mcts;
+pub use self::mcts::*;
+
+pub mod random;
+pub use self::random::*;
+
+use crate::game_state::{GameAction, GameState, GameView};
+
+/// A player that decides on its own what to do.
+pub trait Bot: Send {
+    /// Choose the next action of the player the view belongs to. This is only
+    /// called when [GameView::is_deciding] is true.
+    ///
+    /// # Returns
+    /// One of the [legal actions](GameView::legal_actions) of the view.
+    fn choose_action(&mut self, view: &GameView) -> GameAction;
+}
+
+/// Create the bot with the name provided, or `None` if there is no such bot.
+/// Bots created like this choose their moves the same way for every seed.
+pub fn from_name(name: &str, seed: u64) -> Option<Box<dyn Bot>> {
+    match name.to_lowercase().as_str() {
+        "heuristic" => Some(Box::new(Heuristic)),
+        "mcts" => Some(Box::new(Mcts::with_seed(Budget::Iterations(200), seed))),
+        "random" => Some(Box::new(Random::with_seed(seed))),
+        _ => None
+    }
+}
+
+/// Let the bot of the deciding player choose the next action of the game.
+///
+/// # Panics
+/// If there are fewer bots than players.
+pub fn next_action(state: &GameState, bots: &mut [Box<dyn Bot>]) -> GameAction {
+    let player = state.deciding_player();
+    bots[player].choose_action(&GameView::new(state, player))
+}
@@ -0,0 +1,38 @@
+//! A bot without any strategy, which is useful as a lower bound for the
+//! strength of other bots.
+
+use super::Bot;
+use crate::game_state::{GameAction, GameView};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// Chooses any of the allowed actions at random.
+pub struct Random {
+    rng: StdRng
+}
+
+impl Random {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_entropy()
+        }
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed)
+        }
+    }
+}
+
+impl Default for Random {
+    fn default() -> Self { Self::new() }
+}
+
+impl Bot for Random {
+    fn choose_action(&mut self, view: &GameView) -> GameAction {
+        view.legal_actions()
+            .choose(&mut self.rng)
+            .expect("The bot has been asked for an action, but there is nothing it can do")
+            .clone()
+    }
+}
@@ -1,8 +1,9 @@
 //! Data type used to store the treasure cards and the flood cards respectively.
 
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, thread_rng, Rng};
 
 /// Card Stack to store the draw stack and the discard stack of a card type.
+#[derive(Clone, Debug)]
 pub struct CardStack<T> {
     draw_stack:    Vec<T>,
     discard_stack: Vec<T>
@@ -28,6 +29,9 @@ impl<T> CardStack<T> {
     /// treasure cards.
     pub fn discard_stack(&self) -> &Vec<T> { &self.discard_stack }
 
+    /// Get the cards of the draw stack. The card drawn next is the last one.
+    pub fn draw_stack(&self) -> &Vec<T> { &self.draw_stack }
+
     /// Draw a card from the draw stack and return it. Returns `None`, if there
     /// are no cards on the draw stack left.
     pub fn draw_card(&mut self) -> Option<T> { self.draw_stack.pop() }
@@ -36,16 +40,56 @@ impl<T> CardStack<T> {
     /// throw it away, just drop it or let it go out of scope.
     pub fn discard_card(&mut self, card: T) { self.discard_stack.push(card) }
 
+    /// Put the card on top of the draw stack, so it is the next one drawn.
+    pub fn put_on_top(&mut self, card: T) { self.draw_stack.push(card) }
+
+    /// Take the card out of the draw stack or, if it is not in there, out of
+    /// the discard pile. Returns `false` if it is in neither of them.
+    pub fn remove(&mut self, card: &T) -> bool
+    where
+        T: PartialEq
+    {
+        for stack in &mut [&mut self.draw_stack, &mut self.discard_stack] {
+            if let Some(index) = stack.iter().position(|c| c == card) {
+                stack.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
     /// Shuffle the draw stack. The discard pile is not touched.
-    pub fn shuffle(&mut self) { self.draw_stack.shuffle(&mut thread_rng()); }
+    pub fn shuffle(&mut self) { self.shuffle_with(&mut thread_rng()); }
+
+    /// Shuffle the draw stack with the random number generator provided. The
+    /// discard pile is not touched.
+    pub fn shuffle_with<R: Rng + ?Sized>(&mut self, rng: &mut R) { self.draw_stack.shuffle(rng); }
 
     /// Shuffle the discard pile and put it on top of the draw stack. The draw
     /// stack does not get shuffled.
-    pub fn shuffle_back(&mut self) {
-        self.discard_stack.shuffle(&mut thread_rng());
+    pub fn shuffle_back(&mut self) { self.shuffle_back_with(&mut thread_rng()); }
+
+    /// Shuffle the discard pile with the random number generator provided and
+    /// put it on top of the draw stack. The draw stack does not get shuffled.
+    pub fn shuffle_back_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.discard_stack.shuffle(rng);
         self.draw_stack.append(&mut self.discard_stack);
     }
 
+    /// Shuffle the cards on top of the draw stack among themselves and the
+    /// ones below them among themselves, so the top cards stay on top.
+    ///
+    /// # Parameters
+    /// `top` - The amount of cards on top of the draw stack. If there are
+    /// fewer cards, all of them are shuffled together.
+    /// `rng` - The random number generator to shuffle with
+    pub fn shuffle_split_with<R: Rng + ?Sized>(&mut self, top: usize, rng: &mut R) {
+        let split = self.draw_stack.len().saturating_sub(top);
+        let (rest, top) = self.draw_stack.split_at_mut(split);
+        rest.shuffle(rng);
+        top.shuffle(rng);
+    }
+
     /// Get the amount of cards that are left on the draw stack.
     pub fn draw_stack_size(&self) -> usize { self.draw_stack.len() }
 
@@ -91,6 +135,21 @@ mod test {
         assert_eq!(&vec![2, 1], stack.discard_stack());
     }
 
+    #[test]
+    fn stack_card() {
+        let mut stack = CardStack::new(vec![1, 2, 3]);
+        stack.discard_card(4);
+
+        assert!(stack.remove(&4));
+        stack.put_on_top(4);
+        assert!(stack.remove(&1));
+        stack.put_on_top(1);
+        assert!(!stack.remove(&5));
+
+        assert_eq!(&vec![2, 3, 4, 1], stack.draw_stack());
+        assert_eq!(0, stack.discard_stack_size());
+    }
+
     #[test]
     fn shuffle() {
         // Shuffle can't be tested well, but it can at least be checked, if the
@@ -101,6 +160,17 @@ mod test {
         assert_eq!(0, stack.discard_stack_size());
     }
 
+    #[test]
+    fn shuffle_split() {
+        let mut stack = CardStack::new(vec![1, 2, 3, 4, 5, 6]);
+        stack.shuffle_split_with(2, &mut rand::thread_rng());
+
+        let mut top = stack.draw_stack()[4..].to_vec();
+        top.sort();
+        assert_eq!(vec![5, 6], top);
+        assert_eq!(6, stack.draw_stack_size());
+    }
+
     #[test]
     fn shuffle_back() {
         // Shuffle can't be tested well, but it can at least be checked, if the
@@ -6,7 +6,9 @@ use amethyst::{
 };
 
 use crate::adventurer::{Adventurer, AdventurerType};
-use crate::map::Full as MapFull;
+use crate::map::{FieldPos, Full as MapFull};
+use crate::positionable::Positionable;
+use crate::treasure_card::TreasureCard;
 use nalgebra::Vector3;
 use std::ops::{Deref, DerefMut};
 
@@ -18,20 +20,29 @@ pub const SPRITE_SCALE: f32 = 0.32;
 /// [Transform](amethyst::core::Transform) - The position of the character on
 /// the screen. Automatically updated by the
 /// [CharacterTransformUpdate](crate::system::CharacterTransformUpdate) system.
-#[derive(Component)]
+#[derive(Clone, Component)]
 pub struct Character {
     internal_handler: Box<dyn Adventurer>,
-    adventurer_type:  AdventurerType
+    adventurer_type: AdventurerType,
+    hand: Vec<TreasureCard>
 }
 
 impl Character {
     pub fn new(adventurer_type: AdventurerType) -> Self {
         Self {
             internal_handler: adventurer_type.create(),
-            adventurer_type
+            adventurer_type,
+            hand: Vec::new()
         }
     }
 
+    pub fn adventurer_type(&self) -> AdventurerType { self.adventurer_type }
+
+    /// The treasure cards the player of this character currently holds.
+    pub fn hand(&self) -> &Vec<TreasureCard> { &self.hand }
+
+    pub fn hand_mut(&mut self) -> &mut Vec<TreasureCard> { &mut self.hand }
+
     pub fn spawn_entity(
         mut self,
         map: &MapFull,
@@ -62,6 +73,12 @@ impl Character {
     }
 }
 
+impl Positionable for Character {
+    fn pos(&self) -> FieldPos { self.internal_handler.pos() }
+
+    fn set_pos(&mut self, pos: FieldPos) { self.internal_handler.set_pos(pos) }
+}
+
 impl Deref for Character {
     type Target = dyn Adventurer;
 
@@ -1,6 +1,27 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Difficulty {
     Novice,
     Normal,
     Elite,
     Legendary
 }
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [Self::Novice, Self::Normal, Self::Elite, Self::Legendary];
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    /// Parse the difficulty from its name, ignoring the case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|d| format!("{:?}", d).eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("Unknown difficulty: {}", s))
+    }
+}
@@ -0,0 +1,61 @@
+//! Everything a player can do in the game is expressed as a [GameAction]. Which
+//! actions are allowed at any point is decided by the
+//! [GameState](super::GameState).
+
+use crate::map::FieldPos;
+use crate::treasure_card::TreasureCard;
+use serde::{Deserialize, Serialize};
+
+/// A single decision of a player. Players are referenced by their index in the
+/// turn order of the game state.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameAction {
+    /// Move the active adventurer to a neighbouring tile.
+    Move(FieldPos),
+    /// Move the active adventurer with their special ability, e.g. swimming as
+    /// the Diver or flying as the Pilot.
+    SpecialMove(FieldPos),
+    /// Drain a flooded tile.
+    Drain(FieldPos),
+    /// Move another adventurer one tile. Only the Navigator can do this.
+    Navigate { player: usize, to: FieldPos },
+    /// Give an artefact card of the active adventurer to another player.
+    GiveCard { to: usize, card: TreasureCard },
+    /// Hand in the artefact cards to collect the artefact of the tile the
+    /// active adventurer is standing on.
+    CollectArtefact,
+    /// Stop taking actions for this turn and start drawing cards.
+    EndActions,
+    /// Draw the next card from the treasure deck.
+    DrawTreasureCard,
+    /// Draw the next card from the flood deck.
+    DrawFloodCard,
+    /// Throw away a card, because the player holds more cards than allowed.
+    Discard { player: usize, card: TreasureCard },
+    /// Play sandbags to drain any flooded tile.
+    Sandbags { player: usize, pos: FieldPos },
+    /// Play a helicopter card to fly the passengers, who must all be standing
+    /// on the same tile, to another tile.
+    Helicopter {
+        player: usize,
+        passengers: Vec<usize>,
+        to: FieldPos
+    },
+    /// Play a helicopter card to leave the island with all artefacts, which
+    /// wins the game.
+    LiftOff { player: usize }
+}
+
+impl GameAction {
+    /// The player who takes the action. Most actions can only be taken by the
+    /// active player, but cards can be played or discarded by anyone.
+    pub fn player(&self, active: usize) -> usize {
+        match self {
+            Self::Discard { player, .. }
+            | Self::Sandbags { player, .. }
+            | Self::Helicopter { player, .. }
+            | Self::LiftOff { player } => *player,
+            _ => active
+        }
+    }
+}
@@ -0,0 +1,327 @@
+//! Commands that change a game without following the rules. The developer
+//! tools use them to get to a position quickly, for example to reproduce a bug
+//! report about an adventurer without playing the turns leading up to it.
+
+use super::{GameSnapshot, GameState, LossReason, Outcome};
+use crate::map::{IslandTileInfo, IslandTileState, MapExt};
+use crate::positionable::Positionable;
+use crate::treasure_card::TreasureCard;
+use crate::water_level::{WaterLevel, LOOSING_WATER_LEVEL};
+use serde::Serialize;
+use std::str::FromStr;
+
+/// The commands the developer console understands, one per line.
+pub const DEBUG_COMMANDS: [&str; 6] = [
+    "tile TILE dry|flooded|gone",
+    "teleport PLAYER TILE",
+    "give PLAYER CARD",
+    "stack treasure CARD",
+    "stack flood TILE",
+    "water LEVEL"
+];
+
+/// A change to the game that does not have to be allowed by the rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugCommand {
+    /// Set the state of the tile. The flood card of a tile that sinks is
+    /// removed from the game, and put on the discard pile again if the tile is
+    /// raised back up.
+    SetTile(IslandTileInfo, IslandTileState),
+    /// Put the character of the player onto the tile.
+    Teleport {
+        player: usize,
+        tile:   IslandTileInfo
+    },
+    /// Move the card from the treasure deck into the hand of the player.
+    Give {
+        player: usize,
+        card:   TreasureCard
+    },
+    /// Put the treasure card on top of the deck, so it is drawn next.
+    StackTreasure(TreasureCard),
+    /// Put the flood card on top of the deck, so it is drawn next.
+    StackFlood(IslandTileInfo),
+    SetWaterLevel(u8)
+}
+
+impl FromStr for DebugCommand {
+    type Err = String;
+
+    /// Parse a line typed into the developer console. The players are counted
+    /// from one, like on the screen.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let player = |word: &str| -> Result<usize, String> {
+            match word.parse::<usize>() {
+                Ok(player) if player > 0 => Ok(player - 1),
+                _ => Err(format!("Unknown player: {}", word))
+            }
+        };
+
+        match words.as_slice() {
+            ["tile", tile, state] => Ok(Self::SetTile(tile.parse()?, state.parse()?)),
+            ["teleport", p, tile] => Ok(Self::Teleport {
+                player: player(p)?,
+                tile:   tile.parse()?
+            }),
+            ["give", p, card] => Ok(Self::Give {
+                player: player(p)?,
+                card:   card.parse()?
+            }),
+            ["stack", "treasure", card] => Ok(Self::StackTreasure(card.parse()?)),
+            ["stack", "flood", tile] => Ok(Self::StackFlood(tile.parse()?)),
+            ["water", level] => level
+                .parse()
+                .map(Self::SetWaterLevel)
+                .map_err(|_| format!("Invalid water level: {}", level)),
+            _ => Err(format!("Unknown command: {}", s.trim()))
+        }
+    }
+}
+
+/// Everything about a game that can be written down, including the order of
+/// the cards in the decks.
+#[derive(Clone, Debug, Serialize)]
+pub struct StateDump {
+    #[serde(flatten)]
+    pub snapshot:      GameSnapshot,
+    /// The treasure cards drawn next, the first one on top.
+    pub treasure_deck: Vec<TreasureCard>,
+    /// The flood cards drawn next, the first one on top.
+    pub flood_deck:    Vec<IslandTileInfo>
+}
+
+impl GameState {
+    /// Change the game as the command says, even if the rules would not allow
+    /// it. If the game is lost afterwards, for example because a tile sank or
+    /// the water has reached the skull, it ends right away.
+    ///
+    /// # Returns
+    /// Why the command can not be carried out, if it can't.
+    pub fn apply_debug(&mut self, command: DebugCommand) -> Result<(), String> {
+        let tile_pos = |state: &Self, info: IslandTileInfo| {
+            state
+                .tile_pos(info)
+                .ok_or_else(|| format!("{} is not on the map", info.name()))
+        };
+        let check_player = |state: &Self, player: usize| {
+            if player < state.characters.len() {
+                Ok(player)
+            }
+            else {
+                Err(format!("There is no player {}", player + 1))
+            }
+        };
+
+        let mut events = Vec::new();
+        match command {
+            DebugCommand::SetTile(info, tile_state) => {
+                let pos = tile_pos(self, info)?;
+                let before = self.map.get(pos).unwrap().unwrap().state();
+                self.set_tile_state(pos, tile_state);
+                match (before, tile_state) {
+                    (IslandTileState::Gone, IslandTileState::Gone) => {}
+                    (_, IslandTileState::Gone) => {
+                        self.flood_cards.remove(&info);
+                        self.tile_sunk(pos, &mut events);
+                    }
+                    (IslandTileState::Gone, _) => self.flood_cards.discard_card(info),
+                    _ => {}
+                }
+            }
+            DebugCommand::Teleport { player, tile } => {
+                let player = check_player(self, player)?;
+                let pos = tile_pos(self, tile)?;
+                if !self.map.is_standable(pos) {
+                    return Err(format!("{} has sunk", tile.name()));
+                }
+                self.characters[player].set_pos(pos);
+            }
+            DebugCommand::Give { player, card } => {
+                let player = check_player(self, player)?;
+                if !self.treasure_cards.remove(&card) {
+                    return Err(format!("There is no {:?} card left in the deck", card));
+                }
+                self.characters[player].hand_mut().push(card);
+            }
+            DebugCommand::StackTreasure(card) => {
+                if !self.treasure_cards.remove(&card) {
+                    return Err(format!("There is no {:?} card left in the deck", card));
+                }
+                self.treasure_cards.put_on_top(card);
+            }
+            DebugCommand::StackFlood(info) => {
+                if !self.flood_cards.remove(&info) {
+                    return Err(format!(
+                        "The flood card of {} is out of the game",
+                        info.name()
+                    ));
+                }
+                self.flood_cards.put_on_top(info);
+            }
+            DebugCommand::SetWaterLevel(level) => {
+                if level > LOOSING_WATER_LEVEL {
+                    return Err(format!(
+                        "The water level can be at most {}",
+                        LOOSING_WATER_LEVEL
+                    ));
+                }
+                self.water_level = WaterLevel::new(level);
+            }
+        }
+
+        if !self.is_over() {
+            if self.water_level.game_lost() {
+                self.finish(Outcome::Lost(LossReason::WaterLevel), &mut events);
+            }
+            else if let Some(reason) = self.loss_reason() {
+                self.finish(Outcome::Lost(reason), &mut events);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write down everything about the game, so it can be attached to a bug
+    /// report.
+    pub fn dump(&self) -> StateDump {
+        StateDump {
+            snapshot:      self.snapshot(),
+            treasure_deck: self
+                .treasure_cards
+                .draw_stack()
+                .iter()
+                .rev()
+                .copied()
+                .collect(),
+            flood_deck:    self
+                .flood_cards
+                .draw_stack()
+                .iter()
+                .rev()
+                .copied()
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::artefact_type::ArtefactType;
+    use crate::difficulty::Difficulty;
+    use crate::game_state::GameAction;
+    use crate::map::generate;
+
+    fn state() -> GameState {
+        GameState::with_seed(
+            generate::classic_island(),
+            &[AdventurerType::Pilot, AdventurerType::Diver],
+            Difficulty::Novice,
+            7
+        )
+    }
+
+    #[test]
+    fn parse_commands() {
+        assert_eq!(
+            Ok(DebugCommand::SetTile(
+                IslandTileInfo::FoolsLanding,
+                IslandTileState::Flooded
+            )),
+            "tile FoolsLanding flooded".parse()
+        );
+        assert_eq!(
+            Ok(DebugCommand::Give {
+                player: 1,
+                card:   TreasureCard::Artefact(ArtefactType::Fire)
+            }),
+            "give 2 fire".parse()
+        );
+        assert_eq!(Ok(DebugCommand::SetWaterLevel(4)), "water 4".parse());
+        assert!("give 0 fire".parse::<DebugCommand>().is_err());
+        assert!("fly 1 FoolsLanding".parse::<DebugCommand>().is_err());
+    }
+
+    #[test]
+    fn stacked_cards_are_drawn_next() {
+        let mut state = state();
+        let cards = state.treasure_cards().size();
+        state
+            .apply_debug(DebugCommand::StackTreasure(TreasureCard::Helicopter))
+            .unwrap();
+        state
+            .apply_debug(DebugCommand::StackFlood(IslandTileInfo::TempleOfTheSun))
+            .unwrap();
+        assert_eq!(cards, state.treasure_cards().size());
+
+        let dump = state.dump();
+        assert_eq!(TreasureCard::Helicopter, dump.treasure_deck[0]);
+        assert_eq!(IslandTileInfo::TempleOfTheSun, dump.flood_deck[0]);
+
+        state.apply(&GameAction::EndActions).unwrap();
+        state.apply(&GameAction::DrawTreasureCard).unwrap();
+        assert_eq!(
+            Some(&TreasureCard::Helicopter),
+            state.characters()[0].hand().last()
+        );
+    }
+
+    #[test]
+    fn give_and_teleport() {
+        let mut state = state();
+        let before = state.characters()[1].hand().len();
+        state
+            .apply_debug(DebugCommand::Give {
+                player: 1,
+                card:   TreasureCard::Sandbags
+            })
+            .unwrap();
+        assert_eq!(before + 1, state.characters()[1].hand().len());
+        assert!(state
+            .apply_debug(DebugCommand::Give {
+                player: 5,
+                card:   TreasureCard::Sandbags
+            })
+            .is_err());
+
+        state
+            .apply_debug(DebugCommand::Teleport {
+                player: 0,
+                tile:   IslandTileInfo::CoralPalace
+            })
+            .unwrap();
+        assert_eq!(
+            state.tile_pos(IslandTileInfo::CoralPalace),
+            Some(state.characters()[0].pos())
+        );
+    }
+
+    #[test]
+    fn sinking_can_lose_the_game() {
+        let mut state = state();
+        state
+            .apply_debug(DebugCommand::SetTile(
+                IslandTileInfo::FoolsLanding,
+                IslandTileState::Gone
+            ))
+            .unwrap();
+        assert_eq!(
+            Some(Outcome::Lost(LossReason::FoolsLandingSunk)),
+            state.outcome()
+        );
+        let flood_cards = state.flood_cards();
+        assert!(!flood_cards
+            .draw_stack()
+            .iter()
+            .chain(flood_cards.discard_stack())
+            .any(|&info| info == IslandTileInfo::FoolsLanding));
+
+        let mut state = self::state();
+        state
+            .apply_debug(DebugCommand::SetWaterLevel(LOOSING_WATER_LEVEL))
+            .unwrap();
+        assert_eq!(Some(Outcome::Lost(LossReason::WaterLevel)), state.outcome());
+    }
+}
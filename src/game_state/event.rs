@@ -0,0 +1,60 @@
+//! Events describe what happened when an action was applied to the game state.
+//! Frontends can use them to update their presentation without comparing game
+//! states.
+
+use super::Outcome;
+use crate::artefact_type::ArtefactType;
+use crate::map::FieldPos;
+use crate::treasure_card::TreasureCard;
+use crate::water_level::WaterLevel;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// The player has been moved to the position, no matter by whom.
+    Moved {
+        player: usize,
+        to:     FieldPos
+    },
+    /// The tile of the player sank and they had to move to the position.
+    Rescued {
+        player: usize,
+        to:     FieldPos
+    },
+    /// The tile at the position is dry again.
+    Drained(FieldPos),
+    CardGiven {
+        from: usize,
+        to:   usize,
+        card: TreasureCard
+    },
+    CardDrawn {
+        player: usize,
+        card:   TreasureCard
+    },
+    CardDiscarded {
+        player: usize,
+        card:   TreasureCard
+    },
+    /// A special action card has been played and put on the discard pile.
+    CardPlayed {
+        player: usize,
+        card:   TreasureCard
+    },
+    ArtefactCollected {
+        player:   usize,
+        artefact: ArtefactType
+    },
+    /// The active player does not take any more actions this turn.
+    ActionsEnded {
+        player: usize
+    },
+    /// A waters rise card has been drawn. Contains the new level.
+    WatersRose(WaterLevel),
+    TileFlooded(FieldPos),
+    TileSunk(FieldPos),
+    TurnStarted {
+        player: usize
+    },
+    GameOver(Outcome)
+}
@@ -0,0 +1,1007 @@
+//! The rules of the forbidden island, independent of any frontend. The
+//! [GameState] holds everything that belongs to one game and only changes
+//! through [GameActions](GameAction), which are checked against the rules
+//! before they are applied.
+
+pub mod action;
+pub use self::action::*;
+
+pub mod debug;
+pub use self::debug::*;
+
+pub mod event;
+pub use self::event::*;
+
+pub mod snapshot;
+pub use self::snapshot::*;
+
+pub mod view;
+pub use self::view::*;
+
+use crate::action_state::ActionState;
+use crate::adventurer::{Adventurer, AdventurerInfo, AdventurerType};
+use crate::artefact_type::ArtefactType;
+use crate::card_stack::CardStack;
+use crate::character::Character;
+use crate::difficulty::Difficulty;
+use crate::map::{FieldPos, Full as MapFull, IslandTile, IslandTileInfo, IslandTileState, MapExt};
+use crate::positionable::Positionable;
+use crate::treasure_card::{TreasureCard, CARDS_PER_ARTEFACT, HAND_LIMIT};
+use crate::water_level::WaterLevel;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The amount of actions every adventurer can take per turn.
+pub const ACTIONS_PER_TURN: u8 = 3;
+
+/// The amount of treasure cards that are drawn at the end of every turn.
+pub const TREASURE_CARDS_PER_TURN: u8 = 2;
+
+/// The amount of tiles that are flooded before the first turn.
+pub const INITIAL_FLOODED_TILES: usize = 6;
+
+/// The amount of treasure cards every player starts with.
+pub const INITIAL_HAND_SIZE: usize = 2;
+
+/// How a finished game has ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Outcome {
+    Won,
+    Lost(LossReason)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LossReason {
+    /// Fools' Landing sank, so there is no way off the island anymore.
+    FoolsLandingSunk,
+    /// All tiles of an artefact that has not been collected yet sank.
+    ArtefactSunk(ArtefactType),
+    /// The tile of the adventurer sank and there was nowhere to swim to.
+    Drowned(AdventurerType),
+    /// The water level reached the skull.
+    WaterLevel
+}
+
+/// Reasons why an action could not be applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    /// The game is already over, so nothing can be done anymore.
+    GameOver,
+    /// The action is not allowed in the current state of the game.
+    Illegal
+}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::GameOver => write!(f, "the game is already over"),
+            Self::Illegal => write!(f, "the action is not allowed right now")
+        }
+    }
+}
+
+/// Everything about a game state that decides how the game continues. Two
+/// states with the same key behave the same for every sequence of actions, even
+/// once a deck runs out and is reshuffled, so the key can be used to recognise
+/// states that have been seen before.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StateKey {
+    tiles: Vec<Option<IslandTile>>,
+    characters: Vec<(FieldPos, Vec<TreasureCard>, bool)>,
+    active: usize,
+    action_state: ActionState,
+    treasure_cards: (Vec<TreasureCard>, Vec<TreasureCard>),
+    flood_cards: (Vec<IslandTileInfo>, Vec<IslandTileInfo>),
+    water_level: WaterLevel,
+    artefacts: Vec<ArtefactType>,
+    flight_used: bool,
+    outcome: Option<Outcome>,
+    /// The next number the random number generator would produce, standing in
+    /// for its state, which can not be compared itself.
+    rng: u64
+}
+
+/// Complete state of one game.
+///
+/// The rules differ from the board game in one place: when the tile of an
+/// adventurer sinks, the player is not asked where to swim. The adventurer is
+/// moved to the first of the [rescue positions](GameState::rescue_positions)
+/// right away, so drawing the flood cards never waits for a decision.
+#[derive(Clone)]
+pub struct GameState {
+    map: MapFull,
+    characters: Vec<Character>,
+    active: usize,
+    action_state: ActionState,
+    treasure_cards: CardStack<TreasureCard>,
+    flood_cards: CardStack<IslandTileInfo>,
+    /// The amount of cards on top of the flood deck that have been shuffled
+    /// back when the waters rose. Everyone knows which cards they are, just not
+    /// in which order they come.
+    flood_known: usize,
+    water_level: WaterLevel,
+    artefacts: Vec<ArtefactType>,
+    turn: u32,
+    flight_used: bool,
+    outcome: Option<Outcome>,
+    rng: StdRng
+}
+
+impl GameState {
+    /// Set up a new game on the map for the adventurers provided, who take
+    /// their turns in the order given.
+    ///
+    /// # Panics
+    /// If the map does not contain the spawn tile of one of the adventurers.
+    pub fn new(map: MapFull, adventurers: &[AdventurerType], difficulty: Difficulty) -> Self {
+        Self::with_rng(map, adventurers, difficulty, StdRng::from_entropy())
+    }
+
+    /// Like [GameState::new], but all cards are shuffled with the seed
+    /// provided, so the same seed always results in the same game.
+    pub fn with_seed(
+        map: MapFull,
+        adventurers: &[AdventurerType],
+        difficulty: Difficulty,
+        seed: u64
+    ) -> Self {
+        Self::with_rng(map, adventurers, difficulty, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(
+        map: MapFull,
+        adventurers: &[AdventurerType],
+        difficulty: Difficulty,
+        mut rng: StdRng
+    ) -> Self {
+        let mut treasure_cards = CardStack::new(TreasureCard::standard_deck());
+        treasure_cards.shuffle_with(&mut rng);
+
+        let mut flood_cards = CardStack::new(
+            map.iter()
+                .filter_map(|(_, tile)| tile.map(|tile| *tile.info()))
+                .collect()
+        );
+        flood_cards.shuffle_with(&mut rng);
+
+        let characters = adventurers
+            .iter()
+            .map(|&adventurer_type| {
+                let mut character = Character::new(adventurer_type);
+                character.set_pos(map.spawn_point(adventurer_type));
+                character
+            })
+            .collect();
+
+        let mut state = Self {
+            map,
+            characters,
+            active: 0,
+            action_state: ActionState::PlayerAction(ACTIONS_PER_TURN),
+            treasure_cards,
+            flood_cards,
+            flood_known: 0,
+            water_level: WaterLevel::from_difficulty(difficulty),
+            artefacts: Vec::new(),
+            turn: 0,
+            flight_used: false,
+            outcome: None,
+            rng
+        };
+
+        state.flood_initial_tiles();
+        state.deal_initial_hands();
+
+        state
+    }
+
+    // Flood the first tiles of the flood deck before the first turn.
+    fn flood_initial_tiles(&mut self) {
+        for _ in 0..INITIAL_FLOODED_TILES {
+            if let Some(info) = self.flood_cards.draw_card() {
+                if let Some(pos) = self.tile_pos(info) {
+                    self.set_tile_state(pos, IslandTileState::Flooded);
+                }
+                self.flood_cards.discard_card(info);
+            }
+        }
+    }
+
+    // Deal the starting hands. Waters rise cards can not be dealt, so they are
+    // put aside and shuffled back into the deck afterwards.
+    fn deal_initial_hands(&mut self) {
+        let mut put_aside = Vec::new();
+        for character in &mut self.characters {
+            while character.hand().len() < INITIAL_HAND_SIZE {
+                match self.treasure_cards.draw_card() {
+                    Some(TreasureCard::WatersRise) => put_aside.push(TreasureCard::WatersRise),
+                    Some(card) => character.hand_mut().push(card),
+                    None => break
+                }
+            }
+        }
+
+        for card in put_aside {
+            self.treasure_cards.discard_card(card);
+        }
+        self.treasure_cards.shuffle_back_with(&mut self.rng);
+        self.treasure_cards.shuffle_with(&mut self.rng);
+    }
+
+    pub fn map(&self) -> &MapFull { &self.map }
+
+    /// All characters in the order they take their turns.
+    pub fn characters(&self) -> &[Character] { &self.characters }
+
+    /// Index of the player whose turn it is.
+    pub fn active_player(&self) -> usize { self.active }
+
+    pub fn active_character(&self) -> &Character { &self.characters[self.active] }
+
+    pub fn action_state(&self) -> ActionState { self.action_state }
+
+    pub fn water_level(&self) -> WaterLevel { self.water_level }
+
+    /// The artefacts that have been collected so far.
+    pub fn artefacts(&self) -> &[ArtefactType] { &self.artefacts }
+
+    pub fn treasure_cards(&self) -> &CardStack<TreasureCard> { &self.treasure_cards }
+
+    pub fn flood_cards(&self) -> &CardStack<IslandTileInfo> { &self.flood_cards }
+
+    /// The number of turns that have been completed.
+    pub fn turn(&self) -> u32 { self.turn }
+
+    /// The number of turns that have been played so far, including the one
+    /// that is being played or in which the game has ended. This is the turn
+    /// shown to the players and the one games are recorded with.
+    pub fn turns_played(&self) -> u32 { self.turn + 1 }
+
+    /// The outcome of the game or `None`, if it is still running.
+    pub fn outcome(&self) -> Option<Outcome> { self.outcome }
+
+    pub fn is_over(&self) -> bool { self.outcome.is_some() }
+
+    pub(crate) fn character_mut(&mut self, player: usize) -> &mut Character {
+        &mut self.characters[player]
+    }
+
+    pub(crate) fn artefacts_mut(&mut self) -> &mut Vec<ArtefactType> { &mut self.artefacts }
+
+    pub(crate) fn treasure_cards_mut(&mut self) -> &mut CardStack<TreasureCard> {
+        &mut self.treasure_cards
+    }
+
+    pub(crate) fn flood_cards_mut(&mut self) -> &mut CardStack<IslandTileInfo> {
+        &mut self.flood_cards
+    }
+
+    pub(crate) fn set_water_level(&mut self, water_level: WaterLevel) {
+        self.water_level = water_level;
+    }
+
+    /// The position of the tile with the info provided or `None`, if it is not
+    /// on the map.
+    pub fn tile_pos(&self, info: IslandTileInfo) -> Option<FieldPos> {
+        self.map
+            .iter()
+            .find(|(_, tile)| tile.map_or(false, |tile| *tile.info() == info))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The first player holding more cards than allowed, who must discard
+    /// before the game can continue.
+    pub fn over_hand_limit(&self) -> Option<usize> {
+        self.characters
+            .iter()
+            .position(|character| character.hand().len() > HAND_LIMIT)
+    }
+
+    /// The key identifying this state. The random number generator, which
+    /// shuffles the decks when they run out, is part of it, so states that
+    /// would reshuffle differently have different keys.
+    pub fn key(&self) -> StateKey {
+        StateKey {
+            tiles: self.map.as_slice().to_vec(),
+            characters: self
+                .characters
+                .iter()
+                .map(|c| (c.pos(), c.hand().clone(), c.has_extra_action()))
+                .collect(),
+            active: self.active,
+            action_state: self.action_state,
+            treasure_cards: (
+                self.treasure_cards.draw_stack().clone(),
+                self.treasure_cards.discard_stack().clone()
+            ),
+            flood_cards: (
+                self.flood_cards.draw_stack().clone(),
+                self.flood_cards.discard_stack().clone()
+            ),
+            water_level: self.water_level,
+            artefacts: self.artefacts.clone(),
+            flight_used: self.flight_used,
+            outcome: self.outcome,
+            rng: self.rng.clone().gen()
+        }
+    }
+
+    /// The player who has to decide how the game continues. This is the active
+    /// player, unless someone has to discard cards first.
+    pub fn deciding_player(&self) -> usize { self.over_hand_limit().unwrap_or(self.active) }
+
+    /// The artefact the active player could collect right now, if any.
+    pub fn collectable_artefact(&self) -> Option<ArtefactType> {
+        let character = self.active_character();
+        let artefact = match self.map.get(character.pos()) {
+            Some(Some(tile)) => tile.info().hidden_artefact()?,
+            _ => return None
+        };
+
+        let cards = character
+            .hand()
+            .iter()
+            .filter(|&&card| card == TreasureCard::Artefact(artefact))
+            .count();
+        if !self.artefacts.contains(&artefact) && cards >= CARDS_PER_ARTEFACT {
+            Some(artefact)
+        }
+        else {
+            None
+        }
+    }
+
+    /// Checks if the adventurers could leave the island, meaning all artefacts
+    /// have been collected and everyone is standing on Fools' Landing.
+    pub fn can_lift_off(&self) -> bool {
+        let fools_landing = match self.tile_pos(IslandTileInfo::FoolsLanding) {
+            Some(pos) if self.map.is_standable(pos) => pos,
+            _ => return false
+        };
+
+        self.artefacts.len() == ArtefactType::ALL.len()
+            && self
+                .characters
+                .iter()
+                .all(|character| character.pos() == fools_landing)
+    }
+
+    /// All actions that may be applied in the current state of the game.
+    pub fn legal_actions(&self) -> Vec<GameAction> {
+        let mut actions = Vec::new();
+        if self.is_over() {
+            return actions;
+        }
+
+        // Nothing else can happen until the hand limit is respected again.
+        if let Some(player) = self.over_hand_limit() {
+            for card in distinct(self.characters[player].hand()) {
+                actions.push(GameAction::Discard { player, card });
+            }
+            self.special_card_actions(player, &mut actions);
+            return actions;
+        }
+
+        match self.action_state {
+            ActionState::PlayerAction(points) => self.player_actions(points, &mut actions),
+            ActionState::DrawArtefactCards(_) => actions.push(GameAction::DrawTreasureCard),
+            ActionState::DrawFloodCards(_) => actions.push(GameAction::DrawFloodCard)
+        }
+        for player in 0..self.characters.len() {
+            self.special_card_actions(player, &mut actions);
+        }
+
+        actions
+    }
+
+    // The actions the active player can take with the action points left.
+    fn player_actions(&self, points: u8, actions: &mut Vec<GameAction>) {
+        let character = self.active_character();
+
+        let moves = Adventurer::moves(&**character, &self.map, points);
+        let may_fly = character.adventurer_type() != AdventurerType::Pilot || !self.flight_used;
+        if may_fly {
+            for to in Adventurer::special_moves(&**character, &self.map, points) {
+                if !moves.contains(&to) {
+                    actions.push(GameAction::SpecialMove(to));
+                }
+            }
+        }
+        actions.extend(moves.into_iter().map(GameAction::Move));
+
+        for pos in Adventurer::drains(&**character, &self.map, points) {
+            actions.push(GameAction::Drain(pos));
+        }
+
+        for (player, other) in self.characters.iter().enumerate() {
+            if player == self.active {
+                continue;
+            }
+
+            if character.can_move_other(points) {
+                for to in AdventurerInfo::moves(&**other, &self.map) {
+                    actions.push(GameAction::Navigate { player, to });
+                }
+            }
+
+            if Adventurer::can_trade_with(&**character, other, points) {
+                for card in distinct(character.hand()) {
+                    if card.is_transferable() {
+                        actions.push(GameAction::GiveCard { to: player, card });
+                    }
+                }
+            }
+        }
+
+        if points != 0 && self.collectable_artefact().is_some() {
+            actions.push(GameAction::CollectArtefact);
+        }
+
+        actions.push(GameAction::EndActions);
+    }
+
+    // The special action cards the player can play at this moment.
+    fn special_card_actions(&self, player: usize, actions: &mut Vec<GameAction>) {
+        let hand = self.characters[player].hand();
+
+        if hand.contains(&TreasureCard::Sandbags) {
+            for (pos, tile) in self.map.iter() {
+                if tile.map_or(false, |tile| tile.state() == IslandTileState::Flooded) {
+                    actions.push(GameAction::Sandbags { player, pos });
+                }
+            }
+        }
+
+        if hand.contains(&TreasureCard::Helicopter) {
+            if self.can_lift_off() {
+                actions.push(GameAction::LiftOff { player });
+            }
+
+            // Only players standing on the same tile can fly together.
+            let mut sources: Vec<FieldPos> = self.characters.iter().map(|c| c.pos()).collect();
+            sources.sort();
+            sources.dedup();
+            for source in sources {
+                let on_source: Vec<usize> = (0..self.characters.len())
+                    .filter(|&i| self.characters[i].pos() == source)
+                    .collect();
+
+                for subset in 1..(1_u32 << on_source.len()) {
+                    let passengers: Vec<usize> = on_source
+                        .iter()
+                        .enumerate()
+                        .filter(|(bit, _)| subset & (1 << bit) != 0)
+                        .map(|(_, &i)| i)
+                        .collect();
+
+                    for to in self.map.positions() {
+                        if to != source && self.map.is_standable(to) {
+                            actions.push(GameAction::Helicopter {
+                                player,
+                                passengers: passengers.clone(),
+                                to
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the action to the game, if it is allowed by the rules.
+    ///
+    /// # Returns
+    /// The events that happened because of the action, or the reason why it
+    /// could not be applied. In case of an error, the state is unchanged.
+    pub fn apply(&mut self, action: &GameAction) -> Result<Vec<GameEvent>, ActionError> {
+        if self.is_over() {
+            return Err(ActionError::GameOver);
+        }
+        if !self.legal_actions().contains(action) {
+            return Err(ActionError::Illegal);
+        }
+
+        let mut events = Vec::new();
+        let active = self.active;
+        match *action {
+            GameAction::Move(to) => {
+                let character = &mut self.characters[active];
+                character.set_pos(to);
+                character.on_move();
+                self.spend_action();
+                events.push(GameEvent::Moved { player: active, to });
+            }
+            GameAction::SpecialMove(to) => {
+                let character = &mut self.characters[active];
+                character.set_pos(to);
+                character.on_move();
+                if character.adventurer_type() == AdventurerType::Pilot {
+                    self.flight_used = true;
+                }
+                self.spend_action();
+                events.push(GameEvent::Moved { player: active, to });
+            }
+            GameAction::Drain(pos) => {
+                self.set_tile_state(pos, IslandTileState::Dry);
+                let mut points = self.action_points();
+                self.characters[active].on_drain(&mut points);
+                self.action_state = ActionState::PlayerAction(points);
+                events.push(GameEvent::Drained(pos));
+            }
+            GameAction::Navigate { player, to } => {
+                self.characters[player].set_pos(to);
+                let mut points = self.action_points();
+                self.characters[active].on_move_other(&mut points);
+                self.action_state = ActionState::PlayerAction(points);
+                events.push(GameEvent::Moved { player, to });
+            }
+            GameAction::GiveCard { to, card } => {
+                remove_card(self.characters[active].hand_mut(), card);
+                self.characters[to].hand_mut().push(card);
+                self.spend_action();
+                events.push(GameEvent::CardGiven {
+                    from: active,
+                    to,
+                    card
+                });
+            }
+            GameAction::CollectArtefact => {
+                let artefact = self
+                    .collectable_artefact()
+                    .expect("Legal collect action without collectable artefact");
+                for _ in 0..CARDS_PER_ARTEFACT {
+                    let card = TreasureCard::Artefact(artefact);
+                    remove_card(self.characters[active].hand_mut(), card);
+                    self.treasure_cards.discard_card(card);
+                }
+                self.artefacts.push(artefact);
+                self.spend_action();
+                events.push(GameEvent::ArtefactCollected {
+                    player: active,
+                    artefact
+                });
+            }
+            GameAction::EndActions => {
+                self.characters[active].on_turn_end();
+                self.action_state = ActionState::DrawArtefactCards(TREASURE_CARDS_PER_TURN);
+                events.push(GameEvent::ActionsEnded { player: active });
+            }
+            GameAction::DrawTreasureCard => self.draw_treasure_card(&mut events),
+            GameAction::DrawFloodCard => self.draw_flood_card(&mut events),
+            GameAction::Discard { player, card } => {
+                remove_card(self.characters[player].hand_mut(), card);
+                self.treasure_cards.discard_card(card);
+                events.push(GameEvent::CardDiscarded { player, card });
+            }
+            GameAction::Sandbags { player, pos } => {
+                self.play_card(player, TreasureCard::Sandbags, &mut events);
+                self.set_tile_state(pos, IslandTileState::Dry);
+                events.push(GameEvent::Drained(pos));
+            }
+            GameAction::Helicopter {
+                player,
+                ref passengers,
+                to
+            } => {
+                self.play_card(player, TreasureCard::Helicopter, &mut events);
+                for &passenger in passengers {
+                    self.characters[passenger].set_pos(to);
+                    events.push(GameEvent::Moved {
+                        player: passenger,
+                        to
+                    });
+                }
+            }
+            GameAction::LiftOff { player } => {
+                self.play_card(player, TreasureCard::Helicopter, &mut events);
+                self.finish(Outcome::Won, &mut events);
+            }
+        }
+
+        Ok(events)
+    }
+
+    fn action_points(&self) -> u8 {
+        match self.action_state {
+            ActionState::PlayerAction(points) => points,
+            _ => 0
+        }
+    }
+
+    fn spend_action(&mut self) {
+        if let ActionState::PlayerAction(ref mut points) = self.action_state {
+            *points -= 1;
+        }
+    }
+
+    fn play_card(&mut self, player: usize, card: TreasureCard, events: &mut Vec<GameEvent>) {
+        remove_card(self.characters[player].hand_mut(), card);
+        self.treasure_cards.discard_card(card);
+        events.push(GameEvent::CardPlayed { player, card });
+    }
+
+    pub(crate) fn set_tile_state(&mut self, pos: FieldPos, state: IslandTileState) {
+        if let Some(Some(tile)) = self.map.get_mut(pos) {
+            tile.set_state(state);
+        }
+    }
+
+    fn draw_treasure_card(&mut self, events: &mut Vec<GameEvent>) {
+        if self.treasure_cards.draw_stack_size() == 0 {
+            self.treasure_cards.shuffle_back_with(&mut self.rng);
+        }
+
+        match self.treasure_cards.draw_card() {
+            Some(TreasureCard::WatersRise) => {
+                self.water_level += 1;
+                self.treasure_cards.discard_card(TreasureCard::WatersRise);
+                // The flooded tiles are put on top of the flood deck, so they will be flooded
+                // again soon.
+                self.flood_known = self.flood_cards.discard_stack_size();
+                self.flood_cards.shuffle_back_with(&mut self.rng);
+                events.push(GameEvent::WatersRose(self.water_level));
+
+                if self.water_level.game_lost() {
+                    self.finish(Outcome::Lost(LossReason::WaterLevel), events);
+                    return;
+                }
+            }
+            Some(card) => {
+                self.characters[self.active].hand_mut().push(card);
+                events.push(GameEvent::CardDrawn {
+                    player: self.active,
+                    card
+                });
+            }
+            None => {}
+        }
+
+        self.action_state = match self.action_state {
+            ActionState::DrawArtefactCards(left) if left > 1 => {
+                ActionState::DrawArtefactCards(left - 1)
+            }
+            _ => ActionState::DrawFloodCards(self.water_level.draw_amount())
+        };
+    }
+
+    fn draw_flood_card(&mut self, events: &mut Vec<GameEvent>) {
+        if self.flood_cards.draw_stack_size() == 0 {
+            self.flood_cards.shuffle_back_with(&mut self.rng);
+        }
+
+        if let Some(info) = self.flood_cards.draw_card() {
+            self.flood_known = self.flood_known.saturating_sub(1);
+            if let Some(pos) = self.tile_pos(info) {
+                let state = self.map.get(pos).unwrap().unwrap().state();
+                match state {
+                    IslandTileState::Dry => {
+                        self.set_tile_state(pos, IslandTileState::Flooded);
+                        self.flood_cards.discard_card(info);
+                        events.push(GameEvent::TileFlooded(pos));
+                    }
+                    // The card of a sunk tile is removed from the game.
+                    _ => {
+                        self.set_tile_state(pos, IslandTileState::Gone);
+                        events.push(GameEvent::TileSunk(pos));
+                        self.tile_sunk(pos, events);
+                        if self.is_over() {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+
+        match self.action_state {
+            ActionState::DrawFloodCards(left) if left > 1 => {
+                self.action_state = ActionState::DrawFloodCards(left - 1)
+            }
+            _ => self.next_turn(events)
+        }
+    }
+
+    // Rescue all players from the sunk tile and check, if the game has been lost
+    // because of it.
+    fn tile_sunk(&mut self, pos: FieldPos, events: &mut Vec<GameEvent>) {
+        for player in 0..self.characters.len() {
+            if self.characters[player].pos() != pos {
+                continue;
+            }
+
+            match self.rescue_positions(player).first() {
+                Some(&to) => {
+                    self.characters[player].set_pos(to);
+                    events.push(GameEvent::Rescued { player, to });
+                }
+                None => {
+                    let adventurer_type = self.characters[player].adventurer_type();
+                    self.finish(Outcome::Lost(LossReason::Drowned(adventurer_type)), events);
+                    return;
+                }
+            }
+        }
+
+        if let Some(reason) = self.loss_reason() {
+            self.finish(Outcome::Lost(reason), events);
+        }
+    }
+
+    /// The positions the player could swim to if their tile sank, with the
+    /// dry tiles first. The player is moved to the first one automatically.
+    pub fn rescue_positions(&self, player: usize) -> Vec<FieldPos> {
+        let character = &self.characters[player];
+        let mut positions = AdventurerInfo::moves(&**character, &self.map);
+        for pos in AdventurerInfo::special_moves(&**character, &self.map) {
+            if !positions.contains(&pos) && self.map.is_standable(pos) {
+                positions.push(pos);
+            }
+        }
+
+        let state = |pos: &FieldPos| self.map.get(*pos).unwrap().unwrap().state();
+        positions.sort_by_key(|pos| (state(pos) != IslandTileState::Dry, *pos));
+        positions
+    }
+
+    // Checks if the game can not be won anymore because of the tiles that sank.
+    fn loss_reason(&self) -> Option<LossReason> {
+        let sunk = |info: IslandTileInfo| match self.tile_pos(info) {
+            Some(pos) => !self.map.is_standable(pos),
+            None => false
+        };
+
+        if sunk(IslandTileInfo::FoolsLanding) {
+            return Some(LossReason::FoolsLandingSunk);
+        }
+
+        for &artefact in ArtefactType::ALL.iter() {
+            if self.artefacts.contains(&artefact) {
+                continue;
+            }
+
+            let mut temples = IslandTileInfo::ALL
+                .iter()
+                .filter(|info| info.hidden_artefact() == Some(artefact))
+                .filter(|&&info| self.tile_pos(info).is_some())
+                .peekable();
+            if temples.peek().is_some() && temples.all(|&info| sunk(info)) {
+                return Some(LossReason::ArtefactSunk(artefact));
+            }
+        }
+
+        None
+    }
+
+    fn next_turn(&mut self, events: &mut Vec<GameEvent>) {
+        self.characters[self.active].on_turn_end();
+        self.active = (self.active + 1) % self.characters.len();
+        self.action_state = ActionState::PlayerAction(ACTIONS_PER_TURN);
+        self.flight_used = false;
+        self.turn += 1;
+        events.push(GameEvent::TurnStarted {
+            player: self.active
+        });
+    }
+
+    fn finish(&mut self, outcome: Outcome, events: &mut Vec<GameEvent>) {
+        self.outcome = Some(outcome);
+        events.push(GameEvent::GameOver(outcome));
+    }
+}
+
+// Remove one card of the kind from the hand, if there is one.
+fn remove_card(hand: &mut Vec<TreasureCard>, card: TreasureCard) {
+    if let Some(index) = hand.iter().position(|&c| c == card) {
+        hand.remove(index);
+    }
+}
+
+// The cards of the hand without duplicates, in the order they were received.
+fn distinct(hand: &[TreasureCard]) -> Vec<TreasureCard> {
+    let mut cards = Vec::with_capacity(hand.len());
+    for &card in hand {
+        if !cards.contains(&card) {
+            cards.push(card);
+        }
+    }
+
+    cards
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::generate;
+
+    fn classic_game(adventurers: &[AdventurerType]) -> GameState {
+        let mut rng = StdRng::seed_from_u64(42);
+        let map = generate::filled_island_with(&generate::classic_shape(), &mut rng);
+
+        GameState::with_seed(map, adventurers, Difficulty::Normal, 42)
+    }
+
+    fn flooded_tiles(state: &GameState) -> usize {
+        state
+            .map()
+            .iter()
+            .filter(|(_, tile)| tile.map_or(false, |t| t.state() == IslandTileState::Flooded))
+            .count()
+    }
+
+    #[test]
+    fn setup() {
+        let state = classic_game(&[AdventurerType::Pilot, AdventurerType::Diver]);
+
+        assert_eq!(INITIAL_FLOODED_TILES, flooded_tiles(&state));
+        for character in state.characters() {
+            assert_eq!(INITIAL_HAND_SIZE, character.hand().len());
+            assert!(!character.hand().contains(&TreasureCard::WatersRise));
+            assert_eq!(
+                state.map().spawn_point(character.adventurer_type()),
+                character.pos()
+            );
+        }
+        assert_eq!(28 - 2 * INITIAL_HAND_SIZE, state.treasure_cards().size());
+        assert_eq!(ActionState::PlayerAction(3), state.action_state());
+    }
+
+    #[test]
+    fn illegal_action_does_not_change_state() {
+        let mut state = classic_game(&[AdventurerType::Engineer]);
+
+        assert_eq!(
+            Err(ActionError::Illegal),
+            state.apply(&GameAction::DrawFloodCard)
+        );
+        assert_eq!(ActionState::PlayerAction(3), state.action_state());
+    }
+
+    #[test]
+    fn moves_cost_action_points() {
+        let mut state = classic_game(&[AdventurerType::Courier]);
+
+        for left in (0..3).rev() {
+            let action = state
+                .legal_actions()
+                .into_iter()
+                .find(|a| match a {
+                    GameAction::Move(_) => true,
+                    _ => false
+                })
+                .expect("No move available");
+            state.apply(&action).unwrap();
+            assert_eq!(ActionState::PlayerAction(left), state.action_state());
+        }
+
+        assert!(!state.legal_actions().iter().any(|a| match a {
+            GameAction::Move(_) => true,
+            _ => false
+        }));
+    }
+
+    #[test]
+    fn full_turn_cycle() {
+        let mut state = classic_game(&[AdventurerType::Explorer, AdventurerType::Navigator]);
+
+        state.apply(&GameAction::EndActions).unwrap();
+        assert_eq!(ActionState::DrawArtefactCards(2), state.action_state());
+        state.apply(&GameAction::DrawTreasureCard).unwrap();
+        state.apply(&GameAction::DrawTreasureCard).unwrap();
+
+        // Drawing might have resulted in too many cards, or a rising water level
+        while let Some(player) = state.over_hand_limit() {
+            let card = state.characters()[player].hand()[0];
+            state.apply(&GameAction::Discard { player, card }).unwrap();
+        }
+        let draw_amount = state.water_level().draw_amount();
+        assert_eq!(
+            ActionState::DrawFloodCards(draw_amount),
+            state.action_state()
+        );
+
+        for _ in 0..draw_amount {
+            state.apply(&GameAction::DrawFloodCard).unwrap();
+        }
+        assert_eq!(1, state.active_player());
+        assert_eq!(1, state.turn());
+        assert_eq!(ActionState::PlayerAction(3), state.action_state());
+    }
+
+    #[test]
+    fn sinking_tile_rescues_player() {
+        let mut state = classic_game(&[AdventurerType::Engineer]);
+        let pos = state.active_character().pos();
+        state.set_tile_state(pos, IslandTileState::Flooded);
+
+        let mut events = Vec::new();
+        state.set_tile_state(pos, IslandTileState::Gone);
+        state.tile_sunk(pos, &mut events);
+
+        let new_pos = state.active_character().pos();
+        assert_ne!(pos, new_pos);
+        assert!(state.map().is_standable(new_pos));
+        assert_eq!(
+            GameEvent::Rescued {
+                player: 0,
+                to:     new_pos
+            },
+            events[0]
+        );
+    }
+
+    #[test]
+    fn fools_landing_sunk_loses() {
+        let mut state = classic_game(&[AdventurerType::Pilot]);
+        let pos = state.tile_pos(IslandTileInfo::FoolsLanding).unwrap();
+        // Move the pilot out of the way, so they do not drown first
+        let other = state.rescue_positions(0)[0];
+        state.characters[0].set_pos(other);
+
+        let mut events = Vec::new();
+        state.set_tile_state(pos, IslandTileState::Gone);
+        state.tile_sunk(pos, &mut events);
+
+        assert_eq!(
+            Some(Outcome::Lost(LossReason::FoolsLandingSunk)),
+            state.outcome()
+        );
+        assert_eq!(
+            Err(ActionError::GameOver),
+            state.apply(&GameAction::EndActions)
+        );
+    }
+
+    #[test]
+    fn collect_artefact() {
+        let mut state = classic_game(&[AdventurerType::Diver]);
+        let temple = state.tile_pos(IslandTileInfo::TempleOfTheSun).unwrap();
+        state.set_tile_state(temple, IslandTileState::Dry);
+        state.characters[0].set_pos(temple);
+        *state.characters[0].hand_mut() = vec![TreasureCard::Artefact(ArtefactType::Earth); 4];
+
+        state.apply(&GameAction::CollectArtefact).unwrap();
+
+        assert_eq!(&[ArtefactType::Earth], state.artefacts());
+        assert!(state.active_character().hand().is_empty());
+        assert_eq!(ActionState::PlayerAction(2), state.action_state());
+    }
+
+    #[test]
+    fn lift_off_wins() {
+        let mut state = classic_game(&[AdventurerType::Pilot, AdventurerType::Courier]);
+        let fools_landing = state.tile_pos(IslandTileInfo::FoolsLanding).unwrap();
+        state.set_tile_state(fools_landing, IslandTileState::Dry);
+        state.characters[1].set_pos(fools_landing);
+        state.artefacts = ArtefactType::ALL.to_vec();
+        state.characters[1]
+            .hand_mut()
+            .push(TreasureCard::Helicopter);
+
+        state.apply(&GameAction::LiftOff { player: 1 }).unwrap();
+
+        assert_eq!(Some(Outcome::Won), state.outcome());
+    }
+
+    #[test]
+    fn hand_limit_blocks_other_actions() {
+        let mut state = classic_game(&[AdventurerType::Courier, AdventurerType::Diver]);
+        *state.characters[1].hand_mut() = vec![TreasureCard::Artefact(ArtefactType::Air); 6];
+
+        assert_eq!(
+            vec![GameAction::Discard {
+                player: 1,
+                card:   TreasureCard::Artefact(ArtefactType::Air)
+            }],
+            state.legal_actions()
+        );
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let a = classic_game(&[AdventurerType::Courier, AdventurerType::Diver]);
+        let b = classic_game(&[AdventurerType::Courier, AdventurerType::Diver]);
+
+        assert_eq!(a.map(), b.map());
+        assert_eq!(a.characters()[1].hand(), b.characters()[1].hand());
+    }
+}
@@ -0,0 +1,99 @@
+//! Serialisable copy of everything the players can observe of a game. It is
+//! used to show the game on machines that do not run the rules engine
+//! themselves.
+
+use super::{GameAction, GameState, Outcome};
+use crate::action_state::ActionState;
+use crate::adventurer::AdventurerType;
+use crate::artefact_type::ArtefactType;
+use crate::character::Character;
+use crate::map::{FieldPos, Full as MapFull, IslandTileInfo};
+use crate::positionable::Positionable;
+use crate::treasure_card::TreasureCard;
+use crate::water_level::WaterLevel;
+use serde::{Deserialize, Serialize};
+
+/// One of the adventurers, as seen by everyone at the table.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CharacterSnapshot {
+    pub adventurer: AdventurerType,
+    pub pos: FieldPos,
+    pub hand: Vec<TreasureCard>
+}
+
+/// The observable state of a game. The order of the cards in the decks is
+/// left out, since nobody is supposed to know it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub map: MapFull,
+    pub characters: Vec<CharacterSnapshot>,
+    pub active: usize,
+    /// The player who has to decide how the game continues.
+    pub deciding_player: usize,
+    pub action_state: ActionState,
+    pub water_level: WaterLevel,
+    pub artefacts: Vec<ArtefactType>,
+    pub turn: u32,
+    pub outcome: Option<Outcome>,
+    pub treasure_deck_size: usize,
+    pub treasure_discard: Vec<TreasureCard>,
+    pub flood_deck_size: usize,
+    pub flood_discard: Vec<IslandTileInfo>,
+    /// The actions of all players that are allowed right now. Which player
+    /// takes an action is decided by [GameAction::player].
+    pub legal_actions: Vec<GameAction>
+}
+
+impl GameSnapshot {
+    /// The number of turns that have been played so far, see
+    /// [GameState::turns_played].
+    pub fn turns_played(&self) -> u32 { self.turn + 1 }
+
+    /// The actions the player with the index provided may take right now.
+    pub fn legal_actions_of(&self, player: usize) -> Vec<GameAction> {
+        self.legal_actions
+            .iter()
+            .filter(|action| action.player(self.active) == player)
+            .cloned()
+            .collect()
+    }
+}
+
+impl From<&CharacterSnapshot> for Character {
+    fn from(snapshot: &CharacterSnapshot) -> Self {
+        let mut character = Character::new(snapshot.adventurer);
+        character.set_pos(snapshot.pos);
+        *character.hand_mut() = snapshot.hand.clone();
+        character
+    }
+}
+
+impl GameState {
+    /// Take a snapshot of everything that can be observed in this state.
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            map: self.map.clone(),
+            characters: self
+                .characters
+                .iter()
+                .map(|character| CharacterSnapshot {
+                    adventurer: character.adventurer_type(),
+                    pos: character.pos(),
+                    hand: character.hand().clone()
+                })
+                .collect(),
+            active: self.active,
+            deciding_player: self.deciding_player(),
+            action_state: self.action_state,
+            water_level: self.water_level,
+            artefacts: self.artefacts.clone(),
+            turn: self.turn,
+            outcome: self.outcome,
+            treasure_deck_size: self.treasure_cards.draw_stack_size(),
+            treasure_discard: self.treasure_cards.discard_stack().clone(),
+            flood_deck_size: self.flood_cards.draw_stack_size(),
+            flood_discard: self.flood_cards.discard_stack().clone(),
+            legal_actions: self.legal_actions()
+        }
+    }
+}
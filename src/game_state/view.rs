@@ -0,0 +1,104 @@
+//! What a single player can see of the game. All hands are played openly in
+//! the forbidden island, but the order of the cards in the decks is unknown to
+//! everyone.
+
+use super::{GameAction, GameState, Outcome};
+use crate::action_state::ActionState;
+use crate::artefact_type::ArtefactType;
+use crate::character::Character;
+use crate::map::{FieldPos, Full as MapFull, IslandTileInfo};
+use crate::treasure_card::TreasureCard;
+use crate::water_level::WaterLevel;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Read only view of the game from the perspective of one player.
+#[derive(Clone, Copy)]
+pub struct GameView<'a> {
+    state:  &'a GameState,
+    player: usize
+}
+
+impl<'a> GameView<'a> {
+    /// Create the view of the player with the index provided.
+    ///
+    /// # Panics
+    /// If there is no such player in the game.
+    pub fn new(state: &'a GameState, player: usize) -> Self {
+        assert!(player < state.characters().len());
+        Self { state, player }
+    }
+
+    /// The player observing the game.
+    pub fn player(&self) -> usize { self.player }
+
+    /// The character of the player observing the game.
+    pub fn character(&self) -> &'a Character { &self.state.characters()[self.player] }
+
+    pub fn map(&self) -> &'a MapFull { self.state.map() }
+
+    pub fn characters(&self) -> &'a [Character] { self.state.characters() }
+
+    pub fn active_player(&self) -> usize { self.state.active_player() }
+
+    pub fn action_state(&self) -> ActionState { self.state.action_state() }
+
+    pub fn water_level(&self) -> WaterLevel { self.state.water_level() }
+
+    pub fn artefacts(&self) -> &'a [ArtefactType] { self.state.artefacts() }
+
+    pub fn turn(&self) -> u32 { self.state.turn() }
+
+    pub fn outcome(&self) -> Option<Outcome> { self.state.outcome() }
+
+    /// The amount of cards left in the treasure deck.
+    pub fn treasure_deck_size(&self) -> usize { self.state.treasure_cards().draw_stack_size() }
+
+    /// The treasure cards that have been discarded and will be shuffled back
+    /// into the deck once it runs out.
+    pub fn treasure_discard(&self) -> &'a [TreasureCard] {
+        self.state.treasure_cards().discard_stack()
+    }
+
+    /// The amount of cards left in the flood deck.
+    pub fn flood_deck_size(&self) -> usize { self.state.flood_cards().draw_stack_size() }
+
+    /// The flood cards that have been drawn since the flood deck has been
+    /// shuffled the last time.
+    pub fn flood_discard(&self) -> &'a [IslandTileInfo] { self.state.flood_cards().discard_stack() }
+
+    pub fn tile_pos(&self, info: IslandTileInfo) -> Option<FieldPos> { self.state.tile_pos(info) }
+
+    pub fn collectable_artefact(&self) -> Option<ArtefactType> { self.state.collectable_artefact() }
+
+    pub fn can_lift_off(&self) -> bool { self.state.can_lift_off() }
+
+    /// Checks if the game is waiting for a decision of the observing player.
+    pub fn is_deciding(&self) -> bool {
+        !self.state.is_over() && self.state.deciding_player() == self.player
+    }
+
+    /// All actions the observing player is allowed to take right now.
+    pub fn legal_actions(&self) -> Vec<GameAction> {
+        let active = self.state.active_player();
+        self.state
+            .legal_actions()
+            .into_iter()
+            .filter(|action| action.player(active) == self.player)
+            .collect()
+    }
+
+    /// Create a complete game state that matches everything the player can
+    /// observe. The order of the cards in the decks is unknown, so it is
+    /// sampled with the random number generator provided. This makes it
+    /// possible to look ahead without knowing what will be drawn next. The
+    /// flood cards shuffled back when the waters rose stay on top of the deck,
+    /// since everyone has seen which ones they are.
+    pub fn determinise<R: Rng + ?Sized>(&self, rng: &mut R) -> GameState {
+        let mut state = self.state.clone();
+        state.treasure_cards.shuffle_with(rng);
+        state.flood_cards.shuffle_split_with(state.flood_known, rng);
+        state.rng = StdRng::seed_from_u64(rng.gen());
+
+        state
+    }
+}
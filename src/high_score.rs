@@ -0,0 +1,273 @@
+//! The table of finished games. It is kept in a file in the data directory of
+//! the user, so the best games are remembered between sessions.
+
+use crate::adventurer::AdventurerType;
+use crate::difficulty::Difficulty;
+use crate::game_state::{GameState, Outcome};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// The name of the file the table is stored in.
+pub const HIGH_SCORE_FILE: &str = "high_scores.json";
+
+/// A game that has been played to the end.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    /// When the game has ended, in seconds since the unix epoch.
+    pub played_at: i64,
+    /// The names of the players, in the order they took their turns.
+    pub players: Vec<String>,
+    /// The adventurer of each player.
+    pub adventurers: Vec<AdventurerType>,
+    pub difficulty: Difficulty,
+    /// Which map has been played, for instance `classic` or the name of the map
+    /// file.
+    pub map: String,
+    pub outcome: Outcome,
+    /// The number of turns it took to win or lose.
+    pub turns: u32,
+    /// The level the water has risen to by the end.
+    pub water_level: u8
+}
+
+/// The columns the table can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortBy {
+    /// Highest score first.
+    Score,
+    /// Most recent game first.
+    Date,
+    /// Fastest game first.
+    Turns,
+    /// Lowest water level first.
+    WaterLevel
+}
+
+impl SortBy {
+    pub const ALL: [SortBy; 4] = [Self::Score, Self::Date, Self::Turns, Self::WaterLevel];
+}
+
+/// All games that have been recorded.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    scores: Vec<HighScore>
+}
+
+impl HighScore {
+    /// Record a finished game.
+    ///
+    /// # Parameters
+    /// `state` - The state the game has ended in
+    /// `players` - The names of the players, in the order of their turns
+    /// `difficulty` - The difficulty the game has been started with
+    /// `map` - What the map is called
+    /// `played_at` - When the game has ended, in seconds since the unix epoch
+    ///
+    /// # Returns
+    /// The entry of the game, or `None` if it is not over yet.
+    pub fn new(
+        state: &GameState,
+        players: Vec<String>,
+        difficulty: Difficulty,
+        map: String,
+        played_at: i64
+    ) -> Option<Self> {
+        Some(Self {
+            played_at,
+            players,
+            adventurers: state
+                .characters()
+                .iter()
+                .map(|character| character.adventurer_type())
+                .collect(),
+            difficulty,
+            map,
+            outcome: state.outcome()?,
+            turns: state.turns_played(),
+            water_level: state.water_level().level()
+        })
+    }
+
+    /// The score of the game. Every win ranks above every loss, and harder
+    /// difficulties rank above easier ones. Among wins of the same difficulty,
+    /// faster ones are better. Among losses, holding out longer is better.
+    pub fn score(&self) -> u32 {
+        let weight = match self.difficulty {
+            Difficulty::Novice => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Elite => 3,
+            Difficulty::Legendary => 4
+        };
+
+        match self.outcome {
+            Outcome::Won => 1000 * weight + 500u32.saturating_sub(5 * self.turns),
+            Outcome::Lost(_) => 100 * weight + self.turns.min(99)
+        }
+    }
+}
+
+impl HighScoreTable {
+    /// Load the table from the file. If there is no such file yet, the table
+    /// is empty.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Save the table to the file, creating its directory if necessary.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+
+    /// Add a finished game to the table.
+    pub fn record(&mut self, score: HighScore) { self.scores.push(score); }
+
+    /// All games, in the order they have been recorded.
+    pub fn scores(&self) -> &[HighScore] { &self.scores }
+
+    /// The games to be shown in the table.
+    ///
+    /// # Parameters
+    /// `by` - The column the games are sorted by. Games that are equal in it
+    /// are sorted by their score.
+    /// `difficulty` - Only games of this difficulty are shown, or all games if
+    /// it is `None`.
+    pub fn sorted(&self, by: SortBy, difficulty: Option<Difficulty>) -> Vec<&HighScore> {
+        let mut scores: Vec<&HighScore> = self
+            .scores
+            .iter()
+            .filter(|score| difficulty.map_or(true, |d| score.difficulty == d))
+            .collect();
+
+        scores.sort_by(|a, b| {
+            let column = match by {
+                SortBy::Score => b.score().cmp(&a.score()),
+                SortBy::Date => b.played_at.cmp(&a.played_at),
+                SortBy::Turns => a.turns.cmp(&b.turns),
+                SortBy::WaterLevel => a.water_level.cmp(&b.water_level)
+            };
+            column.then_with(|| b.score().cmp(&a.score()))
+        });
+        scores
+    }
+}
+
+/// The file the table is kept in, inside the data directory of the user.
+/// Returns `None` if the system has no such directory.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("java-game").join(HIGH_SCORE_FILE))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::{DebugCommand, LossReason};
+    use crate::map::generate;
+    use crate::water_level::LOOSING_WATER_LEVEL;
+
+    fn score(difficulty: Difficulty, outcome: Outcome, turns: u32, played_at: i64) -> HighScore {
+        HighScore {
+            played_at,
+            players: vec!["Alice".to_string(), "Bob".to_string()],
+            adventurers: vec![AdventurerType::Pilot, AdventurerType::Diver],
+            difficulty,
+            map: "classic".to_string(),
+            outcome,
+            turns,
+            water_level: 5
+        }
+    }
+
+    #[test]
+    fn counts_the_turn_the_game_ended_in() {
+        let mut state = GameState::with_seed(
+            generate::classic_island(),
+            &[AdventurerType::Pilot, AdventurerType::Diver],
+            Difficulty::Novice,
+            0
+        );
+        state
+            .apply_debug(DebugCommand::SetWaterLevel(LOOSING_WATER_LEVEL))
+            .unwrap();
+
+        let score = HighScore::new(
+            &state,
+            vec!["Alice".to_string(), "Bob".to_string()],
+            Difficulty::Novice,
+            "classic".to_string(),
+            0
+        )
+        .unwrap();
+        assert_eq!(1, score.turns);
+    }
+
+    #[test]
+    fn harder_wins_rank_higher() {
+        let slow_legendary = score(Difficulty::Legendary, Outcome::Won, 200, 0);
+        let fast_novice = score(Difficulty::Novice, Outcome::Won, 1, 0);
+        let fast_elite = score(Difficulty::Elite, Outcome::Won, 10, 0);
+        let slow_elite = score(Difficulty::Elite, Outcome::Won, 20, 0);
+        let long_loss = score(
+            Difficulty::Legendary,
+            Outcome::Lost(LossReason::WaterLevel),
+            500,
+            0
+        );
+
+        assert!(slow_legendary.score() > fast_novice.score());
+        assert!(fast_elite.score() > slow_elite.score());
+        assert!(fast_novice.score() > long_loss.score());
+    }
+
+    #[test]
+    fn sort_and_filter() {
+        let mut table = HighScoreTable::default();
+        table.record(score(Difficulty::Novice, Outcome::Won, 10, 3));
+        table.record(score(Difficulty::Legendary, Outcome::Won, 30, 1));
+        table.record(score(
+            Difficulty::Novice,
+            Outcome::Lost(LossReason::FoolsLandingSunk),
+            5,
+            2
+        ));
+
+        let turns =
+            |scores: Vec<&HighScore>| -> Vec<u32> { scores.iter().map(|s| s.turns).collect() };
+        assert_eq!(vec![30, 10, 5], turns(table.sorted(SortBy::Score, None)));
+        assert_eq!(vec![10, 5, 30], turns(table.sorted(SortBy::Date, None)));
+        assert_eq!(vec![5, 10, 30], turns(table.sorted(SortBy::Turns, None)));
+        assert_eq!(
+            vec![10, 5],
+            turns(table.sorted(SortBy::Score, Some(Difficulty::Novice)))
+        );
+        assert!(table
+            .sorted(SortBy::Score, Some(Difficulty::Elite))
+            .is_empty());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("java-game-test-{}", std::process::id()))
+            .join(HIGH_SCORE_FILE);
+        assert_eq!(
+            Ok(HighScoreTable::default()),
+            HighScoreTable::load(&path).map_err(|_| ())
+        );
+
+        let mut table = HighScoreTable::default();
+        table.record(score(Difficulty::Elite, Outcome::Won, 12, 1_571_000_000));
+        table.save(&path).unwrap();
+        assert_eq!(table, HighScoreTable::load(&path).unwrap());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,17 +1,23 @@
 //! Traits for iterating through a 2D-Vector.
 
 use crate::map::FieldPos;
+use crate::math::Vec2;
+use std::slice;
 
 pub struct Iter2d<'a, T> {
-    internal: &'a [Vec<T>],
+    internal: slice::Iter<'a, T>,
+    width:    u8,
     pos:      FieldPos
 }
 
 impl<'a, T> Iter2d<'a, T> {
-    pub fn new(source: &'a [Vec<T>]) -> Self {
+    /// Create a new iterator over the items of a map, which are laid out row by
+    /// row with each row being `width` items long.
+    pub fn new(source: &'a [T], width: u8) -> Self {
         Self {
-            internal: source,
-            pos:      FieldPos::new()
+            internal: source.iter(),
+            width,
+            pos: FieldPos::new()
         }
     }
 }
@@ -20,27 +26,148 @@ impl<'a, T> Iterator for Iter2d<'a, T> {
     type Item = (FieldPos, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        // Get the current line the Iterator is on.
-        if let Some(line) = self.internal.get(self.pos.y as usize) {
-            // Get the current element
-            if let Some(e) = line.get(self.pos.x as usize) {
-                // Try to shift the pointer one to the right and return the current element.
-                let old_pos = self.pos;
-                self.pos.x += 1;
-
-                Some((old_pos, &e))
-            }
-            else {
-                // There are no items left on the current line. Select the next line and return
-                // the next item recursively.
-                self.pos.y += 1;
-                self.pos.x = 0;
-                self.next()
-            }
+        let e = self.internal.next()?;
+        let pos = self.pos;
+        advance(&mut self.pos, self.width);
+
+        Some((pos, e))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.internal.size_hint() }
+}
+
+impl<'a, T> ExactSizeIterator for Iter2d<'a, T> {}
+
+/// Iterator through a map that allows changing the items while iterating.
+pub struct IterMut2d<'a, T> {
+    internal: slice::IterMut<'a, T>,
+    width:    u8,
+    pos:      FieldPos
+}
+
+impl<'a, T> IterMut2d<'a, T> {
+    /// Create a new mutable iterator over the items of a map, which are laid
+    /// out row by row with each row being `width` items long.
+    pub fn new(source: &'a mut [T], width: u8) -> Self {
+        Self {
+            internal: source.iter_mut(),
+            width,
+            pos: FieldPos::new()
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut2d<'a, T> {
+    type Item = (FieldPos, &'a mut T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let e = self.internal.next()?;
+        let pos = self.pos;
+        advance(&mut self.pos, self.width);
+
+        Some((pos, e))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) { self.internal.size_hint() }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut2d<'a, T> {}
+
+/// Iterator over all positions of a map with the given size, row by row.
+pub struct Positions {
+    width: u8,
+    index: usize,
+    len:   usize
+}
+
+impl Positions {
+    /// Create a new iterator over all positions inside a map of the size
+    /// provided.
+    pub fn new(size: Vec2<u8>) -> Self {
+        Self {
+            width: size.x,
+            index: 0,
+            len:   size.x as usize * size.y as usize
+        }
+    }
+}
+
+impl Iterator for Positions {
+    type Item = FieldPos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.len {
+            let width = self.width as usize;
+            let pos = FieldPos::from_values((self.index % width) as u8, (self.index / width) as u8);
+            self.index += 1;
+
+            Some(pos)
         }
         else {
-            // There are no lines left.
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Positions {}
+
+// Move the position one to the right, or to the beginning of the next line if
+// the end of the current line has been reached.
+fn advance(pos: &mut FieldPos, width: u8) {
+    if pos.x + 1 < width {
+        pos.x += 1;
+    }
+    else {
+        pos.x = 0;
+        pos.y += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn iter_exact_size() {
+        let data = [0; 6];
+        let mut iter = Iter2d::new(&data, 3);
+
+        assert_eq!(6, iter.len());
+        iter.next();
+        assert_eq!(5, iter.len());
+        assert_eq!(Some((FieldPos::from_values(0, 1), &0)), iter.nth(2));
+        assert_eq!(2, iter.len());
+    }
+
+    #[test]
+    fn iter_mut_positions() {
+        let mut data = [FieldPos::new(); 6];
+        for (pos, e) in IterMut2d::new(&mut data, 2) {
+            *e = pos;
+        }
+
+        assert_eq!(FieldPos::from_values(1, 0), data[1]);
+        assert_eq!(FieldPos::from_values(0, 2), data[4]);
+    }
+
+    #[test]
+    fn positions() {
+        let positions: Vec<FieldPos> = Positions::new(Vec2::from_values(2, 2)).collect();
+
+        assert_eq!(
+            vec![
+                FieldPos::from_values(0, 0),
+                FieldPos::from_values(1, 0),
+                FieldPos::from_values(0, 1),
+                FieldPos::from_values(1, 1),
+            ],
+            positions
+        );
+        assert_eq!(0, Positions::new(Vec2::from_values(0, 5)).len());
+    }
 }
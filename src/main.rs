@@ -1,41 +1,27 @@
-#![allow(dead_code)]
-
-//! # \[J\]ust \[a\]nother \[v\]irtual \[a\]dventure game
-//!
-//! Originating from the Software Praktikum at TU Dortmund and written in Java,
-//! this is a Rust rewrite of the computer game based on the board game "The
-//! Forbidden Island". It is purely for educational purposes and fun.
-
-#[macro_use]
-extern crate java_game_derive;
-
-pub mod action_state;
-pub mod adventurer;
-pub mod artefact_type;
-pub mod asset;
-pub mod bfs;
-pub mod card_stack;
-pub mod character;
-pub mod difficulty;
-pub mod direction;
-pub mod iter_2d;
-pub mod map;
-pub mod math;
-pub mod positionable;
-pub mod state;
-pub mod system;
-pub mod water_level;
+//! Graphical frontend of the game, rendered with amethyst.
 
 use amethyst::{
     core::{frame_limiter::FrameRateLimitStrategy, transform::TransformBundle},
+    input::{InputBundle, StringBindings},
     prelude::*,
     renderer::{
         plugins::{RenderFlat2D, RenderToWindow},
         types::DefaultBackend,
         RenderingBundle
     },
-    utils::application_root_dir
+    ui::{RenderUi, UiBundle},
+    utils::application_root_dir,
+    State
 };
+use java_game::adventurer::AdventurerType;
+use java_game::difficulty::Difficulty;
+use java_game::game_state::GameState;
+use java_game::map::{loader, MapChoice};
+use java_game::settings::{GameSettings, SETTINGS_FILE};
+use java_game::{state, system};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::time::Duration;
 
 fn main() -> amethyst::Result<()> {
@@ -45,8 +31,16 @@ fn main() -> amethyst::Result<()> {
     let display_config = app_root.join("config/display.ron");
     let assets_dir = app_root.join("assets");
 
+    let settings_path = app_root.join(SETTINGS_FILE);
+    let settings = GameSettings::load(&settings_path).unwrap_or_else(|err| {
+        eprintln!("Unable to load {}: {}", settings_path.display(), err);
+        GameSettings::default()
+    });
+
     let game_data = GameDataBuilder::default()
         .with_bundle(TransformBundle::new())?
+        .with_bundle(InputBundle::<StringBindings>::new())?
+        .with_bundle(UiBundle::<StringBindings>::new())?
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
                 .with_plugin(
@@ -54,20 +48,137 @@ fn main() -> amethyst::Result<()> {
                         .with_clear([0.34, 0.36, 0.52, 1.0])
                 )
                 .with_plugin(RenderFlat2D::default())
+                .with_plugin(RenderUi::default())
         )?
         .with(
             system::CharacterTransformUpdate,
             "character_transform_update_system",
             &[]
+        )
+        .with(system::ShowTileOptions, "show_tile_options_system", &[])
+        .with(system::TileSpriteUpdate, "tile_sprite_update_system", &[])
+        .with(
+            system::TilePicking::default(),
+            "tile_picking_system",
+            &["input_system"]
+        )
+        .with(
+            system::DragAndDrop::default(),
+            "drag_and_drop_system",
+            &["input_system"]
+        )
+        .with(
+            system::ApplyWindowSettings::default(),
+            "apply_window_settings_system",
+            &[]
         );
 
-    let mut game = Application::build(assets_dir, state::Game)?
+    // Join the lobby of a server with `--connect ADDRESS [--name NAME] [--map
+    // FILE]`, watch its game with `--watch ADDRESS`, edit a map with `--edit
+    // FILE`, play a scenario with `--scenario FILE` or play locally right away
+    // with `--players courier,diver [--difficulty normal] [--map FILE]`.
+    // Otherwise, the game starts in the main menu.
+    let args: Vec<String> = env::args().collect();
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+    let local = option("--players").is_some() || option("--difficulty").is_some();
+    let start: Box<dyn State<GameData<'static, 'static>, StateEvent>> = match (
+        option("--connect"),
+        option("--watch"),
+        option("--edit"),
+        option("--scenario")
+    ) {
+        (Some(address), ..) => {
+            let name = option("--name").unwrap_or_else(|| "Player".to_string());
+            let mut preparations = state::GamePreparations::join(address, name);
+            if let Some(map) = option("--map") {
+                preparations = preparations.with_map_file(PathBuf::from(map));
+            }
+            Box::new(preparations)
+        }
+        (None, Some(address), ..) => Box::new(state::RemoteGame::watch(address)),
+        (None, None, Some(path), _) => Box::new(state::MapEditor::new(PathBuf::from(path))),
+        (None, None, None, Some(path)) => Box::new(state::ScenarioGame::new(path)),
+        (None, None, None, None) if local => Box::new(
+            local_game(option("--players"), option("--difficulty"), option("--map"))
+                .unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                })
+        ),
+        (None, None, None, None) => Box::new(state::MainMenu::default())
+    };
+
+    let mut game = Application::build(assets_dir, Launch(Some(start)))?
         .with_frame_limit(
             FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(2)),
             144
         )
+        .with_resource(settings)
         .build(game_data)?;
     game.run();
 
     Ok(())
 }
+
+// The state the application is built with. It hands over to the state chosen
+// on the command line right away, which can be any kind of state.
+struct Launch(Option<Box<dyn State<GameData<'static, 'static>, StateEvent>>>);
+
+impl SimpleState for Launch {
+    fn update(&mut self, _data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        match self.0.take() {
+            Some(state) => Trans::Switch(state),
+            None => Trans::None
+        }
+    }
+}
+
+// Set up a game for the players sharing this machine.
+fn local_game(
+    players: Option<String>,
+    difficulty: Option<String>,
+    map: Option<String>
+) -> Result<state::Game, String> {
+    let players: Vec<AdventurerType> = match players {
+        Some(players) => players
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()?,
+        None => vec![AdventurerType::Courier, AdventurerType::Diver]
+    };
+    state::check_local_players(&players)?;
+
+    let difficulty = match difficulty {
+        Some(difficulty) => difficulty.parse()?,
+        None => Difficulty::Normal
+    };
+
+    let (map, map_name) = match map {
+        Some(path) => (
+            MapChoice::Custom(
+                loader::load_map(&path)
+                    .map_err(|err| format!("Unable to load map {}: {}", path, err))?
+            ),
+            state::map_name(Path::new(&path))
+        ),
+        None => (MapChoice::Random, "random".to_string())
+    };
+    if let Some(&adventurer) = players.iter().find(|&&a| !map.has_spawn_point(a)) {
+        return Err(format!(
+            "The map has no spawn tile for the {:?}",
+            adventurer
+        ));
+    }
+
+    let names = (1..=players.len())
+        .map(|player| format!("Player {}", player))
+        .collect();
+    let map = map.create(&mut rand::thread_rng());
+    Ok(state::Game::new(GameState::new(map, &players, difficulty))
+        .recorded_as(names, difficulty, map_name))
+}
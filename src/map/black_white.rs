@@ -2,41 +2,12 @@
 //! tiles should be placed (true) and where the sea is (false). They are
 //! usually used to generate maps randomly.
 
-use super::{FieldPos, Map, MapExt};
-use crate::math::Rect;
+use super::{Map, MapCell};
 
 pub type BlackWhite = Map<bool>;
 
-impl MapExt for BlackWhite {
-    fn is_standable(&self, pos: FieldPos) -> bool {
-        if let Some(tile) = self.get(pos) {
-            *tile
-        }
-        else {
-            false
-        }
-    }
+impl MapCell for bool {
+    fn is_island(&self) -> bool { *self }
 
-    fn limit_rect(&self) -> Rect<u8> {
-        // Find the minimum rectangular hull around all true tiles (island tiles) and
-        // return it.
-        let mut min_pos = FieldPos::new();
-        let mut max_pos = FieldPos::new();
-
-        self.iter().for_each(|(pos, &e)| {
-            if e {
-                min_pos.x = min_pos.x.min(pos.x);
-                min_pos.y = min_pos.y.min(pos.y);
-                max_pos.x = max_pos.x.max(pos.x);
-                max_pos.y = max_pos.y.max(pos.y);
-            }
-        });
-
-        Rect::from_slice([
-            min_pos.x,
-            min_pos.y,
-            max_pos.x - min_pos.x,
-            max_pos.y - min_pos.y
-        ])
-    }
+    fn is_standable(&self) -> bool { *self }
 }
@@ -0,0 +1,48 @@
+//! The choice of the island a new game is played on.
+
+use super::{generate, Full};
+use crate::adventurer::AdventurerType;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Where the map of a new game comes from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MapChoice {
+    /// The island of the original board game, with every tile always in the
+    /// same place.
+    Classic,
+    /// The tiles are shuffled onto the classic shape of the island.
+    Random,
+    /// A map that has been prepared beforehand, usually loaded from a file.
+    Custom(Full)
+}
+
+impl MapChoice {
+    /// Create the map that has been chosen.
+    ///
+    /// # Parameters
+    /// `rng` - The source of randomness for the distribution of the tiles, if
+    /// the map is random.
+    pub fn create<R: Rng + ?Sized>(&self, rng: &mut R) -> Full {
+        match self {
+            Self::Classic => generate::classic_island(),
+            Self::Random => generate::filled_island_with(&generate::classic_shape(), rng),
+            Self::Custom(map) => map.clone()
+        }
+    }
+
+    /// Checks if the adventurer can start on the map, which is not the case
+    /// for custom maps that lack the spawn tile of the adventurer.
+    pub fn has_spawn_point(&self, adventurer: AdventurerType) -> bool {
+        match self {
+            Self::Classic | Self::Random => true,
+            Self::Custom(map) => map.iter().any(|(_, tile)| {
+                tile.map_or(false, |tile| tile.info().player_spawn() == Some(adventurer))
+            })
+        }
+    }
+}
+
+impl Default for MapChoice {
+    fn default() -> Self { Self::Random }
+}
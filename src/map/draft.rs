@@ -0,0 +1,362 @@
+//! A map that is still being edited. Unlike a [Full] map, it may have island
+//! cells that have not got a tile yet, and it keeps track of what is missing
+//! before it can be played.
+
+use super::{BlackWhite, FieldPos, Full, IslandTile, IslandTileInfo, IslandTileState, Map, MapExt};
+use crate::adventurer::AdventurerType;
+use crate::bfs;
+use crate::math::Vec2;
+use std::fmt;
+
+/// The largest number of columns and rows a draft can have.
+pub const MAX_DRAFT_SIZE: u8 = 16;
+
+/// A reason why a draft is not a valid map yet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The tile has not been placed on the island.
+    Unplaced(IslandTileInfo),
+    /// The cell is part of the island, but there is no tile on it.
+    EmptyCell(FieldPos),
+    /// The island consists of several parts, which can not be walked between
+    /// by foot.
+    NotConnected,
+    /// The spawn tile of the adventurer is gone before the game has even
+    /// started.
+    SpawnGone(AdventurerType),
+    /// The map has no cells or more than [MAX_DRAFT_SIZE] columns or rows, so
+    /// it can not be edited.
+    BadSize(Vec2<u8>)
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unplaced(info) => write!(f, "{} has not been placed", info.name()),
+            Self::EmptyCell(pos) => {
+                write!(f, "the island cell at {}, {} has no tile", pos.x, pos.y)
+            }
+            Self::NotConnected => write!(f, "the island is not connected"),
+            Self::SpawnGone(adventurer) => {
+                write!(f, "the spawn tile of the {:?} is gone", adventurer)
+            }
+            Self::BadSize(size) => write!(
+                f,
+                "the map has {} columns and {} rows, but between 1 and {} are supported",
+                size.x, size.y, MAX_DRAFT_SIZE
+            )
+        }
+    }
+}
+
+/// A map in the editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Draft {
+    shape: BlackWhite,
+    tiles: Full
+}
+
+impl Draft {
+    /// Create a draft of the size given, which is water only.
+    ///
+    /// # Panics
+    /// If the size is zero or larger than [MAX_DRAFT_SIZE] in any direction.
+    pub fn new(size: Vec2<u8>) -> Self {
+        check_size(size);
+
+        Self {
+            shape: BlackWhite::new(size, false),
+            tiles: Full::new(size, None)
+        }
+    }
+
+    /// Continue editing a map. Every cell with a tile is part of the island.
+    ///
+    /// # Returns
+    /// The draft, or [Problem::BadSize] if the map has no cells or more than
+    /// [MAX_DRAFT_SIZE] columns or rows.
+    pub fn from_map(map: &Full) -> Result<Self, Problem> {
+        let size = map.size();
+        if !is_valid_size(size) {
+            return Err(Problem::BadSize(size));
+        }
+
+        Ok(Self {
+            shape: map.black_white(),
+            tiles: map.clone()
+        })
+    }
+
+    /// The number of columns and rows of the draft.
+    pub fn size(&self) -> Vec2<u8> { self.shape.size() }
+
+    /// Change the number of columns and rows. Cells are added or removed after
+    /// the last column and row. Tiles on removed cells go back to the palette.
+    ///
+    /// # Panics
+    /// If the size is zero or larger than [MAX_DRAFT_SIZE] in any direction.
+    pub fn resize(&mut self, size: Vec2<u8>) {
+        check_size(size);
+
+        let shape = &self.shape;
+        let tiles = &self.tiles;
+        let new_shape = Map::from_fn(size, |pos| shape.get(pos).copied().unwrap_or(false));
+        let mut new_tiles: Full =
+            Map::from_fn(size, |pos| tiles.get(pos).copied().flatten()).into();
+        *new_tiles.transform_mut() = self.tiles.transform().clone();
+
+        self.shape = new_shape;
+        self.tiles = new_tiles;
+    }
+
+    /// Checks if the cell at the position is part of the island.
+    pub fn is_island(&self, pos: FieldPos) -> bool { self.shape.is_island(pos) }
+
+    /// The tile at the position, if one has been placed there.
+    pub fn tile(&self, pos: FieldPos) -> Option<IslandTile> {
+        self.tiles.get(pos).copied().flatten()
+    }
+
+    /// Make the cell part of the island or turn it into water. A tile on a cell
+    /// that is turned into water goes back to the palette.
+    ///
+    /// # Returns
+    /// `false` if the position is outside of the draft.
+    pub fn set_island(&mut self, pos: FieldPos, island: bool) -> bool {
+        if self.shape.get(pos).is_none() {
+            return false;
+        }
+
+        self.shape.set(pos, island);
+        if !island {
+            self.tiles.set(pos, None);
+        }
+        true
+    }
+
+    /// Put the tile on the cell, which becomes part of the island. If the tile
+    /// has already been placed somewhere else, it is moved. A tile that has
+    /// been on the cell before goes back to the palette.
+    ///
+    /// # Returns
+    /// `false` if the position is outside of the draft.
+    pub fn place(&mut self, pos: FieldPos, info: IslandTileInfo) -> bool {
+        if self.shape.get(pos).is_none() {
+            return false;
+        }
+
+        let previous = self.position_of(info);
+        let state = previous
+            .and_then(|previous| self.remove(previous))
+            .map_or(IslandTileState::Dry, |tile| tile.state());
+
+        let mut tile = IslandTile::new(info);
+        tile.set_state(state);
+        self.shape.set(pos, true);
+        self.tiles.set(pos, Some(tile));
+        true
+    }
+
+    /// Take the tile from the cell back to the palette. The cell stays part of
+    /// the island.
+    ///
+    /// # Returns
+    /// The tile that has been on the cell, if any.
+    pub fn remove(&mut self, pos: FieldPos) -> Option<IslandTile> {
+        self.tiles.get_mut(pos)?.take()
+    }
+
+    /// Set the state the tile on the cell is in when the game starts.
+    ///
+    /// # Returns
+    /// `false` if there is no tile on the cell.
+    pub fn set_state(&mut self, pos: FieldPos, state: IslandTileState) -> bool {
+        match self.tiles.get_mut(pos) {
+            Some(Some(tile)) => {
+                tile.set_state(state);
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Where the tile has been placed, if it has been placed at all.
+    pub fn position_of(&self, info: IslandTileInfo) -> Option<FieldPos> {
+        self.tiles
+            .iter()
+            .find(|(_, tile)| tile.map_or(false, |tile| *tile.info() == info))
+            .map(|(pos, _)| pos)
+    }
+
+    /// The tiles that have not been placed yet, in the order of
+    /// [IslandTileInfo::ALL].
+    pub fn unplaced(&self) -> Vec<IslandTileInfo> {
+        IslandTileInfo::ALL
+            .iter()
+            .copied()
+            .filter(|&info| self.position_of(info).is_none())
+            .collect()
+    }
+
+    /// Everything that has to be fixed before the draft can be played. The
+    /// draft is a valid map if there are none.
+    pub fn problems(&self) -> Vec<Problem> {
+        let mut problems: Vec<Problem> =
+            self.unplaced().into_iter().map(Problem::Unplaced).collect();
+
+        problems.extend(
+            self.shape
+                .filter_positions(|&island| island)
+                .filter(|&pos| self.tile(pos).is_none())
+                .map(Problem::EmptyCell)
+        );
+
+        // An island without any cells is reported as unplaced tiles already.
+        if let Ok(reachable) = bfs::reachable_positions(&self.shape, None) {
+            if reachable != self.shape {
+                problems.push(Problem::NotConnected);
+            }
+        }
+
+        problems.extend(self.tiles.iter().filter_map(|(_, tile)| match tile {
+            Some(tile) if tile.state() == IslandTileState::Gone => {
+                tile.info().player_spawn().map(Problem::SpawnGone)
+            }
+            _ => None
+        }));
+
+        problems
+    }
+
+    /// Turn the draft into a map that can be played.
+    ///
+    /// # Returns
+    /// The map, or everything that is still wrong with the draft.
+    pub fn to_map(&self) -> Result<Full, Vec<Problem>> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(self.tiles.clone())
+        }
+        else {
+            Err(problems)
+        }
+    }
+}
+
+impl Default for Draft {
+    fn default() -> Self { Self::new(Full::default().size()) }
+}
+
+fn is_valid_size(size: Vec2<u8>) -> bool {
+    size.x != 0 && size.y != 0 && size.x <= MAX_DRAFT_SIZE && size.y <= MAX_DRAFT_SIZE
+}
+
+fn check_size(size: Vec2<u8>) {
+    assert!(
+        is_valid_size(size),
+        "A draft must have between 1 and {} columns and rows",
+        MAX_DRAFT_SIZE
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::generate;
+
+    #[test]
+    fn place_every_tile_once() {
+        let mut draft = Draft::new(Vec2::from_values(3, 2));
+        assert_eq!(24, draft.unplaced().len());
+
+        let pos = Vec2::from_values(1, 1);
+        let other = Vec2::from_values(2, 1);
+        assert!(draft.place(pos, IslandTileInfo::Watchtower));
+        assert!(draft.is_island(pos));
+        assert!(draft.set_state(pos, IslandTileState::Flooded));
+        assert!(!draft.unplaced().contains(&IslandTileInfo::Watchtower));
+
+        // Placing it again moves it, with the state it is in.
+        assert!(draft.place(other, IslandTileInfo::Watchtower));
+        assert_eq!(None, draft.tile(pos));
+        assert_eq!(Some(other), draft.position_of(IslandTileInfo::Watchtower));
+        assert_eq!(IslandTileState::Flooded, draft.tile(other).unwrap().state());
+        assert_eq!(23, draft.unplaced().len());
+
+        // Another tile on the cell replaces it.
+        assert!(draft.place(other, IslandTileInfo::GoldGate));
+        assert!(draft.unplaced().contains(&IslandTileInfo::Watchtower));
+        assert!(!draft.place(Vec2::from_values(3, 0), IslandTileInfo::GoldGate));
+
+        // Water has no tiles.
+        assert!(draft.set_island(other, false));
+        assert_eq!(24, draft.unplaced().len());
+        assert!(!draft.set_state(other, IslandTileState::Gone));
+    }
+
+    #[test]
+    fn resize_keeps_the_tiles_inside() {
+        let mut draft = Draft::new(Vec2::from_values(3, 3));
+        draft.place(Vec2::from_values(0, 0), IslandTileInfo::IronGate);
+        draft.place(Vec2::from_values(2, 2), IslandTileInfo::CoralPalace);
+
+        draft.resize(Vec2::from_values(5, 2));
+        assert_eq!(Vec2::from_values(5, 2), draft.size());
+        assert_eq!(
+            Some(Vec2::from_values(0, 0)),
+            draft.position_of(IslandTileInfo::IronGate)
+        );
+        assert_eq!(None, draft.position_of(IslandTileInfo::CoralPalace));
+        assert!(!draft.is_island(Vec2::from_values(4, 1)));
+    }
+
+    #[test]
+    fn problems_of_a_draft() {
+        let map = generate::filled_island(&generate::classic_shape());
+        let mut draft = Draft::from_map(&map).unwrap();
+        assert_eq!(Ok(map.clone()), draft.to_map());
+
+        let fools_landing = draft.position_of(IslandTileInfo::FoolsLanding).unwrap();
+        draft.set_state(fools_landing, IslandTileState::Gone);
+        assert_eq!(
+            vec![Problem::SpawnGone(AdventurerType::Pilot)],
+            draft.problems()
+        );
+        draft.set_state(fools_landing, IslandTileState::Flooded);
+        assert!(draft.problems().is_empty());
+
+        // The top row of the classic shape is only connected to the rest of the
+        // island through the second row.
+        let removed: Vec<FieldPos> = (0..draft.size().x)
+            .map(|x| Vec2::from_values(x, 1))
+            .filter(|&pos| draft.is_island(pos))
+            .collect();
+        for &pos in &removed {
+            draft.set_island(pos, false);
+        }
+        let problems = draft.problems();
+        assert!(problems.contains(&Problem::NotConnected));
+        assert_eq!(
+            removed.len(),
+            problems
+                .iter()
+                .filter(|problem| matches!(problem, Problem::Unplaced(_)))
+                .count()
+        );
+
+        draft.set_island(Vec2::from_values(0, 0), true);
+        assert!(draft
+            .problems()
+            .contains(&Problem::EmptyCell(Vec2::from_values(0, 0))));
+    }
+
+    #[test]
+    fn refuses_maps_too_large() {
+        let size = Vec2::from_values(MAX_DRAFT_SIZE + 1, 200);
+        assert_eq!(
+            Err(Problem::BadSize(size)),
+            Draft::from_map(&Full::new(size, None))
+        );
+        assert!(Draft::from_map(&Full::new(Vec2::from_values(MAX_DRAFT_SIZE, 1), None)).is_ok());
+    }
+}
@@ -1,12 +1,12 @@
 //! Mapse, that can be played on contain 24 unique island tiles.
 
-use super::{FieldPos, IslandTile, IslandTileState, Map, MapExt, TILE_AMOUNT};
+use super::{FieldPos, IslandTile, IslandTileState, Map, MapCell};
 use crate::adventurer::AdventurerType;
 use crate::asset;
-use crate::math::{Rect, Vec2};
+use crate::math::Vec2;
 use amethyst::{
-    core::Transform,
-    ecs::{Component, DenseVecStorage, World, WorldExt},
+    core::{Hidden, Transform},
+    ecs::{Component, DenseVecStorage, Entity, World, WorldExt},
     prelude::*,
     renderer::SpriteRender
 };
@@ -27,36 +27,22 @@ pub const TILE_SCALE: f32 = 0.32;
 #[derive(Component)]
 pub struct FieldPosComp(FieldPos);
 
-impl MapExt for Map<Option<IslandTile>> {
-    fn is_standable(&self, pos: FieldPos) -> bool {
-        if let Some(Some(tile)) = self.get(pos) {
-            tile.state() != IslandTileState::Gone
-        }
-        else {
-            false
-        }
-    }
+impl FieldPosComp {
+    /// Place the entity this component belongs to at the position provided.
+    pub fn new(pos: FieldPos) -> Self { Self(pos) }
 
-    fn limit_rect(&self) -> Rect<u8> {
-        // Find the minimum rectangular hull around the island tiles and return it.
-        let mut min_pos = FieldPos::new();
-        let mut max_pos = FieldPos::new();
-
-        self.iter().for_each(|(pos, e)| {
-            if e.is_some() {
-                min_pos.x = min_pos.x.min(pos.x);
-                min_pos.y = min_pos.y.min(pos.y);
-                max_pos.x = max_pos.x.max(pos.x);
-                max_pos.y = max_pos.y.max(pos.y);
-            }
-        });
-
-        Rect::from_slice([
-            min_pos.x,
-            min_pos.y,
-            max_pos.x - min_pos.x,
-            max_pos.y - min_pos.y
-        ])
+    /// The position on the map of the entity this component belongs to.
+    pub fn pos(&self) -> FieldPos { self.0 }
+}
+
+impl MapCell for Option<IslandTile> {
+    fn is_island(&self) -> bool { self.is_some() }
+
+    fn is_standable(&self) -> bool {
+        match self {
+            Some(tile) => tile.state() != IslandTileState::Gone,
+            None => false
+        }
     }
 }
 
@@ -71,32 +57,47 @@ impl Full {
     pub fn transform(&self) -> &Transform { &self.transform }
     pub fn transform_mut(&mut self) -> &mut Transform { &mut self.transform }
 
-    pub fn create_tile_entities(&self, world: &mut World) {
+    /// Create the entities showing the tiles of the map, each with the sprite
+    /// of its tile in its current state. Tiles that are gone are hidden. The
+    /// [TileSpriteUpdate](crate::system::TileSpriteUpdate) system keeps the
+    /// sprites in line with the map resource afterwards.
+    ///
+    /// # Returns
+    /// The entities that have been created, so they can be removed again.
+    pub fn create_tile_entities(&self, world: &mut World) -> Vec<Entity> {
         let sprites = asset::load_sprite_sheet("tiles.png", "tiles_sheet.ron", world);
 
-        let mut sprite = 0;
-        for y in 0..self.height() {
-            for x in 0..self.width() {
-                let mut transform = self.transform.clone();
-                transform.set_scale(Vector3::new(TILE_SCALE, TILE_SCALE, TILE_SCALE));
-                transform.prepend_translation_x(TILE_WIDTH * x as f32 * TILE_SCALE);
-                transform.prepend_translation_y(TILE_HEIGHT * y as f32 * TILE_SCALE);
-
-                let sprite_render = SpriteRender {
-                    sprite_sheet:  sprites.clone(),
-                    sprite_number: sprite % TILE_AMOUNT as usize
-                };
-
-                world
-                    .create_entity()
-                    .with(sprite_render.clone())
-                    .with(FieldPosComp(FieldPos::from_values(x, y)))
-                    .with(transform)
-                    .build();
-
-                sprite += 1;
+        let mut entities = Vec::new();
+        for (pos, tile) in self.iter() {
+            let tile = match tile {
+                Some(tile) => *tile,
+                None => continue
+            };
+
+            let mut transform = self.transform.clone();
+            transform.set_scale(Vector3::new(TILE_SCALE, TILE_SCALE, TILE_SCALE));
+            transform.prepend_translation_x(TILE_WIDTH * pos.x as f32 * TILE_SCALE);
+            transform.prepend_translation_y(TILE_HEIGHT * pos.y as f32 * TILE_SCALE);
+
+            // A gone tile keeps the sprite of its dry side, in case it is
+            // raised again.
+            let sprite_render = SpriteRender {
+                sprite_sheet:  sprites.clone(),
+                sprite_number: tile.sprite_number().unwrap_or(*tile.info() as usize)
+            };
+
+            let mut builder = world
+                .create_entity()
+                .with(sprite_render)
+                .with(FieldPosComp(pos))
+                .with(transform);
+            if tile.sprite_number().is_none() {
+                builder = builder.with(Hidden);
             }
+            entities.push(builder.build());
         }
+
+        entities
     }
 
     /// Find the spawn point of the given adventurer type
@@ -136,6 +137,15 @@ impl From<Vec<Vec<Option<IslandTile>>>> for Full {
     }
 }
 
+impl From<Map<Option<IslandTile>>> for Full {
+    fn from(map: Map<Option<IslandTile>>) -> Self {
+        Self {
+            map,
+            transform: Transform::default()
+        }
+    }
+}
+
 impl Default for Full {
     fn default() -> Self { Self::new(Vec2::from_values(10, 7), None) }
 }
@@ -171,12 +181,47 @@ pub fn calculate_field_translation(map_transform: &Transform, field_pos: FieldPo
     Vec2::from_values(transform.translation().x, transform.translation().y)
 }
 
+/// The inverse of [calculate_field_translation]. Finds the field a point in the
+/// world lies on. Tiles are centred on their translation, so a field reaches
+/// half a tile in every direction from it.
+///
+/// # Parameters
+/// `map_transform` - The base transform of the map, i.e. where the map is on
+/// the screen
+/// `point` - The point in world coordinates, for instance where the cursor is
+///
+/// # Returns
+/// The position of the field, or `None` if the point is left of or below the
+/// map. Whether the position is still on the map has to be checked
+/// separately, since the transform does not know the size of the map.
+pub fn calculate_field_pos(map_transform: &Transform, point: Vec2<f32>) -> Option<FieldPos> {
+    let origin = map_transform.translation();
+    let x = ((point.x - origin.x) / (TILE_WIDTH * TILE_SCALE) + 0.5).floor();
+    let y = ((point.y - origin.y) / (TILE_HEIGHT * TILE_SCALE) + 0.5).floor();
+
+    let limit = f32::from(u8::MAX);
+    if x < 0. || y < 0. || x > limit || y > limit {
+        return None;
+    }
+    Some(FieldPos::from_values(x as u8, y as u8))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::map::IslandTileInfo;
+    use crate::map::{IslandTileInfo, TILE_AMOUNT};
     use std::mem;
 
+    #[test]
+    fn tile_sprites() {
+        let mut tile = IslandTile::new(IslandTileInfo::Watchtower);
+        assert_eq!(Some(6), tile.sprite_number());
+        tile.set_state(IslandTileState::Flooded);
+        assert_eq!(Some(TILE_AMOUNT as usize + 6), tile.sprite_number());
+        tile.set_state(IslandTileState::Gone);
+        assert_eq!(None, tile.sprite_number());
+    }
+
     #[test]
     fn spawn_point_valid() {
         let mut map = Full::new(Vec2::from_values(6, 4), None);
@@ -222,6 +267,27 @@ mod test {
         map.spawn_point(AdventurerType::Pilot);
     }
 
+    #[test]
+    fn field_pos_is_inverse_of_translation() {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(64., 64., -1.);
+        let tile = TILE_WIDTH * TILE_SCALE;
+
+        for &(x, y) in &[(0, 0), (3, 1), (9, 6)] {
+            let pos = Vec2::from_values(x, y);
+            let centre = calculate_field_translation(&transform, pos);
+            assert_eq!(Some(pos), calculate_field_pos(&transform, centre));
+
+            let corner = Vec2::from_values(centre.x + tile * 0.49, centre.y - tile * 0.49);
+            assert_eq!(Some(pos), calculate_field_pos(&transform, corner));
+        }
+
+        assert_eq!(
+            None,
+            calculate_field_pos(&transform, Vec2::from_values(64. - tile * 0.6, 100.))
+        );
+    }
+
     #[test]
     fn cover_untestable() {
         let mut map = Full::new(
@@ -1,7 +1,8 @@
 //! Utilities to generate maps
 
-use super::{BlackWhite, Full};
-use crate::math::Rect;
+use super::{BlackWhite, Full, IslandTile, IslandTileInfo, MapExt, TILE_AMOUNT};
+use crate::math::{Rect, Vec2};
+use rand::{seq::SliceRandom, thread_rng, Rng};
 
 /// Generate a valid black and white island.
 ///
@@ -12,6 +13,35 @@ use crate::math::Rect;
 /// If `limit_rect` cannot hold the 24 tiles necessary for a valid map.
 pub fn black_white_island(_limit_rect: Rect<u8>) -> BlackWhite { unimplemented!() }
 
+/// The shape of the island as it is laid out in the original board game. It
+/// is a 6x6 square with three tiles cut out at every corner: the corner itself
+/// and the two next to it.
+pub fn classic_shape() -> BlackWhite {
+    BlackWhite::from_fn(Vec2::from_values(6, 6), |pos| {
+        // Distance from the nearest corner in both directions
+        let dx = pos.x.min(5 - pos.x);
+        let dy = pos.y.min(5 - pos.y);
+        dx + dy >= 2
+    })
+}
+
+/// The island as it is laid out in the original board game, with the tiles in
+/// the order of [IslandTileInfo::ALL], row by row. Every game on it starts with
+/// the same layout.
+pub fn classic_island() -> Full {
+    let shape = classic_shape();
+
+    let mut map = Full::new(shape.size(), None);
+    for (pos, &info) in shape
+        .filter_positions(|&island| island)
+        .zip(IslandTileInfo::ALL.iter())
+    {
+        map.set(pos, Some(IslandTile::new(info)));
+    }
+
+    map
+}
+
 /// Generate a random distribution of the 24 possible tiles on a black and white
 /// map.
 ///
@@ -20,4 +50,81 @@ pub fn black_white_island(_limit_rect: Rect<u8>) -> BlackWhite { unimplemented!(
 ///
 /// # Panics
 /// If the shape is not a valid black and white map
-pub fn filled_island(_shape: &BlackWhite) -> Full { unimplemented!() }
+pub fn filled_island(shape: &BlackWhite) -> Full { filled_island_with(shape, &mut thread_rng()) }
+
+/// Generate a random distribution of the 24 possible tiles on a black and white
+/// map, using the random number generator provided.
+///
+/// # Parameters
+/// `shape` - The shape the island should have.
+/// `rng` - The source of randomness for the distribution of the tiles.
+///
+/// # Panics
+/// If the shape is not a valid black and white map
+pub fn filled_island_with<R: Rng + ?Sized>(shape: &BlackWhite, rng: &mut R) -> Full {
+    assert_eq!(
+        TILE_AMOUNT as usize,
+        shape
+            .positions()
+            .filter(|&pos| shape.is_island(pos))
+            .count(),
+        "The shape must have exactly one island cell per tile"
+    );
+
+    let mut tiles = IslandTileInfo::ALL.to_vec();
+    tiles.shuffle(rng);
+
+    let mut map = Full::new(shape.size(), None);
+    for pos in shape.filter_positions(|&island| island) {
+        map.set(pos, Some(IslandTile::new(tiles.pop().unwrap())));
+    }
+
+    map
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bfs;
+    use crate::map::Draft;
+
+    #[test]
+    fn classic_shape_valid() {
+        let shape = classic_shape();
+
+        assert_eq!(
+            TILE_AMOUNT as usize,
+            shape.filter_positions(|&island| island).count()
+        );
+        let reachable = bfs::reachable_positions(&shape, None).unwrap();
+        assert_eq!(shape, reachable);
+    }
+
+    #[test]
+    fn filled_island_uses_every_tile_once() {
+        let map = filled_island(&classic_shape());
+
+        for info in IslandTileInfo::ALL.iter() {
+            assert_eq!(
+                1,
+                map.iter()
+                    .filter(|(_, tile)| tile.map_or(false, |t| t.info() == info))
+                    .count()
+            );
+        }
+    }
+
+    #[test]
+    fn classic_island_valid() {
+        let map = classic_island();
+
+        assert!(Draft::from_map(&map).unwrap().problems().is_empty());
+        assert_eq!(map, classic_island());
+    }
+
+    #[test]
+    #[should_panic]
+    fn filled_island_invalid_shape() {
+        filled_island(&BlackWhite::new(Vec2::from_values(2, 2), true));
+    }
+}
@@ -1,10 +1,12 @@
 use amethyst::ecs::{Component, DenseVecStorage};
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 
 use crate::adventurer::AdventurerType;
 use crate::artefact_type::ArtefactType;
+use crate::map::TILE_AMOUNT;
 
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IslandTileInfo {
     CaveOfShadows = 0,
     FoolsLanding,
@@ -33,7 +35,7 @@ pub enum IslandTileInfo {
 }
 
 /// The different states an island can be.
-#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum IslandTileState {
     /// The island is dry. Players can freely do anything on these tiles.
     Dry,
@@ -48,13 +50,71 @@ pub enum IslandTileState {
 }
 
 /// Represents one of 24 island map tiles.
-#[derive(Copy, Clone, Component, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Copy, Clone, Component, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct IslandTile {
     state: IslandTileState,
     info:  IslandTileInfo
 }
 
 impl IslandTileInfo {
+    /// All 24 island tiles of a valid map, in the order of their discriminant.
+    pub const ALL: [IslandTileInfo; TILE_AMOUNT as usize] = [
+        Self::CaveOfShadows,
+        Self::FoolsLanding,
+        Self::CliffsOfAbandon,
+        Self::BreakersBridge,
+        Self::CrimsonForest,
+        Self::TwighlightHorrow,
+        Self::Watchtower,
+        Self::MistyMarsh,
+        Self::Observatory,
+        Self::DunesOfDeception,
+        Self::LostLagoon,
+        Self::PhantomRock,
+        Self::GoldGate,
+        Self::IronGate,
+        Self::BronzeGate,
+        Self::CopperGate,
+        Self::SilverGate,
+        Self::TempleOfTheMoon,
+        Self::TempleOfTheSun,
+        Self::WhisperingGarden,
+        Self::HowlingGarden,
+        Self::CoralPalace,
+        Self::TidalPalace,
+        Self::CaveOfAmbers
+    ];
+
+    /// The name of the tile as printed on the card.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::CaveOfShadows => "Cave of Shadows",
+            Self::FoolsLanding => "Fools' Landing",
+            Self::CliffsOfAbandon => "Cliffs of Abandon",
+            Self::BreakersBridge => "Breakers Bridge",
+            Self::CrimsonForest => "Crimson Forest",
+            Self::TwighlightHorrow => "Twilight Hollow",
+            Self::Watchtower => "Watchtower",
+            Self::MistyMarsh => "Misty Marsh",
+            Self::Observatory => "Observatory",
+            Self::DunesOfDeception => "Dunes of Deception",
+            Self::LostLagoon => "Lost Lagoon",
+            Self::PhantomRock => "Phantom Rock",
+            Self::GoldGate => "Gold Gate",
+            Self::IronGate => "Iron Gate",
+            Self::BronzeGate => "Bronze Gate",
+            Self::CopperGate => "Copper Gate",
+            Self::SilverGate => "Silver Gate",
+            Self::TempleOfTheMoon => "Temple of the Moon",
+            Self::TempleOfTheSun => "Temple of the Sun",
+            Self::WhisperingGarden => "Whispering Garden",
+            Self::HowlingGarden => "Howling Garden",
+            Self::CoralPalace => "Coral Palace",
+            Self::TidalPalace => "Tidal Palace",
+            Self::CaveOfAmbers => "Cave of Embers"
+        }
+    }
+
     pub fn hidden_artefact(self) -> Option<ArtefactType> {
         match self {
             Self::CaveOfShadows => Some(ArtefactType::Fire),
@@ -82,6 +142,43 @@ impl IslandTileInfo {
     }
 }
 
+impl FromStr for IslandTileInfo {
+    type Err = String;
+
+    /// Parse the tile from its name, ignoring the case, spaces and
+    /// apostrophes, so both `FoolsLanding` and `fools' landing` are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let simplify = |name: &str| -> String {
+            name.chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let wanted = simplify(s);
+
+        Self::ALL
+            .iter()
+            .find(|info| {
+                simplify(info.name()) == wanted || simplify(&format!("{:?}", info)) == wanted
+            })
+            .copied()
+            .ok_or_else(|| format!("Unknown tile: {}", s))
+    }
+}
+
+impl FromStr for IslandTileState {
+    type Err = String;
+
+    /// Parse the state from its name, ignoring the case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [Self::Dry, Self::Flooded, Self::Gone]
+            .iter()
+            .find(|state| format!("{:?}", state).eq_ignore_ascii_case(s.trim()))
+            .copied()
+            .ok_or_else(|| format!("Unknown tile state: {}", s))
+    }
+}
+
 impl IslandTile {
     pub fn new(info: IslandTileInfo) -> Self {
         Self {
@@ -95,4 +192,20 @@ impl IslandTile {
     pub fn set_state(&mut self, state: IslandTileState) { self.state = state; }
 
     pub fn info(&self) -> &IslandTileInfo { &self.info }
+
+    /// The sprite of the tile in `tiles_sheet.ron`. The dry tiles come first,
+    /// followed by the flooded ones, both in the order of
+    /// [IslandTileInfo::ALL].
+    ///
+    /// # Returns
+    /// The number of the sprite, or `None` if the tile is gone and shows
+    /// nothing but water.
+    pub fn sprite_number(self) -> Option<usize> {
+        let index = self.info as usize;
+        match self.state {
+            IslandTileState::Dry => Some(index),
+            IslandTileState::Flooded => Some(TILE_AMOUNT as usize + index),
+            IslandTileState::Gone => None
+        }
+    }
 }
@@ -7,6 +7,12 @@
 pub mod black_white;
 pub use self::black_white::*;
 
+pub mod choice;
+pub use self::choice::*;
+
+pub mod draft;
+pub use self::draft::*;
+
 pub mod full;
 pub use self::full::*;
 
@@ -17,10 +23,11 @@ pub use self::island_tile::*;
 
 pub mod loader;
 
-use crate::iter_2d::Iter2d;
+use crate::iter_2d::{Iter2d, IterMut2d, Positions};
 use crate::math::{Rect, Vec2};
-use serde::{Deserialize, Serialize};
-use std::ops::{Deref, DerefMut};
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
+use std::iter::StepBy;
+use std::{fmt, mem, slice, u8};
 
 /// The number of tiles that are in a single map when it is valid. In later
 /// versions, this could vary.
@@ -30,22 +37,107 @@ pub const TILE_AMOUNT: u8 = 24;
 /// map.
 pub type FieldPos = Vec2<u8>;
 
+/// A single cell of a map, which is either part of the island or water.
+pub trait MapCell {
+    /// Returns true, if there is or was an island tile in this cell, even if
+    /// it has sunk since.
+    fn is_island(&self) -> bool;
+
+    /// Returns true, if a positionable object may be placed on this cell.
+    fn is_standable(&self) -> bool;
+}
+
 pub trait MapExt {
     /// Get the [Rect](Rect<u8>) this map is bounded by, meaning that beyond it,
     /// there is only water and therefore it is considered out of bounds for
     /// anything that is placeable on the island. Does not mean all tiles
     /// inside it are island tiles, since not all maps are rectangular.
+    ///
+    /// If there are no island tiles at all, an empty rect at the origin is
+    /// returned.
     fn limit_rect(&self) -> Rect<u8>;
 
+    /// Checks if there is or was an island tile at the [FieldPos] provided.
+    /// Returns false if the position is outside of the map.
+    fn is_island(&self, _pos: FieldPos) -> bool;
+
     /// Checks, if a positionable object may be placed on the [FieldPos]
     /// provided, which means it returns false in case there is no island
     /// tile or it's gone.
     fn is_standable(&self, _pos: FieldPos) -> bool;
+
+    /// Project the map onto a black and white map, where every island cell is
+    /// `true` and the water is `false`.
+    fn black_white(&self) -> BlackWhite;
+}
+
+impl<T: MapCell> MapExt for Map<T> {
+    fn limit_rect(&self) -> Rect<u8> {
+        // Find the minimum rectangular hull around the island tiles and return it.
+        let mut bounds: Option<(FieldPos, FieldPos)> = None;
+        for pos in self.filter_positions(MapCell::is_island) {
+            bounds = Some(match bounds {
+                Some((min, max)) => (
+                    Vec2::from_values(min.x.min(pos.x), min.y.min(pos.y)),
+                    Vec2::from_values(max.x.max(pos.x), max.y.max(pos.y))
+                ),
+                None => (pos, pos)
+            });
+        }
+
+        match bounds {
+            Some((min_pos, max_pos)) => Rect::from_slice([
+                min_pos.x,
+                min_pos.y,
+                max_pos.x - min_pos.x,
+                max_pos.y - min_pos.y
+            ]),
+            None => Rect::default()
+        }
+    }
+
+    fn is_island(&self, pos: FieldPos) -> bool { self.get(pos).map_or(false, MapCell::is_island) }
+
+    fn is_standable(&self, pos: FieldPos) -> bool {
+        self.get(pos).map_or(false, MapCell::is_standable)
+    }
+
+    fn black_white(&self) -> BlackWhite { self.map(|_, cell| cell.is_island()) }
+}
+
+/// Errors that can occur when a [Map] is created from raw data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The rows of the raw data do not all have the same length.
+    UnevenRows,
+    /// The amount of items provided does not match the size of the map.
+    SizeMismatch { expected: usize, actual: usize },
+    /// The map would be wider or higher than a [FieldPos] can address.
+    TooLarge
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnevenRows => write!(f, "not all rows of the map have the same length"),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "the map requires {} items, but {} were provided",
+                expected, actual
+            ),
+            Self::TooLarge => write!(f, "the map is too large to be addressed by field positions")
+        }
+    }
 }
 
-#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+/// Rectangular grid of items, addressed by [FieldPos]. The items are stored
+/// row by row in one contiguous buffer, so every row is guaranteed to have the
+/// same length.
+#[derive(Clone, Debug, PartialEq)]
 pub struct Map<T> {
-    data: Vec<Vec<T>>
+    data:   Vec<T>,
+    width:  u8,
+    height: u8
 }
 
 impl<T> Map<T> {
@@ -55,30 +147,89 @@ impl<T> Map<T> {
         T: Clone
     {
         Self {
-            data: vec![vec![fill_value; size.x as usize]; size.y as usize]
+            data:   vec![fill_value; size.x as usize * size.y as usize],
+            width:  size.x,
+            height: size.y
         }
     }
 
+    /// Create a new Map, where every item is created by calling the provided
+    /// function with its position. The positions are visited row by row.
+    pub fn from_fn<F>(size: Vec2<u8>, mut f: F) -> Self
+    where
+        F: FnMut(FieldPos) -> T
+    {
+        let mut data = Vec::with_capacity(size.x as usize * size.y as usize);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                data.push(f(Vec2::from_values(x, y)));
+            }
+        }
+
+        Self {
+            data,
+            width: size.x,
+            height: size.y
+        }
+    }
+
+    /// Create a Map of the given size from items that are already laid out row
+    /// by row.
+    ///
+    /// # Returns
+    /// The map, or [Error::SizeMismatch] if the amount of items does not match
+    /// the size requested.
+    pub fn from_vec(size: Vec2<u8>, data: Vec<T>) -> Result<Self, Error> {
+        let expected = size.x as usize * size.y as usize;
+        if data.len() != expected {
+            return Err(Error::SizeMismatch {
+                expected,
+                actual: data.len()
+            });
+        }
+
+        Ok(Self {
+            data,
+            width: size.x,
+            height: size.y
+        })
+    }
+
+    /// Create a Map from a list of rows. All rows must have the same length.
+    ///
+    /// # Returns
+    /// The map, [Error::UnevenRows] if the rows differ in length or
+    /// [Error::TooLarge] if there are more than 255 rows or columns.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, Error> {
+        let width = rows.get(0).map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(Error::UnevenRows);
+        }
+        if width > u8::MAX as usize || rows.len() > u8::MAX as usize {
+            return Err(Error::TooLarge);
+        }
+
+        let height = rows.len() as u8;
+        let data = rows.into_iter().flatten().collect();
+        Ok(Self {
+            data,
+            width: width as u8,
+            height
+        })
+    }
+
     /// Get the item at the provided position of the map or `None`, if there is
     /// no item at the position.
     pub fn get(&self, pos: FieldPos) -> Option<&T> {
-        if let Some(line) = self.data.get(pos.y as usize) {
-            line.get(pos.x as usize)
-        }
-        else {
-            None
-        }
+        let index = self.index(pos)?;
+        self.data.get(index)
     }
 
     /// Get the item at the provided position of the map mutably or `None`, if
     /// there is no item at the position.
     pub fn get_mut(&mut self, pos: FieldPos) -> Option<&mut T> {
-        if let Some(line) = self.data.get_mut(pos.y as usize) {
-            line.get_mut(pos.x as usize)
-        }
-        else {
-            None
-        }
+        let index = self.index(pos)?;
+        self.data.get_mut(index)
     }
 
     /// Set the item at the provided position of the map. Returns the item that
@@ -88,62 +239,189 @@ impl<T> Map<T> {
     /// If the index is out of bounds. The map will not be resized in this
     /// function.
     pub fn set(&mut self, pos: FieldPos, new: T) -> T {
-        assert!(pos.x < self.width());
-        assert!(pos.y < self.height());
+        let index = self
+            .index(pos)
+            .expect("Position is out of the bounds of the map");
 
-        // Push the new item to the end of the line in question and swap it with the
-        // element at the x position afterwards.
-        self.data[pos.y as usize].push(new);
-        self.data[pos.y as usize].swap_remove(pos.x as usize)
+        mem::replace(&mut self.data[index], new)
     }
 
     /// Iterator over all map tiles.
-    pub fn iter(&self) -> Iter2d<T> { Iter2d::new(&self.data) }
+    pub fn iter(&self) -> Iter2d<T> { Iter2d::new(&self.data, self.width) }
 
-    /// Amount of tiles in the x-direction.
-    pub fn width(&self) -> u8 {
-        if self.data.is_empty() {
-            0
+    /// Iterator over all map tiles, that allows changing them.
+    pub fn iter_mut(&mut self) -> IterMut2d<T> { IterMut2d::new(&mut self.data, self.width) }
+
+    /// Iterator over all positions of the map, row by row.
+    pub fn positions(&self) -> Positions { Positions::new(self.size()) }
+
+    /// Iterator over the positions of all items the predicate returns true for.
+    pub fn filter_positions<'a, P>(&'a self, mut pred: P) -> impl Iterator<Item = FieldPos> + 'a
+    where
+        P: FnMut(&T) -> bool + 'a
+    {
+        self.iter().filter_map(move |(pos, e)| {
+            if pred(e) {
+                Some(pos)
+            }
+            else {
+                None
+            }
+        })
+    }
+
+    /// Iterator over the neighbours in the primary directions (up, down, left,
+    /// right) of the position provided, together with their items. Positions
+    /// outside of the map are left out.
+    pub fn neighbours<'a>(&'a self, pos: FieldPos) -> impl Iterator<Item = (FieldPos, &'a T)> + 'a {
+        pos.neighbours(None)
+            .into_iter()
+            .filter_map(move |nb| self.get(nb).map(|e| (nb, e)))
+    }
+
+    /// The row at the provided y-coordinate or `None`, if the map is not that
+    /// high.
+    pub fn row(&self, y: u8) -> Option<&[T]> {
+        if y < self.height {
+            let start = y as usize * self.width as usize;
+            Some(&self.data[start..start + self.width as usize])
+        }
+        else {
+            None
+        }
+    }
+
+    /// The row at the provided y-coordinate mutably or `None`, if the map is
+    /// not that high.
+    pub fn row_mut(&mut self, y: u8) -> Option<&mut [T]> {
+        if y < self.height {
+            let start = y as usize * self.width as usize;
+            Some(&mut self.data[start..start + self.width as usize])
+        }
+        else {
+            None
+        }
+    }
+
+    /// Iterator over all rows of the map from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |y| self.row(y).unwrap())
+    }
+
+    /// Iterator over the items of the column at the provided x-coordinate from
+    /// top to bottom or `None`, if the map is not that wide.
+    pub fn column(&self, x: u8) -> Option<StepBy<slice::Iter<T>>> {
+        if x < self.width {
+            Some(self.data[x as usize..].iter().step_by(self.width as usize))
         }
         else {
-            self.data[0].len() as u8
+            None
+        }
+    }
+
+    /// Create a new map of the same size, where every item has been converted
+    /// by the function provided.
+    pub fn map<U, F>(&self, mut f: F) -> Map<U>
+    where
+        F: FnMut(FieldPos, &T) -> U
+    {
+        Map {
+            data:   self.iter().map(|(pos, e)| f(pos, e)).collect(),
+            width:  self.width,
+            height: self.height
         }
     }
 
+    /// Combine this map with another map of the same size item by item.
+    ///
+    /// # Panics
+    /// If the maps do not have the same size.
+    pub fn zip<U, V, F>(&self, other: &Map<U>, mut f: F) -> Map<V>
+    where
+        F: FnMut(FieldPos, &T, &U) -> V
+    {
+        assert_eq!(
+            self.size(),
+            other.size(),
+            "Only maps of the same size can be zipped"
+        );
+
+        Map {
+            data:   self
+                .iter()
+                .zip(other.data.iter())
+                .map(|((pos, a), b)| f(pos, a, b))
+                .collect(),
+            width:  self.width,
+            height: self.height
+        }
+    }
+
+    /// All items of the map, row by row.
+    pub fn as_slice(&self) -> &[T] { &self.data }
+
+    /// Amount of tiles in the x-direction.
+    pub fn width(&self) -> u8 { self.width }
+
     /// Amount of tiles in the y-direction.
-    pub fn height(&self) -> u8 { self.data.len() as u8 }
+    pub fn height(&self) -> u8 { self.height }
 
     /// Amount of tiles in the x and y-direction.
     pub fn size(&self) -> Vec2<u8> { Vec2::from_values(self.width(), self.height()) }
+
+    // Index of the position in the internal buffer, or `None` if it is outside of
+    // the map.
+    fn index(&self, pos: FieldPos) -> Option<usize> {
+        if pos.x < self.width && pos.y < self.height {
+            Some(pos.y as usize * self.width as usize + pos.x as usize)
+        }
+        else {
+            None
+        }
+    }
 }
 
 impl<T> From<Vec<Vec<T>>> for Map<T> {
+    /// Create the map from its rows.
+    ///
+    /// # Panics
+    /// If the rows do not all have the same length or there are too many of
+    /// them. Use [Map::from_rows] to handle these cases.
     fn from(from: Vec<Vec<T>>) -> Self {
-        #[cfg(Debug)]
-        {
-            // The map can only be constructed, if all lines of the raw data are the same
-            // length, since this function does not fill empty space.
-            let len = if let Some(line) = from.get(0) {
-                line.len()
-            }
-            else {
-                0
-            };
-            assert_eq!(true, from.iter().all(|line| line.len() == len));
+        match Self::from_rows(from) {
+            Ok(map) => map,
+            Err(err) => panic!("Unable to create map: {}", err)
         }
-
-        Self { data: from }
     }
 }
 
-impl<T> Deref for Map<T> {
-    type Target = Vec<Vec<T>>;
-
-    fn deref(&self) -> &Self::Target { &self.data }
+// The map is saved row by row, so that map files stay readable and every row
+// is checked to have the same length when it is loaded again.
+impl<T: Serialize> Serialize for Map<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer
+    {
+        let mut state = serializer.serialize_struct("Map", 1)?;
+        state.serialize_field("data", &self.rows().collect::<Vec<_>>())?;
+        state.end()
+    }
 }
 
-impl<T> DerefMut for Map<T> {
-    fn deref_mut(&mut self) -> &mut Self::Target { &mut self.data }
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Map<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "Map")]
+        struct Rows<T> {
+            data: Vec<Vec<T>>
+        }
+
+        let rows = Rows::deserialize(deserializer)?;
+        Self::from_rows(rows.data).map_err(de::Error::custom)
+    }
 }
 
 #[cfg(test)]
@@ -170,4 +448,169 @@ mod tests {
 
         assert_ne!(map_a, map_b);
     }
+
+    #[test]
+    fn limit_rect_shrinks_from_top_left() {
+        let map: BlackWhite = vec![
+            vec![false, false, false, false],
+            vec![false, false, true, false],
+            vec![false, true, true, false],
+            vec![false, false, false, false],
+        ]
+        .into();
+
+        let rect = map.limit_rect();
+        assert_eq!(
+            (1, 1, 1, 1),
+            (rect.x, rect.y, rect.w, rect.h),
+            "Bounding box should only contain the island tiles"
+        );
+    }
+
+    #[test]
+    fn limit_rect_no_island() {
+        let map = BlackWhite::new(Vec2::from_values(3, 3), false);
+
+        let rect = map.limit_rect();
+        assert_eq!((0, 0, 0, 0), (rect.x, rect.y, rect.w, rect.h));
+    }
+
+    #[test]
+    fn full_to_black_white() {
+        let mut gone = IslandTile::new(IslandTileInfo::GoldGate);
+        gone.set_state(IslandTileState::Gone);
+        let full: Full = vec![
+            vec![None, Some(IslandTile::new(IslandTileInfo::IronGate))],
+            vec![Some(gone), None],
+        ]
+        .into();
+
+        let expected: BlackWhite = vec![vec![false, true], vec![true, false]].into();
+        assert_eq!(expected, full.black_white());
+        assert!(full.is_island(Vec2::from_values(0, 1)));
+        assert!(!full.is_standable(Vec2::from_values(0, 1)));
+    }
+
+    #[test]
+    fn from_rows_uneven() {
+        let rows = vec![vec![1, 2, 3], vec![4, 5]];
+
+        assert_eq!(Err(Error::UnevenRows), Map::from_rows(rows));
+    }
+
+    #[test]
+    fn from_vec_size_mismatch() {
+        assert_eq!(
+            Err(Error::SizeMismatch {
+                expected: 6,
+                actual:   5
+            }),
+            Map::from_vec(Vec2::from_values(3, 2), vec![0; 5])
+        );
+        assert!(Map::from_vec(Vec2::from_values(3, 2), vec![0; 6]).is_ok());
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_uneven_panics() { let _: Map<u8> = vec![vec![1], vec![]].into(); }
+
+    #[test]
+    fn get_set() {
+        let mut map = Map::new(Vec2::from_values(3, 2), 0);
+        assert_eq!(0, map.set(Vec2::from_values(2, 1), 5));
+        assert_eq!(5, map.set(Vec2::from_values(2, 1), 7));
+
+        assert_eq!(Some(&7), map.get(Vec2::from_values(2, 1)));
+        assert_eq!(None, map.get(Vec2::from_values(3, 0)));
+        assert_eq!(None, map.get(Vec2::from_values(0, 2)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_out_of_bounds() {
+        let mut map = Map::new(Vec2::from_values(3, 2), 0);
+        map.set(Vec2::from_values(3, 0), 1);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let map: Map<u8> = vec![vec![0, 1, 2], vec![3, 4, 5]].into();
+
+        assert_eq!(Some(&[3, 4, 5][..]), map.row(1));
+        assert_eq!(None, map.row(2));
+        assert_eq!(
+            vec![&[0, 1, 2][..], &[3, 4, 5][..]],
+            map.rows().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![&1, &4], map.column(1).unwrap().collect::<Vec<_>>());
+        assert!(map.column(3).is_none());
+    }
+
+    #[test]
+    fn iter_positions() {
+        let map = Map::from_fn(Vec2::from_values(3, 2), |pos| pos);
+
+        for (pos, &e) in map.iter() {
+            assert_eq!(pos, e);
+        }
+        assert_eq!(6, map.iter().count());
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut map = Map::new(Vec2::from_values(3, 2), 0);
+        for (pos, e) in map.iter_mut() {
+            *e = pos.x + pos.y * 10;
+        }
+
+        assert_eq!(Some(&12), map.get(Vec2::from_values(2, 1)));
+    }
+
+    #[test]
+    fn filter_positions() {
+        let map: Map<u8> = vec![vec![0, 1, 0], vec![1, 0, 1]].into();
+
+        assert_eq!(
+            vec![
+                Vec2::from_values(1, 0),
+                Vec2::from_values(0, 1),
+                Vec2::from_values(2, 1),
+            ],
+            map.filter_positions(|&e| e == 1).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let map = Map::from_fn(Vec2::from_values(3, 2), |pos| pos.x + pos.y * 3);
+
+        let corner: Vec<(FieldPos, u8)> = map
+            .neighbours(Vec2::from_values(2, 1))
+            .map(|(pos, &e)| (pos, e))
+            .collect();
+        assert_eq!(2, corner.len());
+        assert!(corner.contains(&(Vec2::from_values(2, 0), 2)));
+        assert!(corner.contains(&(Vec2::from_values(1, 1), 4)));
+        assert_eq!(3, map.neighbours(Vec2::from_values(1, 0)).count());
+    }
+
+    #[test]
+    fn map_and_zip() {
+        let a: Map<u8> = vec![vec![0, 1], vec![2, 3]].into();
+        let b = a.map(|_, &e| e * 2);
+        let sum = a.zip(&b, |_, &a, &b| a + b);
+
+        let expected: Map<u8> = vec![vec![0, 3], vec![6, 9]].into();
+        assert_eq!(expected, sum);
+    }
+
+    #[test]
+    fn serde_checks_rows() {
+        let map: Map<u8> = vec![vec![0, 1], vec![2, 3]].into();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!("{\"data\":[[0,1],[2,3]]}", json);
+        assert_eq!(map, serde_json::from_str(&json).unwrap());
+
+        assert!(serde_json::from_str::<Map<u8>>("{\"data\":[[0,1],[2]]}").is_err());
+    }
 }
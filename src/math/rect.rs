@@ -22,8 +22,8 @@ impl Rect<f32> {
     /// the size, with the transforms x and y being in the middle.
     pub fn from_transform_as_middle(transform: &Transform, size: Vec2<f32>) -> Rect<f32> {
         Rect {
-            x: transform.translation().x + (size.x / 2.),
-            y: transform.translation().y + (size.y / 2.),
+            x: transform.translation().x - (size.x / 2.),
+            y: transform.translation().y - (size.y / 2.),
             w: size.x,
             h: size.y
         }
@@ -124,4 +124,14 @@ mod test {
 
         assert!(Rect::intersect(&a, &b));
     }
+
+    #[test]
+    fn test_from_transform_as_middle() {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(10., 20., 0.);
+
+        let rect = Rect::from_transform_as_middle(&transform, Vec2::from_values(4., 6.));
+        assert!(rect.contains(Vec2::from_values(10., 20.)));
+        assert_eq!((8., 17.), (rect.x, rect.y));
+    }
 }
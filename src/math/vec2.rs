@@ -1,6 +1,7 @@
 use alga::general::{Additive, ClosedAdd, ClosedSub, Identity, Multiplicative};
 use nalgebra::{RealField, Scalar};
 use num::{CheckedAdd, CheckedSub, Integer};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
@@ -8,7 +9,7 @@ use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 use crate::direction::Direction;
 use crate::math::Rect;
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd, Eq, Hash, Serialize, Deserialize)]
 pub struct Vec2<T: Scalar> {
     pub x: T,
     pub y: T
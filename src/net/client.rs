@@ -0,0 +1,236 @@
+//! Connection of a player to a [Server](super::Server). The connection is kept
+//! on its own thread, so the frontend can poll for messages once per frame
+//! without ever blocking. When the connection is lost, the client connects
+//! again by itself and takes back its seat. Spectators use the same client to
+//! watch the game.
+
+use super::protocol::*;
+use crate::game_state::GameAction;
+use std::io::{self, BufReader};
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The time between two attempts to reach the server.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Everything that can happen to the connection.
+#[derive(Clone, Debug)]
+pub enum ClientEvent {
+    /// A message has been received from the server.
+    Message(ServerMessage),
+    /// The connection to the server has been lost. The client keeps trying to
+    /// reconnect until it is dropped or refused by the server.
+    Disconnected
+}
+
+/// A player connected to a game server.
+pub struct Client {
+    events: Receiver<ClientEvent>,
+    writer: Arc<Mutex<Option<TcpStream>>>,
+    closed: Arc<AtomicBool>
+}
+
+impl Client {
+    /// Start connecting to the server at the address. The name is shown to
+    /// the other players. The connection is established in the background, so
+    /// this returns immediately.
+    pub fn connect(address: String, name: String) -> Self { Self::start(address, Some(name)) }
+
+    /// Start connecting to the server at the address to watch the game as a
+    /// spectator. Like [Client::connect], this returns immediately.
+    pub fn watch(address: String) -> Self { Self::start(address, None) }
+
+    // Connect in the background, either as the player with the name or as a
+    // spectator without one.
+    fn start(address: String, name: Option<String>) -> Self {
+        let (sender, events) = mpsc::channel();
+        let writer = Arc::new(Mutex::new(None));
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let connection = Connection {
+            address,
+            name,
+            token: None,
+            events: sender,
+            writer: Arc::clone(&writer),
+            closed: Arc::clone(&closed)
+        };
+        thread::spawn(move || connection.run());
+
+        Self {
+            events,
+            writer,
+            closed
+        }
+    }
+
+    /// Checks if the client is currently connected to the server.
+    pub fn is_connected(&self) -> bool { self.writer.lock().unwrap().is_some() }
+
+    /// Send the message to the server.
+    ///
+    /// # Returns
+    /// An error, if the client is not connected right now.
+    pub fn send(&self, message: &ClientMessage) -> io::Result<()> {
+        match &mut *self.writer.lock().unwrap() {
+            Some(stream) => write_message(stream, message),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "not connected to the server"
+            ))
+        }
+    }
+
+    /// Ask the server to apply the action for the player of this client.
+    pub fn send_action(&self, action: GameAction) -> io::Result<()> {
+        self.send(&ClientMessage::Action(action))
+    }
+
+    /// Everything that has happened since the last time the client has been
+    /// polled, without waiting for anything new.
+    pub fn poll(&self) -> Vec<ClientEvent> { self.events.try_iter().collect() }
+
+    /// Wait for the next event, but not longer than the timeout.
+    pub fn next_event(&self, timeout: Duration) -> Option<ClientEvent> {
+        match self.events.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None
+        }
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if let Some(stream) = &*self.writer.lock().unwrap() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// The background part of the client, which reads from the server and
+// reconnects when necessary.
+struct Connection {
+    address: String,
+    name:    Option<String>,
+    token:   Option<u64>,
+    events:  Sender<ClientEvent>,
+    writer:  Arc<Mutex<Option<TcpStream>>>,
+    closed:  Arc<AtomicBool>
+}
+
+impl Connection {
+    fn run(mut self) {
+        while !self.closed.load(Ordering::SeqCst) {
+            if let Ok(stream) = TcpStream::connect(&self.address) {
+                match self.receive(stream) {
+                    Ok(true) => {}
+                    _ => return
+                }
+            }
+
+            thread::sleep(RECONNECT_INTERVAL);
+        }
+    }
+
+    // Greet the server and pass on its messages until the connection is lost.
+    //
+    // # Returns
+    // If the client should try to reconnect.
+    fn receive(&mut self, stream: TcpStream) -> io::Result<bool> {
+        let mut writer = stream.try_clone()?;
+        let hello = match &self.name {
+            Some(name) => ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name:    name.clone(),
+                token:   self.token
+            },
+            None => ClientMessage::Watch {
+                version: PROTOCOL_VERSION
+            }
+        };
+        write_message(&mut writer, &hello)?;
+        *self.writer.lock().unwrap() = Some(writer);
+
+        let mut reader = BufReader::new(stream);
+        let mut reconnect = true;
+        while let Ok(Some(message)) = read_message(&mut reader) {
+            match &message {
+                ServerMessage::Welcome { token, .. } => self.token = Some(*token),
+                ServerMessage::Refused(_) => reconnect = false,
+                _ => {}
+            }
+
+            // Stop as soon as nobody is listening anymore.
+            if self.events.send(ClientEvent::Message(message)).is_err() {
+                return Ok(false);
+            }
+        }
+
+        *self.writer.lock().unwrap() = None;
+        if self.closed.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+        let _ = self.events.send(ClientEvent::Disconnected);
+
+        Ok(reconnect)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn reconnect_with_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let client = Client::connect(address, "Alice".to_string());
+
+        // The first connection is welcomed and then lost.
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        match read_message(&mut reader).unwrap() {
+            Some(ClientMessage::Hello { token: None, .. }) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        let mut writer = stream;
+        write_message(
+            &mut writer,
+            &ServerMessage::Welcome {
+                version: PROTOCOL_VERSION,
+                seat:    1,
+                token:   7
+            }
+        )
+        .unwrap();
+        match client.next_event(TIMEOUT) {
+            Some(ClientEvent::Message(ServerMessage::Welcome { seat: 1, .. })) => {}
+            other => panic!("Unexpected event: {:?}", other)
+        }
+        assert!(client.is_connected());
+        drop(reader);
+        drop(writer);
+
+        match client.next_event(TIMEOUT) {
+            Some(ClientEvent::Disconnected) => {}
+            other => panic!("Unexpected event: {:?}", other)
+        }
+        assert!(client.send_action(GameAction::EndActions).is_err());
+
+        // The client comes back on its own and asks for its seat.
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        match read_message(&mut reader).unwrap() {
+            Some(ClientMessage::Hello { token: Some(7), .. }) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+}
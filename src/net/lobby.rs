@@ -0,0 +1,348 @@
+//! The table the players gather at before a game on the server starts. Here
+//! they choose their adventurers and agree on the difficulty and the map. As
+//! soon as everyone at the table is ready, the game begins.
+
+use crate::adventurer::AdventurerType;
+use crate::difficulty::Difficulty;
+use crate::game_state::GameState;
+use crate::map::MapChoice;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The number of players that fit around the table.
+pub const MAX_PLAYERS: usize = 4;
+
+/// A player waiting for the game to start.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LobbySeat {
+    pub name: String,
+    /// The adventurer the player wants to play, or `None` to get one of the
+    /// remaining adventurers at random.
+    pub adventurer: Option<AdventurerType>,
+    pub ready: bool
+}
+
+/// Everything that has been agreed on for the next game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Lobby {
+    /// The seats of the table. Empty seats are `None`.
+    pub seats: Vec<Option<LobbySeat>>,
+    pub difficulty: Difficulty,
+    pub map: MapChoice
+}
+
+/// Reasons why a choice in the lobby can not be accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LobbyError {
+    /// Nobody sits in the seat.
+    EmptySeat,
+    /// Another player has already chosen the adventurer.
+    AdventurerTaken(AdventurerType),
+    /// The map does not contain the spawn tile of the adventurer.
+    NoSpawnPoint(AdventurerType),
+    /// There is no adventurer left to be drawn for a player.
+    NoAdventurerLeft,
+    /// There is nobody at the table to play the game.
+    NoPlayers
+}
+
+impl fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptySeat => write!(f, "nobody sits in this seat"),
+            Self::AdventurerTaken(adventurer) => {
+                write!(f, "the {:?} has already been chosen", adventurer)
+            }
+            Self::NoSpawnPoint(adventurer) => {
+                write!(f, "the map has no spawn tile for the {:?}", adventurer)
+            }
+            Self::NoAdventurerLeft => write!(f, "there is no adventurer left to be drawn"),
+            Self::NoPlayers => write!(f, "there is nobody to play the game")
+        }
+    }
+}
+
+impl Lobby {
+    /// Create an empty table, with the difficulty and map the game is played
+    /// with unless someone chooses otherwise.
+    pub fn new(difficulty: Difficulty, map: MapChoice) -> Self {
+        Self {
+            seats: vec![None; MAX_PLAYERS],
+            difficulty,
+            map
+        }
+    }
+
+    /// The number of players at the table.
+    pub fn players(&self) -> usize { self.seats.iter().flatten().count() }
+
+    /// Checks if there is anyone at the table and all of them are ready.
+    pub fn all_ready(&self) -> bool {
+        self.players() != 0 && self.seats.iter().flatten().all(|seat| seat.ready)
+    }
+
+    /// Let a player with the name sit down in the seat.
+    ///
+    /// # Panics
+    /// If the seat does not exist.
+    pub fn join(&mut self, seat: usize, name: String) {
+        self.seats[seat] = Some(LobbySeat {
+            name,
+            adventurer: None,
+            ready: false
+        });
+    }
+
+    /// The player of the seat leaves the table.
+    pub fn leave(&mut self, seat: usize) {
+        if let Some(seat) = self.seats.get_mut(seat) {
+            *seat = None;
+        }
+    }
+
+    /// Choose the adventurer for the player of the seat, or `None` to draw one
+    /// of the remaining ones when the game starts.
+    pub fn choose_adventurer(
+        &mut self,
+        seat: usize,
+        adventurer: Option<AdventurerType>
+    ) -> Result<(), LobbyError> {
+        if let Some(adventurer) = adventurer {
+            let taken = self.seats.iter().enumerate().any(|(other, taken)| {
+                other != seat
+                    && taken.as_ref().and_then(|taken| taken.adventurer) == Some(adventurer)
+            });
+            if taken {
+                return Err(LobbyError::AdventurerTaken(adventurer));
+            }
+        }
+
+        self.seat_mut(seat)?.adventurer = adventurer;
+        Ok(())
+    }
+
+    /// Change the difficulty. Everyone has to confirm again that they are
+    /// ready with the new one.
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        if self.difficulty != difficulty {
+            self.difficulty = difficulty;
+            self.unready();
+        }
+    }
+
+    /// Change the map. Everyone has to confirm again that they are ready with
+    /// the new one.
+    pub fn set_map(&mut self, map: MapChoice) {
+        if self.map != map {
+            self.map = map;
+            self.unready();
+        }
+    }
+
+    /// Set if the player of the seat is ready to start the game.
+    pub fn set_ready(&mut self, seat: usize, ready: bool) -> Result<(), LobbyError> {
+        self.seat_mut(seat)?.ready = ready;
+        Ok(())
+    }
+
+    /// Nobody at the table is ready anymore.
+    pub fn unready(&mut self) {
+        for seat in self.seats.iter_mut().flatten() {
+            seat.ready = false;
+        }
+    }
+
+    /// Set up the game that has been agreed on. Players who have not chosen an
+    /// adventurer draw one of the remaining ones.
+    ///
+    /// # Returns
+    /// The seats of the table in the order the players take their turns,
+    /// together with the game. The first seat belongs to the first player and
+    /// so on.
+    pub fn start<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R
+    ) -> Result<(Vec<usize>, GameState), LobbyError> {
+        let order: Vec<usize> = self
+            .seats
+            .iter()
+            .enumerate()
+            .filter_map(|(seat, taken)| taken.as_ref().map(|_| seat))
+            .collect();
+        if order.is_empty() {
+            return Err(LobbyError::NoPlayers);
+        }
+
+        let chosen: Vec<AdventurerType> = self
+            .seats
+            .iter()
+            .flatten()
+            .filter_map(|seat| seat.adventurer)
+            .collect();
+        let mut remaining: Vec<AdventurerType> = AdventurerType::ALL
+            .iter()
+            .copied()
+            .filter(|&adventurer| {
+                !chosen.contains(&adventurer) && self.map.has_spawn_point(adventurer)
+            })
+            .collect();
+        remaining.shuffle(rng);
+
+        let mut adventurers = Vec::with_capacity(order.len());
+        for seat in self.seats.iter().flatten() {
+            let adventurer = match seat.adventurer {
+                Some(adventurer) => adventurer,
+                None => remaining.pop().ok_or(LobbyError::NoAdventurerLeft)?
+            };
+            if !self.map.has_spawn_point(adventurer) {
+                return Err(LobbyError::NoSpawnPoint(adventurer));
+            }
+            adventurers.push(adventurer);
+        }
+
+        let map = self.map.create(rng);
+        let state = GameState::with_seed(map, &adventurers, self.difficulty, rng.gen());
+
+        Ok((order, state))
+    }
+
+    fn seat_mut(&mut self, seat: usize) -> Result<&mut LobbySeat, LobbyError> {
+        self.seats
+            .get_mut(seat)
+            .and_then(Option::as_mut)
+            .ok_or(LobbyError::EmptySeat)
+    }
+}
+
+impl Default for Lobby {
+    fn default() -> Self { Self::new(Difficulty::Normal, MapChoice::Random) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::{generate, IslandTile, IslandTileInfo};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn lobby(players: usize) -> Lobby {
+        let mut lobby = Lobby::default();
+        for seat in 0..players {
+            lobby.join(seat, format!("Player {}", seat));
+        }
+        lobby
+    }
+
+    #[test]
+    fn no_duplicate_adventurers() {
+        let mut lobby = lobby(2);
+
+        lobby
+            .choose_adventurer(0, Some(AdventurerType::Diver))
+            .unwrap();
+        assert_eq!(
+            Err(LobbyError::AdventurerTaken(AdventurerType::Diver)),
+            lobby.choose_adventurer(1, Some(AdventurerType::Diver))
+        );
+        // Choosing the same adventurer again is fine.
+        lobby
+            .choose_adventurer(0, Some(AdventurerType::Diver))
+            .unwrap();
+
+        lobby.choose_adventurer(0, None).unwrap();
+        lobby
+            .choose_adventurer(1, Some(AdventurerType::Diver))
+            .unwrap();
+        assert_eq!(
+            Err(LobbyError::EmptySeat),
+            lobby.choose_adventurer(2, Some(AdventurerType::Pilot))
+        );
+    }
+
+    #[test]
+    fn ready_until_settings_change() {
+        let mut lobby = lobby(2);
+        assert!(!lobby.all_ready());
+
+        lobby.set_ready(0, true).unwrap();
+        lobby.set_ready(1, true).unwrap();
+        assert!(lobby.all_ready());
+
+        lobby.set_difficulty(Difficulty::Normal);
+        assert!(lobby.all_ready());
+        lobby.set_difficulty(Difficulty::Elite);
+        assert!(!lobby.all_ready());
+
+        lobby.set_ready(0, true).unwrap();
+        lobby.set_ready(1, true).unwrap();
+        lobby.set_map(MapChoice::Custom(generate::filled_island(
+            &generate::classic_shape()
+        )));
+        assert!(!lobby.all_ready());
+
+        assert!(!Lobby::default().all_ready());
+    }
+
+    #[test]
+    fn start_with_random_draw() {
+        let mut lobby = lobby(3);
+        lobby.leave(1);
+        lobby
+            .choose_adventurer(2, Some(AdventurerType::Pilot))
+            .unwrap();
+        lobby.set_difficulty(Difficulty::Legendary);
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let (order, state) = lobby.start(&mut rng).unwrap();
+        assert_eq!(vec![0, 2], order);
+        assert_eq!(2, state.characters().len());
+        assert_eq!(
+            AdventurerType::Pilot,
+            state.characters()[1].adventurer_type()
+        );
+        assert_ne!(
+            AdventurerType::Pilot,
+            state.characters()[0].adventurer_type()
+        );
+
+        assert_eq!(
+            Err(LobbyError::NoPlayers),
+            Lobby::default().start(&mut rng).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn custom_map_needs_spawn_tiles() {
+        let mut map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(0));
+        let fools_landing = map
+            .iter()
+            .find(|(_, tile)| {
+                tile.map_or(false, |tile| *tile.info() == IslandTileInfo::FoolsLanding)
+            })
+            .unwrap()
+            .0;
+        map.set(fools_landing, None::<IslandTile>);
+
+        let mut lobby = lobby(2);
+        lobby.set_map(MapChoice::Custom(map));
+        lobby
+            .choose_adventurer(0, Some(AdventurerType::Pilot))
+            .unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Err(LobbyError::NoSpawnPoint(AdventurerType::Pilot)),
+            lobby.start(&mut rng).map(|_| ())
+        );
+
+        // Nobody draws the pilot at random.
+        lobby.choose_adventurer(0, None).unwrap();
+        for seed in 0..20 {
+            let (_, state) = lobby.start(&mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(state
+                .characters()
+                .iter()
+                .all(|character| character.adventurer_type() != AdventurerType::Pilot));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Playing over the network. One machine runs the [Server], which holds the
+//! game and checks every action with the rules engine, while the players
+//! connect to it with clients. Before the game starts, the players meet in
+//! the [Lobby] to set it up.
+
+pub mod client;
+pub use self::client::*;
+
+pub mod lobby;
+pub use self::lobby::*;
+
+pub mod protocol;
+pub use self::protocol::*;
+
+pub mod server;
+pub use self::server::*;
@@ -0,0 +1,258 @@
+//! The messages exchanged between the server and its clients.
+//!
+//! Every message is a single line of JSON, terminated by `\n`. The types below
+//! define the format: enums are externally tagged, as serde does by default, so
+//! a client written in any language can take part. For example a client joins
+//! with
+//!
+//! ```text
+//! {"Hello":{"version":4,"name":"Alice"}}
+//! ```
+//!
+//! and the server answers with
+//!
+//! ```text
+//! {"Welcome":{"version":4,"seat":0,"token":5017}}
+//! ```
+//!
+//! followed by the players already at the table. Before the game starts, the
+//! players meet in the [Lobby](super::Lobby), which is sent to everyone
+//! whenever it changes. There they choose their adventurers with
+//! `{"ChooseAdventurer":"Diver"}` or `{"ChooseAdventurer":null}` for a random
+//! one, agree on the difficulty with `{"ChooseDifficulty":"Elite"}` and on the
+//! map with `{"ChooseMap":"Classic"}`, `{"ChooseMap":"Random"}` or a map of
+//! their own. When everyone has sent `{"Ready":true}`, every client is told its
+//! seat in the order of the turns with
+//! [GameStarted](ServerMessage::GameStarted).
+//!
+//! From then on the clients receive [Snapshot](ServerMessage::Snapshot)s of the
+//! game. Actions are sent as `{"Action":"EndActions"}` or
+//! `{"Action":{"Move":{"x":2,"y":3}}}`. After every accepted action, all
+//! clients receive what happened and a new snapshot.
+//!
+//! Clients that only want to watch join with
+//!
+//! ```text
+//! {"Watch":{"version":4}}
+//! ```
+//!
+//! instead. Spectators do not take a seat. They receive the lobby and
+//! everything that happens in the game, but the server may hold the game back
+//! from them for a number of turns. Since the cards of all players are public
+//! in this cooperative game, spectators see every hand in the snapshots, just
+//! like the players do.
+//!
+//! If the connection is lost during the game, the seat is kept. The client can
+//! take it again by sending the token of the welcome along with its next
+//! hello. In the lobby, the seat is given up right away.
+//!
+//! The version is raised whenever the format of a message changes. Clients of a
+//! different version are refused.
+
+use super::Lobby;
+use crate::adventurer::AdventurerType;
+use crate::difficulty::Difficulty;
+use crate::game_state::{GameAction, GameEvent, GameSnapshot};
+use crate::map::MapChoice;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io::{self, BufRead, Read, Write};
+
+/// The version of the protocol described by this module.
+pub const PROTOCOL_VERSION: u32 = 4;
+
+/// The port the server listens on unless told otherwise.
+pub const DEFAULT_PORT: u16 = 4242;
+
+/// The longest line a message may take up, in bytes. It leaves plenty of room
+/// for snapshots of big maps, but keeps a peer from filling the memory of the
+/// other side with a line that never ends.
+pub const MAX_MESSAGE_LEN: u64 = 1 << 20;
+
+/// Messages sent from a client to the server.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Must be the first message of every client. The name is shown to the
+    /// other players. Clients that have lost their connection send the token
+    /// they have been welcomed with, to get their seat back.
+    Hello {
+        version: u32,
+        name:    String,
+        #[serde(default)]
+        token:   Option<u64>
+    },
+    /// May be the first message instead of the hello, to watch the game as a
+    /// spectator.
+    Watch { version: u32 },
+    /// Choose the adventurer to play in the lobby, or `None` to draw one of
+    /// the adventurers nobody has chosen.
+    ChooseAdventurer(Option<AdventurerType>),
+    /// Change the difficulty of the game in the lobby.
+    ChooseDifficulty(Difficulty),
+    /// Change the map of the game in the lobby.
+    ChooseMap(MapChoice),
+    /// Tell the others in the lobby if the client is ready to start.
+    Ready(bool),
+    /// Take an action in the game. It is only accepted if the client is the
+    /// one who takes the action according to [GameAction::player].
+    Action(GameAction)
+}
+
+/// Reasons why the server does not let a client join. The connection is closed
+/// right after the refusal has been sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Refusal {
+    /// The client speaks a different version of the protocol.
+    VersionMismatch { server: u32 },
+    /// All seats of the game are taken.
+    GameFull,
+    /// The client did not start with [ClientMessage::Hello] or
+    /// [ClientMessage::Watch].
+    NoHello,
+    /// None of the seats belongs to the token of the hello.
+    UnknownToken
+}
+
+/// Messages sent from the server to a client.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// The client has taken the seat. The token is needed to take the seat
+    /// again after a lost connection.
+    Welcome {
+        version: u32,
+        seat:    usize,
+        token:   u64
+    },
+    /// The client watches the game as a spectator. Everything that happens in
+    /// the game reaches it once the number of turns of the delay has passed.
+    Spectating { version: u32, delay: u32 },
+    /// The client may not join the game.
+    Refused(Refusal),
+    /// Everything that has been agreed on in the lobby so far.
+    Lobby(Lobby),
+    /// Everyone in the lobby is ready and the game has begun. The client
+    /// plays the player with the index of the seat, which may differ from the
+    /// seat it had in the lobby, since empty seats are left out.
+    GameStarted { seat: usize },
+    /// Everything the players can observe of the game right now.
+    Snapshot(GameSnapshot),
+    /// An action has been applied to the game, which caused the events.
+    Applied {
+        player: usize,
+        action: GameAction,
+        events: Vec<GameEvent>
+    },
+    /// The last message of the client could not be accepted.
+    Rejected { reason: String },
+    /// Someone has taken the seat. Right after the welcome, a new client is
+    /// told about everyone who is already at the table this way.
+    PlayerJoined { seat: usize, name: String },
+    /// The connection to the player of the seat has been lost. During the game,
+    /// the seat is kept until they come back with its token.
+    PlayerLeft { seat: usize }
+}
+
+/// Write the message as a single line.
+pub fn write_message<W: Write, M: Serialize>(writer: &mut W, message: &M) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+/// Read the next message.
+///
+/// # Returns
+/// The message, `None` if the connection has been closed, or an error if the
+/// line could not be read, is longer than [MAX_MESSAGE_LEN] or is not a valid
+/// message.
+pub fn read_message<R: BufRead, M: DeserializeOwned>(reader: &mut R) -> io::Result<Option<M>> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(MAX_MESSAGE_LEN).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if read as u64 == MAX_MESSAGE_LEN && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("The message is longer than {} bytes", MAX_MESSAGE_LEN)
+        ));
+    }
+
+    Ok(Some(serde_json::from_str(&line)?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::FieldPos;
+
+    #[test]
+    fn documented_format() {
+        let hello: ClientMessage =
+            serde_json::from_str(r#"{"Hello":{"version":4,"name":"Alice"}}"#).unwrap();
+        assert_eq!(
+            ClientMessage::Hello {
+                version: 4,
+                name:    "Alice".to_string(),
+                token:   None
+            },
+            hello
+        );
+
+        let watch: ClientMessage = serde_json::from_str(r#"{"Watch":{"version":4}}"#).unwrap();
+        assert_eq!(ClientMessage::Watch { version: 4 }, watch);
+
+        let action: ClientMessage =
+            serde_json::from_str(r#"{"Action":{"Move":{"x":2,"y":3}}}"#).unwrap();
+        assert_eq!(
+            ClientMessage::Action(GameAction::Move(FieldPos::from_values(2, 3))),
+            action
+        );
+
+        let lobby: Vec<ClientMessage> = serde_json::from_str(
+            r#"[{"ChooseAdventurer":"Diver"},{"ChooseAdventurer":null},
+                {"ChooseDifficulty":"Elite"},{"ChooseMap":"Random"},{"Ready":true}]"#
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                ClientMessage::ChooseAdventurer(Some(AdventurerType::Diver)),
+                ClientMessage::ChooseAdventurer(None),
+                ClientMessage::ChooseDifficulty(Difficulty::Elite),
+                ClientMessage::ChooseMap(MapChoice::Random),
+                ClientMessage::Ready(true)
+            ],
+            lobby
+        );
+    }
+
+    #[test]
+    fn messages_are_lines() {
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &ClientMessage::Action(GameAction::EndActions)).unwrap();
+        write_message(
+            &mut buffer,
+            &ClientMessage::Action(GameAction::DrawFloodCard)
+        )
+        .unwrap();
+        assert_eq!(2, buffer.iter().filter(|&&b| b == b'\n').count());
+
+        let mut reader = &buffer[..];
+        assert_eq!(
+            Some(ClientMessage::Action(GameAction::EndActions)),
+            read_message(&mut reader).unwrap()
+        );
+        assert_eq!(
+            Some(ClientMessage::Action(GameAction::DrawFloodCard)),
+            read_message(&mut reader).unwrap()
+        );
+        assert_eq!(None, read_message::<_, ClientMessage>(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn endless_line() {
+        let buffer = vec![b' '; MAX_MESSAGE_LEN as usize + 1];
+        let err = read_message::<_, ClientMessage>(&mut &buffer[..]).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,937 @@
+//! Server hosting a single game for clients on other machines. Every client
+//! gets its own thread, which reads its messages and applies its actions to the
+//! game, so the game is shared between them behind a mutex. Messages to a
+//! client are written by another thread of its own, so a client that does not
+//! read them can not hold up the others. The game is either
+//! set up beforehand or by the players themselves in a [Lobby]. Spectators can
+//! watch the game, held back by a number of turns if the host wishes so.
+
+use super::lobby::{Lobby, LobbyError};
+use super::protocol::*;
+use crate::game_state::{GameAction, GameState};
+use std::collections::VecDeque;
+use std::io::{self, BufReader};
+use std::mem;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// How long writing a message to a client may take before the client is
+/// dropped.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Hosts a game and lets clients play the adventurers in it.
+pub struct Server {
+    listener: TcpListener,
+    shared:   Arc<Mutex<Shared>>
+}
+
+// Everything the client threads have to agree on.
+struct Shared {
+    phase: Phase,
+    seats: Vec<Option<Seat>>,
+    spectators: Vec<Spectator>,
+    // The number of turns the game is held back from the spectators.
+    delay: u32,
+    // Messages about the game the spectators have not received yet, together
+    // with the turn they belong to.
+    delayed: VecDeque<(u32, ServerMessage)>,
+    // The last snapshot the spectators have received, for those who come
+    // later.
+    shown: Option<ServerMessage>,
+    connections: u64
+}
+
+// What the players at the table are doing.
+enum Phase {
+    Lobby(Lobby),
+    Playing(GameState)
+}
+
+// A player sitting at the table. The outbox is `None` while the player is
+// disconnected.
+struct Seat {
+    name: String,
+    token: u64,
+    connection: u64,
+    outbox: Option<Sender<ServerMessage>>
+}
+
+// Someone watching the game.
+struct Spectator {
+    connection: u64,
+    outbox:     Sender<ServerMessage>
+}
+
+impl Server {
+    /// Host the game on the address provided. Every adventurer of the game is
+    /// a seat a client can take.
+    pub fn bind<A: ToSocketAddrs>(addr: A, state: GameState) -> io::Result<Self> {
+        let seats = state.characters().iter().map(|_| None).collect();
+        let snapshot = ServerMessage::Snapshot(state.snapshot());
+
+        let server = Self::host(addr, Phase::Playing(state), seats)?;
+        server.shared.lock().unwrap().shown = Some(snapshot);
+        Ok(server)
+    }
+
+    /// Host a lobby on the address provided, in which the clients set up the
+    /// game themselves. Every seat of the lobby can be taken by a client and
+    /// the game starts as soon as everyone at the table is ready.
+    pub fn bind_lobby<A: ToSocketAddrs>(addr: A, lobby: Lobby) -> io::Result<Self> {
+        let seats = lobby.seats.iter().map(|_| None).collect();
+        Self::host(addr, Phase::Lobby(lobby), seats)
+    }
+
+    fn host<A: ToSocketAddrs>(addr: A, phase: Phase, seats: Vec<Option<Seat>>) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            shared:   Arc::new(Mutex::new(Shared {
+                phase,
+                seats,
+                spectators: Vec::new(),
+                delay: 0,
+                delayed: VecDeque::new(),
+                shown: None,
+                connections: 0
+            }))
+        })
+    }
+
+    /// Hold the game back from the spectators by the number of turns, so they
+    /// can not give the players hints about what is going on right now.
+    pub fn with_spectator_delay(self, turns: u32) -> Self {
+        self.shared.lock().unwrap().delay = turns;
+        self
+    }
+
+    /// The address the server is listening on. Useful if it has been bound to
+    /// port 0 to let the system choose a free one.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> { self.listener.local_addr() }
+
+    /// Accept clients until the listener fails. Every client is handled on its
+    /// own thread.
+    pub fn run(self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let shared = Arc::clone(&self.shared);
+            thread::spawn(move || {
+                if let Err(err) = handle_client(&shared, stream) {
+                    eprintln!("Client disconnected: {}", err);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Shared {
+    // Send the message to every connected client. Clients that can not be
+    // reached are removed by their own threads, once their connection fails.
+    fn broadcast(&mut self, message: &ServerMessage) {
+        for seat in self.seats.iter_mut().flatten() {
+            if let Some(outbox) = &seat.outbox {
+                let _ = outbox.send(message.clone());
+            }
+        }
+    }
+
+    // Send the message to the players and the spectators alike.
+    fn announce(&mut self, message: &ServerMessage) {
+        self.broadcast(message);
+        for spectator in &self.spectators {
+            let _ = spectator.outbox.send(message.clone());
+        }
+    }
+
+    // Pass the message about the game on to the spectators, once the delay has
+    // passed.
+    fn spectate(&mut self, message: ServerMessage) {
+        if let Phase::Playing(state) = &self.phase {
+            self.delayed.push_back((state.turn(), message));
+            self.release();
+        }
+    }
+
+    // Send the spectators everything they are allowed to see by now. Once the
+    // game is over, there is nothing left to hold back.
+    fn release(&mut self) {
+        let (turn, over) = match &self.phase {
+            Phase::Playing(state) => (state.turn(), state.is_over()),
+            Phase::Lobby(_) => return
+        };
+
+        while let Some(&(at, _)) = self.delayed.front() {
+            if !over && at + self.delay > turn {
+                break;
+            }
+
+            let (_, message) = self.delayed.pop_front().unwrap();
+            self.show(message);
+        }
+    }
+
+    // Send the message to the spectators right away.
+    fn show(&mut self, message: ServerMessage) {
+        for spectator in &self.spectators {
+            let _ = spectator.outbox.send(message.clone());
+        }
+        if let ServerMessage::Snapshot(_) = message {
+            self.shown = Some(message);
+        }
+    }
+
+    fn send(&mut self, seat: usize, message: &ServerMessage) {
+        if let Some(Some(Seat {
+            outbox: Some(outbox),
+            ..
+        })) = self.seats.get(seat)
+        {
+            let _ = outbox.send(message.clone());
+        }
+    }
+
+    // Find the seat for the client. A client with a token gets the seat of the
+    // token back, even if the server has not noticed yet that the old
+    // connection has been lost. Everyone else gets the first free seat. Since
+    // seats are given up in the lobby, an unknown token is only refused once
+    // the game has started.
+    fn choose_seat(&self, token: Option<u64>) -> Result<usize, Refusal> {
+        let own = token.and_then(|token| {
+            self.seats
+                .iter()
+                .position(|seat| seat.as_ref().map_or(false, |seat| seat.token == token))
+        });
+
+        match (own, &self.phase) {
+            (Some(seat), _) => Ok(seat),
+            (None, Phase::Playing(_)) if token.is_some() => Err(Refusal::UnknownToken),
+            (None, _) => self
+                .seats
+                .iter()
+                .position(Option::is_none)
+                .ok_or(Refusal::GameFull)
+        }
+    }
+
+    // The seat that is currently taken by the connection.
+    fn seat_of(&self, connection: u64) -> Option<usize> {
+        self.seats.iter().position(|seat| {
+            seat.as_ref()
+                .map_or(false, |seat| seat.connection == connection)
+        })
+    }
+
+    // Apply the action for the player of the seat.
+    fn apply(&mut self, seat: usize, action: GameAction) -> Result<(), String> {
+        let state = match &mut self.phase {
+            Phase::Playing(state) => state,
+            Phase::Lobby(_) => return Err("the game has not started yet".to_string())
+        };
+
+        let player = action.player(state.active_player());
+        if player != seat {
+            return Err("the action has to be taken by another player".to_string());
+        }
+
+        let events = state.apply(&action).map_err(|err| err.to_string())?;
+        let applied = ServerMessage::Applied {
+            player,
+            action,
+            events
+        };
+        let snapshot = ServerMessage::Snapshot(state.snapshot());
+        self.broadcast(&applied);
+        self.broadcast(&snapshot);
+        self.spectate(applied);
+        self.spectate(snapshot);
+
+        Ok(())
+    }
+
+    // Make a change to the lobby, as long as the game has not started.
+    fn arrange<F>(&mut self, change: F) -> Result<(), String>
+    where
+        F: FnOnce(&mut Lobby) -> Result<(), LobbyError>
+    {
+        match &mut self.phase {
+            Phase::Lobby(lobby) => change(lobby).map_err(|err| err.to_string())?,
+            Phase::Playing(_) => return Err("the game has already started".to_string())
+        }
+
+        self.lobby_changed();
+        Ok(())
+    }
+
+    // Tell everyone about the lobby and start the game once everyone at the
+    // table is ready. If the game can not be started with the settings, nobody
+    // is ready anymore and everyone is told why.
+    fn lobby_changed(&mut self) {
+        let lobby = match &mut self.phase {
+            Phase::Lobby(lobby) => lobby,
+            Phase::Playing(_) => return
+        };
+
+        let mut failed = None;
+        let mut started = None;
+        if lobby.all_ready() {
+            match lobby.start(&mut rand::thread_rng()) {
+                Ok(game) => started = Some(game),
+                Err(err) => {
+                    lobby.unready();
+                    failed = Some(err.to_string());
+                }
+            }
+        }
+
+        let message = ServerMessage::Lobby(lobby.clone());
+        if let Some(reason) = failed {
+            self.broadcast(&ServerMessage::Rejected { reason });
+        }
+        self.announce(&message);
+        if let Some((order, state)) = started {
+            self.start(&order, state);
+        }
+    }
+
+    // Leave the lobby and start the game. The players are seated in the order
+    // they take their turns.
+    fn start(&mut self, order: &[usize], state: GameState) {
+        let mut seats = mem::take(&mut self.seats);
+        self.seats = order.iter().map(|&seat| seats[seat].take()).collect();
+        let snapshot = ServerMessage::Snapshot(state.snapshot());
+        self.phase = Phase::Playing(state);
+
+        for seat in 0..self.seats.len() {
+            self.send(seat, &ServerMessage::GameStarted { seat });
+        }
+        // The game as it has been dealt gives nothing away, so the spectators
+        // see it right away.
+        self.broadcast(&snapshot);
+        self.show(snapshot);
+    }
+}
+
+// Write the messages sent through the outbox returned to the stream, on a
+// thread of its own. If a message can not be written in time, the client is
+// given up on and the stream is shut down, so its reading thread ends as well.
+// The thread ends once the outbox has been dropped by everyone.
+fn spawn_writer(mut stream: TcpStream) -> io::Result<Sender<ServerMessage>> {
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let (outbox, messages) = mpsc::channel();
+    thread::spawn(move || {
+        for message in messages {
+            if write_message(&mut stream, &message).is_err() {
+                let _ = stream.shutdown(Shutdown::Both);
+                break;
+            }
+        }
+    });
+
+    Ok(outbox)
+}
+
+fn handle_client(shared: &Mutex<Shared>, stream: TcpStream) -> io::Result<()> {
+    let outbox = spawn_writer(stream.try_clone()?)?;
+    let mut reader = BufReader::new(stream);
+    let refuse = |refusal| {
+        let _ = outbox.send(ServerMessage::Refused(refusal));
+        Ok(())
+    };
+
+    let (version, name, token) = match read_message(&mut reader) {
+        Ok(Some(ClientMessage::Hello {
+            version,
+            name,
+            token
+        })) => (version, Some(name), token),
+        Ok(Some(ClientMessage::Watch { version })) => (version, None, None),
+        _ => return refuse(Refusal::NoHello)
+    };
+    if version != PROTOCOL_VERSION {
+        return refuse(Refusal::VersionMismatch {
+            server: PROTOCOL_VERSION
+        });
+    }
+    let name = match name {
+        Some(name) => name,
+        None => return watch(shared, outbox, &mut reader)
+    };
+
+    let connection = {
+        let mut shared = shared.lock().unwrap();
+        let seat = match shared.choose_seat(token) {
+            Ok(seat) => seat,
+            Err(refusal) => return refuse(refusal)
+        };
+
+        let token = token.unwrap_or_else(rand::random);
+        let welcome = ServerMessage::Welcome {
+            version: PROTOCOL_VERSION,
+            seat,
+            token
+        };
+        let _ = outbox.send(welcome);
+        for (other, taken) in shared.seats.iter().enumerate() {
+            if let Some(taken) = taken.as_ref().filter(|taken| taken.outbox.is_some()) {
+                if other != seat {
+                    let _ = outbox.send(ServerMessage::PlayerJoined {
+                        seat: other,
+                        name: taken.name.clone()
+                    });
+                }
+            }
+        }
+
+        shared.announce(&ServerMessage::PlayerJoined {
+            seat,
+            name: name.clone()
+        });
+        shared.connections += 1;
+        let connection = shared.connections;
+        shared.seats[seat] = Some(Seat {
+            name: name.clone(),
+            token,
+            connection,
+            outbox: Some(outbox.clone())
+        });
+
+        match &mut shared.phase {
+            Phase::Lobby(lobby) => {
+                // Someone who comes back before the server has noticed that they
+                // were gone keeps their choices.
+                if lobby.seats[seat].is_none() {
+                    lobby.join(seat, name);
+                }
+                shared.lobby_changed();
+            }
+            Phase::Playing(state) => {
+                let snapshot = ServerMessage::Snapshot(state.snapshot());
+                shared.send(seat, &snapshot);
+            }
+        }
+
+        connection
+    };
+
+    let result = play(shared, connection, &mut reader);
+
+    // Keep the seat for the player during the game, unless they have already
+    // come back on another connection. In the lobby, the seat is given up.
+    let mut shared = shared.lock().unwrap();
+    if let Some(seat) = shared.seat_of(connection) {
+        match &mut shared.phase {
+            Phase::Lobby(lobby) => {
+                lobby.leave(seat);
+                shared.seats[seat] = None;
+            }
+            Phase::Playing(_) => {
+                if let Some(taken) = &mut shared.seats[seat] {
+                    taken.outbox = None;
+                }
+            }
+        }
+        shared.announce(&ServerMessage::PlayerLeft { seat });
+        shared.lobby_changed();
+    }
+
+    result
+}
+
+// Let the client watch the game until it leaves. Spectators are told about the
+// players at the table and get the lobby or the last snapshot they may see.
+fn watch(
+    shared: &Mutex<Shared>,
+    outbox: Sender<ServerMessage>,
+    reader: &mut BufReader<TcpStream>
+) -> io::Result<()> {
+    let connection = {
+        let mut shared = shared.lock().unwrap();
+        let _ = outbox.send(ServerMessage::Spectating {
+            version: PROTOCOL_VERSION,
+            delay:   shared.delay
+        });
+        for (seat, taken) in shared.seats.iter().enumerate() {
+            if let Some(taken) = taken.as_ref().filter(|taken| taken.outbox.is_some()) {
+                let _ = outbox.send(ServerMessage::PlayerJoined {
+                    seat,
+                    name: taken.name.clone()
+                });
+            }
+        }
+        match (&shared.phase, &shared.shown) {
+            (Phase::Lobby(lobby), _) => {
+                let _ = outbox.send(ServerMessage::Lobby(lobby.clone()));
+            }
+            (Phase::Playing(_), Some(shown)) => {
+                let _ = outbox.send(shown.clone());
+            }
+            (Phase::Playing(_), None) => {}
+        }
+
+        shared.connections += 1;
+        let connection = shared.connections;
+        shared.spectators.push(Spectator {
+            connection,
+            outbox: outbox.clone()
+        });
+        connection
+    };
+
+    let result = refuse_to_play(&outbox, reader);
+
+    shared
+        .lock()
+        .unwrap()
+        .spectators
+        .retain(|spectator| spectator.connection != connection);
+    result
+}
+
+// Answer everything the spectator sends with a rejection, until it leaves.
+fn refuse_to_play(
+    outbox: &Sender<ServerMessage>,
+    reader: &mut BufReader<TcpStream>
+) -> io::Result<()> {
+    while read_message::<_, ClientMessage>(reader)?.is_some() {
+        let reason = "spectators can not take part in the game".to_string();
+        let _ = outbox.send(ServerMessage::Rejected { reason });
+    }
+
+    Ok(())
+}
+
+// Handle the messages of the client until it leaves. The seat of the client is
+// looked up for every message, since it changes when the game starts.
+fn play(
+    shared: &Mutex<Shared>,
+    connection: u64,
+    reader: &mut BufReader<TcpStream>
+) -> io::Result<()> {
+    while let Some(message) = read_message(reader)? {
+        let mut shared = shared.lock().unwrap();
+        let seat = match shared.seat_of(connection) {
+            Some(seat) => seat,
+            // The player has come back on another connection.
+            None => break
+        };
+
+        let result = match message {
+            ClientMessage::Hello { .. } | ClientMessage::Watch { .. } => {
+                Err("Already joined the game".to_string())
+            }
+            ClientMessage::ChooseAdventurer(adventurer) => {
+                shared.arrange(|lobby| lobby.choose_adventurer(seat, adventurer))
+            }
+            ClientMessage::ChooseDifficulty(difficulty) => shared.arrange(|lobby| {
+                lobby.set_difficulty(difficulty);
+                Ok(())
+            }),
+            ClientMessage::ChooseMap(map) => shared.arrange(|lobby| {
+                lobby.set_map(map);
+                Ok(())
+            }),
+            ClientMessage::Ready(ready) => shared.arrange(|lobby| lobby.set_ready(seat, ready)),
+            ClientMessage::Action(action) => shared.apply(seat, action)
+        };
+
+        if let Err(reason) = result {
+            shared.send(seat, &ServerMessage::Rejected { reason });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::difficulty::Difficulty;
+    use crate::map::{generate, MapChoice};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream
+    }
+
+    impl Client {
+        fn connect(addr: SocketAddr) -> Self {
+            let stream = TcpStream::connect(addr).unwrap();
+            Self {
+                writer: stream.try_clone().unwrap(),
+                reader: BufReader::new(stream)
+            }
+        }
+
+        fn join(addr: SocketAddr, name: &str) -> Self {
+            let mut client = Self::connect(addr);
+            client.send(ClientMessage::Hello {
+                version: PROTOCOL_VERSION,
+                name:    name.to_string(),
+                token:   None
+            });
+            client
+        }
+
+        fn send(&mut self, message: ClientMessage) {
+            write_message(&mut self.writer, &message).unwrap();
+        }
+
+        fn receive(&mut self) -> ServerMessage { read_message(&mut self.reader).unwrap().unwrap() }
+    }
+
+    fn start_server() -> SocketAddr {
+        let map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(0));
+        let state = GameState::with_seed(
+            map,
+            &[AdventurerType::Pilot, AdventurerType::Diver],
+            Difficulty::Normal,
+            0
+        );
+
+        let server = Server::bind("127.0.0.1:0", state).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    #[test]
+    fn play_over_the_network() {
+        let addr = start_server();
+
+        let mut alice = Client::join(addr, "Alice");
+        match alice.receive() {
+            ServerMessage::Welcome { seat: 0, .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        match alice.receive() {
+            ServerMessage::Snapshot(snapshot) => {
+                assert_eq!(0, snapshot.active);
+                assert_eq!(AdventurerType::Pilot, snapshot.characters[0].adventurer);
+            }
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        let mut bob = Client::join(addr, "Bob");
+        match bob.receive() {
+            ServerMessage::Welcome { seat: 1, .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        match bob.receive() {
+            ServerMessage::PlayerJoined { seat: 0, name } => assert_eq!("Alice", name),
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        match alice.receive() {
+            ServerMessage::PlayerJoined { seat: 1, name } => assert_eq!("Bob", name),
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        bob.receive();
+
+        // It is not Bob's turn.
+        bob.send(ClientMessage::Action(GameAction::EndActions));
+        match bob.receive() {
+            ServerMessage::Rejected { .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        alice.send(ClientMessage::Action(GameAction::EndActions));
+        for client in &mut [&mut alice, &mut bob] {
+            match client.receive() {
+                ServerMessage::Applied {
+                    player: 0,
+                    action: GameAction::EndActions,
+                    ..
+                } => {}
+                other => panic!("Unexpected message: {:?}", other)
+            }
+            match client.receive() {
+                ServerMessage::Snapshot(snapshot) => {
+                    assert_eq!(
+                        vec![GameAction::DrawTreasureCard],
+                        snapshot.legal_actions_of(0)
+                    )
+                }
+                other => panic!("Unexpected message: {:?}", other)
+            }
+        }
+
+        drop(bob);
+        match alice.receive() {
+            ServerMessage::PlayerLeft { seat: 1 } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn refuse_clients() {
+        let addr = start_server();
+
+        let mut old = Client::connect(addr);
+        old.send(ClientMessage::Hello {
+            version: PROTOCOL_VERSION + 1,
+            name:    "Future".to_string(),
+            token:   None
+        });
+        match old.receive() {
+            ServerMessage::Refused(Refusal::VersionMismatch { server }) => {
+                assert_eq!(PROTOCOL_VERSION, server)
+            }
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        let mut rude = Client::connect(addr);
+        rude.send(ClientMessage::Action(GameAction::EndActions));
+        match rude.receive() {
+            ServerMessage::Refused(Refusal::NoHello) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        // Wait for every welcome, so the seats are taken in order.
+        let mut alice = Client::join(addr, "Alice");
+        alice.receive();
+        let mut bob = Client::join(addr, "Bob");
+        bob.receive();
+        let mut carol = Client::join(addr, "Carol");
+        match carol.receive() {
+            ServerMessage::Refused(Refusal::GameFull) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn reconnect_to_the_same_seat() {
+        let addr = start_server();
+
+        let mut alice = Client::join(addr, "Alice");
+        let token = match alice.receive() {
+            ServerMessage::Welcome { seat: 0, token, .. } => token,
+            other => panic!("Unexpected message: {:?}", other)
+        };
+        alice.receive();
+        let mut bob = Client::join(addr, "Bob");
+        bob.receive();
+        bob.receive();
+        bob.receive();
+
+        drop(alice);
+        match bob.receive() {
+            ServerMessage::PlayerLeft { seat: 0 } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        // The seat is kept for Alice, even though it is empty.
+        let mut carol = Client::join(addr, "Carol");
+        match carol.receive() {
+            ServerMessage::Refused(Refusal::GameFull) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        let mut wrong = Client::connect(addr);
+        wrong.send(ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name:    "Mallory".to_string(),
+            token:   Some(token.wrapping_add(1))
+        });
+        match wrong.receive() {
+            ServerMessage::Refused(Refusal::UnknownToken) => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        let mut alice = Client::connect(addr);
+        alice.send(ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+            name:    "Alice".to_string(),
+            token:   Some(token)
+        });
+        match alice.receive() {
+            ServerMessage::Welcome { seat: 0, .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        match bob.receive() {
+            ServerMessage::PlayerJoined { seat: 0, name } => assert_eq!("Alice", name),
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+
+    fn start_lobby() -> SocketAddr {
+        let server = Server::bind_lobby("127.0.0.1:0", Lobby::default()).unwrap();
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        addr
+    }
+
+    // Skip everything up to the next state of the lobby.
+    fn next_lobby(client: &mut Client) -> Lobby {
+        loop {
+            if let ServerMessage::Lobby(lobby) = client.receive() {
+                return lobby;
+            }
+        }
+    }
+
+    #[test]
+    fn set_up_the_game_in_the_lobby() {
+        let addr = start_lobby();
+
+        let mut alice = Client::join(addr, "Alice");
+        next_lobby(&mut alice);
+        let mut bob = Client::join(addr, "Bob");
+        let lobby = next_lobby(&mut bob);
+        assert_eq!(2, lobby.players());
+        assert_eq!("Bob", lobby.seats[1].as_ref().unwrap().name);
+        next_lobby(&mut alice);
+
+        // Actions have to wait for the game.
+        alice.send(ClientMessage::Action(GameAction::EndActions));
+        match alice.receive() {
+            ServerMessage::Rejected { .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        alice.send(ClientMessage::ChooseAdventurer(Some(AdventurerType::Diver)));
+        next_lobby(&mut alice);
+        next_lobby(&mut bob);
+        bob.send(ClientMessage::ChooseAdventurer(Some(AdventurerType::Diver)));
+        match bob.receive() {
+            ServerMessage::Rejected { .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        alice.send(ClientMessage::Ready(true));
+        next_lobby(&mut alice);
+        next_lobby(&mut bob);
+        bob.send(ClientMessage::ChooseDifficulty(Difficulty::Elite));
+        let lobby = next_lobby(&mut alice);
+        assert_eq!(Difficulty::Elite, lobby.difficulty);
+        assert!(!lobby.seats[0].as_ref().unwrap().ready);
+        next_lobby(&mut bob);
+
+        // Carol leaves before the game starts, which frees her seat.
+        let mut carol = Client::join(addr, "Carol");
+        next_lobby(&mut carol);
+        next_lobby(&mut alice);
+        next_lobby(&mut bob);
+        drop(carol);
+        assert_eq!(2, next_lobby(&mut alice).players());
+        next_lobby(&mut bob);
+
+        bob.send(ClientMessage::ChooseMap(MapChoice::Random));
+        next_lobby(&mut alice);
+        next_lobby(&mut bob);
+        alice.send(ClientMessage::Ready(true));
+        next_lobby(&mut alice);
+        next_lobby(&mut bob);
+        bob.send(ClientMessage::Ready(true));
+
+        for (seat, client) in [&mut alice, &mut bob].iter_mut().enumerate() {
+            assert!(next_lobby(client).all_ready());
+            match client.receive() {
+                ServerMessage::GameStarted { seat: started } => assert_eq!(seat, started),
+                other => panic!("Unexpected message: {:?}", other)
+            }
+            match client.receive() {
+                ServerMessage::Snapshot(snapshot) => {
+                    assert_eq!(2, snapshot.characters.len());
+                    assert_eq!(AdventurerType::Diver, snapshot.characters[0].adventurer);
+                }
+                other => panic!("Unexpected message: {:?}", other)
+            }
+        }
+
+        // The lobby is closed now.
+        bob.send(ClientMessage::Ready(false));
+        match bob.receive() {
+            ServerMessage::Rejected { .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+
+    #[test]
+    fn spectate_with_delay() {
+        let map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(0));
+        let state = GameState::with_seed(map, &[AdventurerType::Pilot], Difficulty::Normal, 0);
+        let server = Server::bind("127.0.0.1:0", state)
+            .unwrap()
+            .with_spectator_delay(1);
+        let addr = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+
+        let mut alice = Client::join(addr, "Alice");
+        alice.receive();
+        let mut snapshot = match alice.receive() {
+            ServerMessage::Snapshot(snapshot) => snapshot,
+            other => panic!("Unexpected message: {:?}", other)
+        };
+
+        let mut spectator = Client::connect(addr);
+        spectator.send(ClientMessage::Watch {
+            version: PROTOCOL_VERSION
+        });
+        match spectator.receive() {
+            ServerMessage::Spectating { delay: 1, .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        match spectator.receive() {
+            ServerMessage::PlayerJoined { seat: 0, name } => assert_eq!("Alice", name),
+            other => panic!("Unexpected message: {:?}", other)
+        }
+        // The cards of everyone are public.
+        match spectator.receive() {
+            ServerMessage::Snapshot(shown) => {
+                assert_eq!(0, shown.turn);
+                assert_eq!(snapshot.characters[0].hand, shown.characters[0].hand);
+                assert!(!shown.characters[0].hand.is_empty());
+            }
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        spectator.send(ClientMessage::Action(GameAction::EndActions));
+        match spectator.receive() {
+            ServerMessage::Rejected { .. } => {}
+            other => panic!("Unexpected message: {:?}", other)
+        }
+
+        // Alice plays her first turn, which the spectator only sees once her
+        // second turn has begun.
+        let mut played = Vec::new();
+        while snapshot.turn == 0 {
+            let legal = snapshot.legal_actions_of(0);
+            let action = [
+                GameAction::EndActions,
+                GameAction::DrawTreasureCard,
+                GameAction::DrawFloodCard
+            ]
+            .iter()
+            .find(|action| legal.contains(action))
+            .cloned()
+            .unwrap_or_else(|| legal[0].clone());
+
+            alice.send(ClientMessage::Action(action.clone()));
+            alice.receive();
+            snapshot = match alice.receive() {
+                ServerMessage::Snapshot(snapshot) => snapshot,
+                other => panic!("Unexpected message: {:?}", other)
+            };
+            played.push(action);
+        }
+
+        // The last action already belongs to the second turn.
+        for action in &played[..played.len() - 1] {
+            match spectator.receive() {
+                ServerMessage::Applied { action: seen, .. } => assert_eq!(action, &seen),
+                other => panic!("Unexpected message: {:?}", other)
+            }
+            match spectator.receive() {
+                ServerMessage::Snapshot(shown) => assert_eq!(0, shown.turn),
+                other => panic!("Unexpected message: {:?}", other)
+            }
+        }
+    }
+}
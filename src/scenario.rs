@@ -0,0 +1,570 @@
+//! Scenarios are games that start from an authored position instead of a
+//! shuffled one, together with an objective the players have to achieve. They
+//! are used for puzzles that teach the rules and to test tricky situations.
+//!
+//! Scenarios are stored as JSON, like maps. Everything that is not provided in
+//! the file is taken from a regular game: tiles are dry, players start on
+//! their spawn tiles with empty hands and the cards that have not been placed
+//! anywhere are shuffled beneath the stacked ones.
+
+use crate::adventurer::AdventurerType;
+use crate::artefact_type::ArtefactType;
+use crate::card_stack::CardStack;
+use crate::difficulty::Difficulty;
+use crate::game_state::{GameState, Outcome};
+use crate::map::{loader, Full as MapFull, IslandTile, IslandTileInfo, IslandTileState, Map};
+use crate::positionable::Positionable;
+use crate::treasure_card::TreasureCard;
+use crate::water_level::WaterLevel;
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The island a scenario is played on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ScenarioMap {
+    /// The tiles written out row by row, `null` being water.
+    Tiles(Vec<Vec<Option<IslandTileInfo>>>),
+    /// A map file as saved by the map editor. Relative paths are relative to
+    /// the scenario file.
+    File(PathBuf)
+}
+
+/// One of the adventurers taking part in the scenario.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioPlayer {
+    pub adventurer: AdventurerType,
+    /// The tile the adventurer starts on. If it is not provided, the
+    /// adventurer starts on their spawn tile.
+    #[serde(default)]
+    pub tile: Option<IslandTileInfo>,
+    #[serde(default)]
+    pub hand: Vec<TreasureCard>
+}
+
+/// What the players have to achieve for the scenario to be solved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    /// Collect the artefact before the number of turns has been played.
+    Collect {
+        artefact: ArtefactType,
+        turns:    u32
+    },
+    /// Do not lose the game before the flood cards have been drawn the number
+    /// of times provided.
+    Survive { flood_phases: u32 },
+    /// Win the game before the number of turns has been played.
+    Win { turns: u32 }
+}
+
+/// How far the players have come with the objective.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectiveStatus {
+    /// The objective can still be achieved.
+    Open,
+    Achieved,
+    Failed
+}
+
+impl Objective {
+    /// Check the objective against the game. The game is expected to have been
+    /// started by [Scenario::start], so its turns are counted from the start
+    /// of the scenario.
+    pub fn status(self, state: &GameState) -> ObjectiveStatus {
+        let lost = match state.outcome() {
+            Some(Outcome::Lost(_)) => true,
+            _ => false
+        };
+
+        match self {
+            Self::Collect { artefact, turns } => {
+                if state.artefacts().contains(&artefact) {
+                    ObjectiveStatus::Achieved
+                }
+                else if lost || state.turn() >= turns {
+                    ObjectiveStatus::Failed
+                }
+                else {
+                    ObjectiveStatus::Open
+                }
+            }
+            Self::Survive { flood_phases } => {
+                if lost {
+                    ObjectiveStatus::Failed
+                }
+                else if state.is_over() || state.turn() >= flood_phases {
+                    ObjectiveStatus::Achieved
+                }
+                else {
+                    ObjectiveStatus::Open
+                }
+            }
+            Self::Win { turns } => {
+                if state.outcome() == Some(Outcome::Won) {
+                    ObjectiveStatus::Achieved
+                }
+                else if lost || state.turn() >= turns {
+                    ObjectiveStatus::Failed
+                }
+                else {
+                    ObjectiveStatus::Open
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Collect { artefact, turns } => {
+                write!(
+                    f,
+                    "Collect the {:?} artefact within {} turns",
+                    artefact, turns
+                )
+            }
+            Self::Survive { flood_phases } => write!(f, "Survive {} flood phases", flood_phases),
+            Self::Win { turns } => write!(f, "Win the game within {} turns", turns)
+        }
+    }
+}
+
+/// An authored starting position of a game.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub map: ScenarioMap,
+    /// The states of all tiles that are not dry.
+    #[serde(default)]
+    pub tiles: HashMap<IslandTileInfo, IslandTileState>,
+    /// The adventurers in the order they take their turns. The first one
+    /// begins.
+    pub players: Vec<ScenarioPlayer>,
+    /// The treasure cards drawn next, the first one on top.
+    #[serde(default)]
+    pub treasure_deck: Vec<TreasureCard>,
+    #[serde(default)]
+    pub treasure_discard: Vec<TreasureCard>,
+    /// The flood cards drawn next, the first one on top.
+    #[serde(default)]
+    pub flood_deck: Vec<IslandTileInfo>,
+    #[serde(default)]
+    pub flood_discard: Vec<IslandTileInfo>,
+    #[serde(default)]
+    pub water_level: u8,
+    /// The artefacts that have already been collected.
+    #[serde(default)]
+    pub artefacts: Vec<ArtefactType>,
+    pub objective: Objective
+}
+
+/// Reasons why a scenario could not be loaded or started.
+#[derive(Debug)]
+pub enum ScenarioError {
+    /// The scenario or its map file could not be read.
+    Io(io::Error),
+    /// The scenario file is not valid JSON or does not describe a scenario.
+    Format(serde_json::Error),
+    /// The rows of the map have different lengths.
+    Map(crate::map::Error),
+    /// The scenario refers to a tile that is not on the map or has sunk.
+    MissingTile(IslandTileInfo),
+    /// A tile is on the map more than once.
+    DuplicateTile(IslandTileInfo),
+    /// The scenario needs more of the treasure card than there are in a deck.
+    TooManyCards(TreasureCard),
+    /// A flood card has been placed more than once.
+    DuplicateFloodCard(IslandTileInfo),
+    /// There are no players, or more than four.
+    PlayerCount(usize)
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Format(err) => write!(f, "invalid scenario: {}", err),
+            Self::Map(err) => write!(f, "invalid map: {}", err),
+            Self::MissingTile(info) => write!(f, "{} is not on the island", info.name()),
+            Self::DuplicateTile(info) => write!(f, "{} is on the map twice", info.name()),
+            Self::TooManyCards(card) => write!(f, "there are not enough {:?} cards", card),
+            Self::DuplicateFloodCard(info) => {
+                write!(f, "the flood card of {} is used twice", info.name())
+            }
+            Self::PlayerCount(count) => {
+                write!(f, "a game needs one to four players, not {}", count)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ScenarioError {
+    fn from(err: io::Error) -> Self { Self::Io(err) }
+}
+
+impl From<serde_json::Error> for ScenarioError {
+    fn from(err: serde_json::Error) -> Self { Self::Format(err) }
+}
+
+impl From<crate::map::Error> for ScenarioError {
+    fn from(err: crate::map::Error) -> Self { Self::Map(err) }
+}
+
+/// Load a scenario from a file
+///
+/// # Parameters
+/// `path` - The path of the scenario file
+///
+/// # Returns
+/// The scenario, with the path of its map file made relative to the working
+/// directory, or the error why it could not be read.
+pub fn load_scenario<P: AsRef<Path>>(path: P) -> Result<Scenario, ScenarioError> {
+    let path = path.as_ref();
+    let mut scenario: Scenario = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    if let ScenarioMap::File(map_path) = &mut scenario.map {
+        if map_path.is_relative() {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            *map_path = dir.join(&map_path);
+        }
+    }
+
+    Ok(scenario)
+}
+
+/// Save a scenario to a file
+///
+/// # Parameters
+/// * `path` - The path of the scenario file
+/// * `scenario` - The scenario to be saved
+pub fn save_scenario<P: AsRef<Path>>(path: P, scenario: &Scenario) -> Result<(), ScenarioError> {
+    fs::write(path, serde_json::to_string_pretty(scenario)?)?;
+
+    Ok(())
+}
+
+impl Scenario {
+    /// Set up the game described by the scenario. The cards that have not been
+    /// placed by the scenario are shuffled with the seed provided.
+    pub fn start(&self, seed: u64) -> Result<GameState, ScenarioError> {
+        if self.players.is_empty() || self.players.len() > 4 {
+            return Err(ScenarioError::PlayerCount(self.players.len()));
+        }
+
+        let mut map = self.load_map()?;
+        for (_, tile) in map.iter_mut() {
+            if let Some(tile) = tile {
+                let state = self.tiles.get(tile.info()).cloned();
+                tile.set_state(state.unwrap_or(IslandTileState::Dry));
+            }
+        }
+        let on_island = |info: IslandTileInfo| {
+            map.iter().any(|(_, tile)| {
+                tile.map_or(false, |tile| {
+                    *tile.info() == info && tile.state() != IslandTileState::Gone
+                })
+            })
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let adventurers: Vec<AdventurerType> = self.players.iter().map(|p| p.adventurer).collect();
+        let mut state = GameState::with_seed(map.clone(), &adventurers, Difficulty::Novice, seed);
+        for (pos, tile) in map.iter() {
+            if let Some(tile) = tile {
+                state.set_tile_state(pos, tile.state());
+            }
+        }
+
+        for (player, scenario_player) in self.players.iter().enumerate() {
+            if let Some(info) = scenario_player.tile {
+                if !on_island(info) {
+                    return Err(ScenarioError::MissingTile(info));
+                }
+                let pos = state.tile_pos(info).unwrap();
+                state.character_mut(player).set_pos(pos);
+            }
+            *state.character_mut(player).hand_mut() = scenario_player.hand.clone();
+        }
+
+        // Every card of the standard deck that has not been placed ends up
+        // shuffled beneath the stacked ones.
+        let mut unused = TreasureCard::standard_deck();
+        let placed = self
+            .players
+            .iter()
+            .flat_map(|p| p.hand.iter())
+            .chain(self.treasure_deck.iter())
+            .chain(self.treasure_discard.iter());
+        for card in placed {
+            match unused.iter().position(|c| c == card) {
+                Some(index) => {
+                    unused.swap_remove(index);
+                }
+                None => return Err(ScenarioError::TooManyCards(*card))
+            }
+        }
+        unused.sort_by_key(|&card| card_order(card));
+        unused.shuffle(&mut rng);
+        *state.treasure_cards_mut() = stacked(unused, &self.treasure_deck, &self.treasure_discard);
+
+        // Only the tiles that are still on the island have flood cards.
+        let mut unused: Vec<IslandTileInfo> = map
+            .iter()
+            .filter_map(|(_, tile)| tile.filter(|t| t.state() != IslandTileState::Gone))
+            .map(|tile| *tile.info())
+            .collect();
+        for info in self.flood_deck.iter().chain(self.flood_discard.iter()) {
+            match unused.iter().position(|i| i == info) {
+                Some(index) => {
+                    unused.swap_remove(index);
+                }
+                None if on_island(*info) => return Err(ScenarioError::DuplicateFloodCard(*info)),
+                None => return Err(ScenarioError::MissingTile(*info))
+            }
+        }
+        unused.sort_by_key(|&info| info as u8);
+        unused.shuffle(&mut rng);
+        *state.flood_cards_mut() = stacked(unused, &self.flood_deck, &self.flood_discard);
+
+        state.set_water_level(WaterLevel::new(self.water_level));
+        *state.artefacts_mut() = self.artefacts.clone();
+
+        Ok(state)
+    }
+
+    fn load_map(&self) -> Result<MapFull, ScenarioError> {
+        let map = match &self.map {
+            ScenarioMap::Tiles(rows) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| row.iter().map(|info| info.map(IslandTile::new)).collect())
+                    .collect();
+                MapFull::from(Map::from_rows(rows)?)
+            }
+            ScenarioMap::File(path) => loader::load_map(path)?
+        };
+
+        let mut seen = Vec::new();
+        for (_, tile) in map.iter() {
+            if let Some(tile) = tile {
+                if seen.contains(tile.info()) {
+                    return Err(ScenarioError::DuplicateTile(*tile.info()));
+                }
+                seen.push(*tile.info());
+            }
+        }
+        for scenario_player in &self.players {
+            let spawn = IslandTileInfo::ALL
+                .iter()
+                .cloned()
+                .find(|info| info.player_spawn() == Some(scenario_player.adventurer))
+                .unwrap();
+            if !seen.contains(&spawn) {
+                return Err(ScenarioError::MissingTile(spawn));
+            }
+        }
+
+        Ok(map)
+    }
+}
+
+// A deck with the stacked cards on top of the shuffled ones. The first stacked
+// card is drawn first.
+fn stacked<T: Copy>(shuffled: Vec<T>, top: &[T], discard: &[T]) -> CardStack<T> {
+    let mut draw_stack = shuffled;
+    draw_stack.extend(top.iter().rev());
+
+    let mut stack = CardStack::new(draw_stack);
+    for &card in discard {
+        stack.discard_card(card);
+    }
+
+    stack
+}
+
+// The shuffled cards must not depend on the order in which they have been
+// removed from the deck, so they are sorted before shuffling.
+fn card_order(card: TreasureCard) -> u8 {
+    match card {
+        TreasureCard::Artefact(artefact) => artefact as u8,
+        TreasureCard::Helicopter => 10,
+        TreasureCard::Sandbags => 11,
+        TreasureCard::WatersRise => 12
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game_state::GameAction;
+    use std::path::Path;
+
+    fn bundled(name: &str) -> Scenario {
+        load_scenario(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("scenarios")
+                .join(name)
+        )
+        .expect("Could not load the scenario")
+    }
+
+    // Play the actions of the deciding player, drawing cards whenever they
+    // have to be drawn.
+    fn play(state: &mut GameState, actions: &[GameAction]) {
+        for action in actions {
+            state.apply(action).unwrap();
+        }
+        while !state.is_over() {
+            let draw = state
+                .legal_actions()
+                .into_iter()
+                .find(|a| *a == GameAction::DrawTreasureCard || *a == GameAction::DrawFloodCard);
+            match draw {
+                Some(draw) => state.apply(&draw).unwrap(),
+                None => break
+            };
+        }
+    }
+
+    #[test]
+    fn start_position() {
+        let scenario = bundled("fire_in_two_turns.json");
+        let state = scenario.start(0).unwrap();
+
+        let moon = state.tile_pos(IslandTileInfo::TempleOfTheMoon).unwrap();
+        assert_eq!(moon, state.characters()[1].pos());
+        assert_eq!(scenario.players[1].hand, *state.characters()[1].hand());
+        assert_eq!(
+            Some(&Some(IslandTile::new(IslandTileInfo::FoolsLanding))),
+            state
+                .map()
+                .get(state.tile_pos(IslandTileInfo::FoolsLanding).unwrap())
+        );
+
+        let ambers = state.tile_pos(IslandTileInfo::CaveOfAmbers).unwrap();
+        assert_eq!(
+            IslandTileState::Gone,
+            state.map().get(ambers).unwrap().unwrap().state()
+        );
+        assert_eq!(2, state.water_level().level());
+
+        // The stacked cards are drawn first and the deck is complete.
+        let draw_stack = state.treasure_cards().draw_stack();
+        assert_eq!(
+            scenario.treasure_deck,
+            draw_stack.iter().rev().take(4).cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(28 - 5, state.treasure_cards().size());
+        assert_eq!(
+            IslandTileInfo::MistyMarsh,
+            *state.flood_cards().draw_stack().last().unwrap()
+        );
+        assert_eq!(23, state.flood_cards().size());
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let scenario = bundled("hold_fools_landing.json");
+
+        assert_eq!(
+            scenario.start(3).unwrap().key(),
+            scenario.start(3).unwrap().key()
+        );
+    }
+
+    #[test]
+    fn collect_objective() {
+        let scenario = bundled("fire_in_two_turns.json");
+        let mut state = scenario.start(0).unwrap();
+        assert_eq!(ObjectiveStatus::Open, scenario.objective.status(&state));
+
+        play(
+            &mut state,
+            &[
+                GameAction::GiveCard {
+                    to:   1,
+                    card: TreasureCard::Artefact(ArtefactType::Fire)
+                },
+                GameAction::EndActions
+            ]
+        );
+        assert_eq!(ObjectiveStatus::Open, scenario.objective.status(&state));
+
+        let cave = state.tile_pos(IslandTileInfo::CaveOfShadows).unwrap();
+        play(
+            &mut state,
+            &[GameAction::SpecialMove(cave), GameAction::CollectArtefact]
+        );
+        assert_eq!(ObjectiveStatus::Achieved, scenario.objective.status(&state));
+    }
+
+    #[test]
+    fn survive_objective() {
+        let scenario = bundled("hold_fools_landing.json");
+
+        // Fools' Landing sinks, if it is not drained right away.
+        let mut state = scenario.start(0).unwrap();
+        play(&mut state, &[GameAction::EndActions]);
+        assert_eq!(ObjectiveStatus::Failed, scenario.objective.status(&state));
+
+        let mut state = scenario.start(0).unwrap();
+        let gold_gate = state.tile_pos(IslandTileInfo::GoldGate).unwrap();
+        let fools_landing = state.tile_pos(IslandTileInfo::FoolsLanding).unwrap();
+        play(
+            &mut state,
+            &[
+                GameAction::Move(gold_gate),
+                GameAction::Drain(fools_landing),
+                GameAction::Drain(gold_gate),
+                GameAction::EndActions
+            ]
+        );
+        assert_eq!(ObjectiveStatus::Open, scenario.objective.status(&state));
+        play(&mut state, &[GameAction::EndActions]);
+        assert_eq!(ObjectiveStatus::Achieved, scenario.objective.status(&state));
+    }
+
+    #[test]
+    fn invalid_scenarios() {
+        let mut scenario = bundled("fire_in_two_turns.json");
+        scenario.treasure_discard = vec![TreasureCard::Helicopter; 4];
+        match scenario.start(0) {
+            Err(ScenarioError::TooManyCards(TreasureCard::Helicopter)) => {}
+            other => panic!("Unexpected result: {:?}", other.map(|_| ()))
+        }
+
+        let mut scenario = bundled("fire_in_two_turns.json");
+        scenario.players[0].tile = Some(IslandTileInfo::CaveOfAmbers);
+        match scenario.start(0) {
+            Err(ScenarioError::MissingTile(IslandTileInfo::CaveOfAmbers)) => {}
+            other => panic!("Unexpected result: {:?}", other.map(|_| ()))
+        }
+
+        let mut scenario = bundled("hold_fools_landing.json");
+        scenario.flood_deck.push(IslandTileInfo::FoolsLanding);
+        match scenario.start(0) {
+            Err(ScenarioError::DuplicateFloodCard(IslandTileInfo::FoolsLanding)) => {}
+            other => panic!("Unexpected result: {:?}", other.map(|_| ()))
+        }
+    }
+
+    #[test]
+    fn all_bundled_scenarios_start() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let scenario = load_scenario(&path).unwrap();
+            let state = scenario
+                .start(0)
+                .unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+            assert_eq!(ObjectiveStatus::Open, scenario.objective.status(&state));
+        }
+    }
+}
@@ -0,0 +1,217 @@
+//! The settings of the player. They are kept in `config/settings.ron`, next to
+//! the display configuration, and are available to the states and systems as a
+//! resource.
+
+use amethyst::utils::application_root_dir;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// The file the settings are kept in, relative to the root of the application.
+pub const SETTINGS_FILE: &str = "config/settings.ron";
+
+/// The window sizes that can be chosen, in pixels.
+pub const RESOLUTIONS: [(u32, u32); 5] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440)
+];
+
+/// How much a volume changes with one step on the settings screen.
+pub const VOLUME_STEP: u8 = 10;
+/// The loudest a volume can be set to.
+pub const MAX_VOLUME: u8 = 100;
+
+/// How fast the characters walk from one tile to the next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnimationSpeed {
+    /// The characters jump to their new tile right away.
+    Off,
+    Slow,
+    Normal,
+    Fast
+}
+
+impl AnimationSpeed {
+    pub const ALL: [AnimationSpeed; 4] = [Self::Off, Self::Slow, Self::Normal, Self::Fast];
+
+    /// The number of tiles a character crosses per second, or `None` if it is
+    /// not animated at all.
+    pub fn tiles_per_second(self) -> Option<f32> {
+        match self {
+            Self::Off => None,
+            Self::Slow => Some(2.),
+            Self::Normal => Some(5.),
+            Self::Fast => Some(12.)
+        }
+    }
+}
+
+/// Everything the player can change on the settings screen. The game does not
+/// play any sound yet, so the volumes are only kept until it does.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameSettings {
+    /// The volume of the music, from zero to [MAX_VOLUME].
+    pub music_volume:    u8,
+    /// The volume of the sound effects, from zero to [MAX_VOLUME].
+    pub effects_volume:  u8,
+    pub fullscreen:      bool,
+    /// The size of the window, if it is not in fullscreen. One of
+    /// [RESOLUTIONS].
+    pub resolution:      (u32, u32),
+    pub animation_speed: AnimationSpeed,
+    /// Ask again before leaving a running game.
+    pub confirmations:   bool,
+    /// Allow the developer tools to be opened during a game.
+    pub developer_tools: bool
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            music_volume:    80,
+            effects_volume:  80,
+            fullscreen:      false,
+            resolution:      (1920, 1080),
+            animation_speed: AnimationSpeed::Normal,
+            confirmations:   true,
+            developer_tools: false
+        }
+    }
+}
+
+/// Reasons why the settings could not be loaded or saved.
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The settings file could not be read or written.
+    Io(io::Error),
+    /// The settings file is not valid RON or does not describe settings.
+    Format(ron::de::Error),
+    /// The settings could not be written as RON.
+    Serialize(ron::ser::Error)
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Format(err) => write!(f, "invalid settings: {}", err),
+            Self::Serialize(err) => write!(f, "unable to write the settings: {}", err)
+        }
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(err: io::Error) -> Self { Self::Io(err) }
+}
+
+impl From<ron::de::Error> for SettingsError {
+    fn from(err: ron::de::Error) -> Self { Self::Format(err) }
+}
+
+impl From<ron::ser::Error> for SettingsError {
+    fn from(err: ron::ser::Error) -> Self { Self::Serialize(err) }
+}
+
+impl GameSettings {
+    /// Load the settings from the file. If there is no such file yet, the
+    /// default settings are used. Settings missing in the file keep their
+    /// default value, so files of older versions can still be read.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SettingsError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(ron::de::from_str(&content)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into())
+        }
+    }
+
+    /// Save the settings to the file, creating its directory if necessary.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SettingsError> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(self, PrettyConfig::default())?
+        )?;
+
+        Ok(())
+    }
+
+    /// The position of the resolution in [RESOLUTIONS]. A resolution that has
+    /// been written into the file by hand counts as the closest one below it.
+    pub fn resolution_index(&self) -> usize {
+        RESOLUTIONS
+            .iter()
+            .rposition(|&(width, height)| width <= self.resolution.0 && height <= self.resolution.1)
+            .unwrap_or(0)
+    }
+}
+
+/// The file the settings are kept in, next to the display configuration.
+pub fn default_path() -> io::Result<PathBuf> { Ok(application_root_dir()?.join(SETTINGS_FILE)) }
+
+/// Change a volume by a number of steps, keeping it between zero and
+/// [MAX_VOLUME].
+pub fn step_volume(volume: u8, steps: i32) -> u8 {
+    (i32::from(volume) + steps * i32::from(VOLUME_STEP))
+        .max(0)
+        .min(i32::from(MAX_VOLUME)) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir()
+            .join(format!("java-game-settings-{}", std::process::id()))
+            .join("settings.ron");
+        assert_eq!(GameSettings::default(), GameSettings::load(&path).unwrap());
+
+        let settings = GameSettings {
+            music_volume: 30,
+            fullscreen: true,
+            resolution: (1600, 900),
+            animation_speed: AnimationSpeed::Off,
+            developer_tools: true,
+            ..GameSettings::default()
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(settings, GameSettings::load(&path).unwrap());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn missing_settings_are_default() {
+        let settings: GameSettings = ron::de::from_str("(music_volume: 20)").unwrap();
+        assert_eq!(20, settings.music_volume);
+        assert_eq!(
+            GameSettings::default().animation_speed,
+            settings.animation_speed
+        );
+        assert!(settings.confirmations);
+    }
+
+    #[test]
+    fn volume_and_resolution_limits() {
+        assert_eq!(0, step_volume(10, -3));
+        assert_eq!(MAX_VOLUME, step_volume(95, 1));
+        assert_eq!(60, step_volume(50, 1));
+
+        let mut settings = GameSettings::default();
+        assert_eq!(3, settings.resolution_index());
+        settings.resolution = (1700, 1000);
+        assert_eq!(2, settings.resolution_index());
+        settings.resolution = (800, 600);
+        assert_eq!(0, settings.resolution_index());
+    }
+}
@@ -0,0 +1,227 @@
+//! Play complete games without any frontend and collect statistics about them.
+//! This is used to balance maps and to check that changes of the rules do not
+//! make the game a lot easier or harder by accident.
+
+use crate::game_state::{GameAction, GameEvent, GameState, LossReason, Outcome};
+use crate::map::IslandTileInfo;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The result of a single simulated game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameReport {
+    /// How the game ended, or `None` if it was aborted after the maximum amount
+    /// of turns.
+    pub outcome: Option<Outcome>,
+    /// The amount of turns that have been started.
+    pub turns:   u32,
+    /// All tiles that sank during the game, in the order they sank.
+    pub sunk:    Vec<IslandTileInfo>
+}
+
+/// Play the game to the end, letting the policy choose every action.
+///
+/// # Parameters
+/// `policy` is asked for the next action as long as the game is not over. If
+/// it returns an action that is not allowed, the game is aborted.
+/// `max_turns` stops games that would otherwise never end, for instance when
+/// the policy keeps flying around with the Pilot.
+pub fn play<P>(mut state: GameState, mut policy: P, max_turns: u32) -> GameReport
+where
+    P: FnMut(&GameState) -> GameAction
+{
+    let mut sunk = Vec::new();
+    while !state.is_over() && state.turn() < max_turns {
+        let action = policy(&state);
+        let events = match state.apply(&action) {
+            Ok(events) => events,
+            Err(_) => break
+        };
+
+        for event in events {
+            if let GameEvent::TileSunk(pos) = event {
+                if let Some(Some(tile)) = state.map().get(pos) {
+                    sunk.push(*tile.info());
+                }
+            }
+        }
+    }
+
+    GameReport {
+        outcome: state.outcome(),
+        turns: state.turns_played(),
+        sunk
+    }
+}
+
+/// Aggregated results of many games.
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    games: u32,
+    wins: u32,
+    unfinished: u32,
+    total_turns: u64,
+    losses: HashMap<LossReason, u32>,
+    sunk: HashMap<IslandTileInfo, u32>
+}
+
+impl Statistics {
+    pub fn new() -> Self { Self::default() }
+
+    /// Add the result of another game to the statistics.
+    pub fn add(&mut self, report: &GameReport) {
+        self.games += 1;
+        self.total_turns += u64::from(report.turns);
+        match report.outcome {
+            Some(Outcome::Won) => self.wins += 1,
+            Some(Outcome::Lost(reason)) => *self.losses.entry(reason).or_insert(0) += 1,
+            None => self.unfinished += 1
+        }
+
+        for &info in &report.sunk {
+            *self.sunk.entry(info).or_insert(0) += 1;
+        }
+    }
+
+    pub fn games(&self) -> u32 { self.games }
+
+    pub fn wins(&self) -> u32 { self.wins }
+
+    /// The amount of games that have been aborted before they were over.
+    pub fn unfinished(&self) -> u32 { self.unfinished }
+
+    /// The amount of games that have been lost for the reason provided.
+    pub fn losses(&self, reason: LossReason) -> u32 {
+        self.losses.get(&reason).copied().unwrap_or(0)
+    }
+
+    /// The share of games that have been won, between 0 and 1.
+    pub fn win_rate(&self) -> f64 { ratio(self.wins, self.games) }
+
+    pub fn average_turns(&self) -> f64 {
+        if self.games == 0 {
+            0.
+        }
+        else {
+            self.total_turns as f64 / f64::from(self.games)
+        }
+    }
+
+    /// The share of games in which the tile sank, between 0 and 1.
+    pub fn sink_rate(&self, info: IslandTileInfo) -> f64 {
+        ratio(self.sunk.get(&info).copied().unwrap_or(0), self.games)
+    }
+}
+
+fn ratio(amount: u32, total: u32) -> f64 {
+    if total == 0 {
+        0.
+    }
+    else {
+        f64::from(amount) / f64::from(total)
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Games:         {}", self.games)?;
+        writeln!(
+            f,
+            "Won:           {} ({:.1}%)",
+            self.wins,
+            self.win_rate() * 100.
+        )?;
+        if self.unfinished != 0 {
+            writeln!(f, "Unfinished:    {}", self.unfinished)?;
+        }
+        writeln!(f, "Average turns: {:.1}", self.average_turns())?;
+
+        let mut losses: Vec<(&LossReason, &u32)> = self.losses.iter().collect();
+        losses.sort_by(|a, b| b.1.cmp(a.1));
+        writeln!(f, "\nLoss reasons:")?;
+        for (reason, &amount) in losses {
+            writeln!(
+                f,
+                "  {:<30} {:>6} ({:.1}%)",
+                format!("{:?}", reason),
+                amount,
+                ratio(amount, self.games) * 100.
+            )?;
+        }
+
+        let mut tiles: Vec<IslandTileInfo> = IslandTileInfo::ALL.to_vec();
+        tiles.sort_by(|a, b| self.sink_rate(*b).partial_cmp(&self.sink_rate(*a)).unwrap());
+        writeln!(f, "\nSunk tiles:")?;
+        for info in tiles {
+            writeln!(
+                f,
+                "  {:<30} {:>5.1}%",
+                info.name(),
+                self.sink_rate(info) * 100.
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::difficulty::Difficulty;
+    use crate::map::generate;
+    use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+    fn random_game(seed: u64) -> GameReport {
+        let map = generate::filled_island_with(
+            &generate::classic_shape(),
+            &mut StdRng::seed_from_u64(seed)
+        );
+        let state = GameState::with_seed(
+            map,
+            &[AdventurerType::Pilot, AdventurerType::Diver],
+            Difficulty::Normal,
+            seed
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        play(
+            state,
+            |state| state.legal_actions().choose(&mut rng).unwrap().clone(),
+            1000
+        )
+    }
+
+    #[test]
+    fn random_games_end() {
+        for seed in 0..20 {
+            let report = random_game(seed);
+            assert!(report.outcome.is_some());
+            assert!(report.turns > 1);
+        }
+    }
+
+    #[test]
+    fn statistics() {
+        let mut statistics = Statistics::new();
+        statistics.add(&GameReport {
+            outcome: Some(Outcome::Won),
+            turns:   10,
+            sunk:    vec![IslandTileInfo::BreakersBridge]
+        });
+        statistics.add(&GameReport {
+            outcome: Some(Outcome::Lost(LossReason::WaterLevel)),
+            turns:   20,
+            sunk:    vec![IslandTileInfo::BreakersBridge, IslandTileInfo::FoolsLanding]
+        });
+
+        assert_eq!(2, statistics.games());
+        assert_eq!(0.5, statistics.win_rate());
+        assert_eq!(15., statistics.average_turns());
+        assert_eq!(1, statistics.losses(LossReason::WaterLevel));
+        assert_eq!(0, statistics.losses(LossReason::FoolsLandingSunk));
+        assert_eq!(1., statistics.sink_rate(IslandTileInfo::BreakersBridge));
+        assert_eq!(0.5, statistics.sink_rate(IslandTileInfo::FoolsLanding));
+        assert_eq!(0., statistics.sink_rate(IslandTileInfo::CaveOfAmbers));
+    }
+}
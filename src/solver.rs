@@ -0,0 +1,283 @@
+//! Exact search for a way to win from a game state. Since the order of the
+//! cards in the decks is part of the state, the game is completely determined
+//! by the actions taken, so the solver can find out whether the team can still
+//! win at all.
+//!
+//! The search tries all legal actions, shortest lines first, and remembers the
+//! states it has already seen, so it does not search the same position twice.
+//! It is bounded by the number of actions it looks ahead and the number of
+//! states it visits, so it can be used for small positions in tests and for
+//! puzzles.
+
+use crate::game_state::{GameAction, GameState, Outcome, StateKey};
+use std::collections::HashMap;
+
+/// The result of the search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// The game can be won with the actions provided, in this order. There is
+    /// no shorter way to win.
+    Win(Vec<GameAction>),
+    /// The game will be lost, no matter what the players do.
+    Lost,
+    /// The search has hit its bounds before it could decide.
+    Unknown
+}
+
+/// The verdict of the solver together with the effort it took.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Solution {
+    pub verdict: Verdict,
+    /// The number of game states that have been looked at.
+    pub nodes:   u64
+}
+
+/// Exhaustive search with configurable bounds.
+#[derive(Clone, Copy, Debug)]
+pub struct Solver {
+    max_depth: u32,
+    max_nodes: u64
+}
+
+impl Solver {
+    /// The number of actions looked ahead unless set otherwise.
+    pub const DEFAULT_MAX_DEPTH: u32 = 30;
+    /// The number of states looked at unless set otherwise.
+    pub const DEFAULT_MAX_NODES: u64 = 1_000_000;
+
+    pub fn new() -> Self {
+        Self {
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            max_nodes: Self::DEFAULT_MAX_NODES
+        }
+    }
+
+    /// Set the maximum number of actions of a winning line.
+    pub fn max_depth(mut self, max_depth: u32) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Set the maximum number of game states the search may look at.
+    pub fn max_nodes(mut self, max_nodes: u64) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Find out whether the game can still be won from the state provided.
+    pub fn solve(&self, state: &GameState) -> Solution {
+        let mut search = Search {
+            max_nodes: self.max_nodes,
+            nodes:     0,
+            seen:      HashMap::new()
+        };
+
+        // Deepen the search step by step, so the first win found is the
+        // shortest one. The states seen before stay useful, since they know
+        // how deep they have been searched.
+        for depth in 0..=self.max_depth {
+            let verdict = match search.search(state, depth) {
+                Found::Win(mut line) => {
+                    line.reverse();
+                    Verdict::Win(line)
+                }
+                Found::Lost => Verdict::Lost,
+                Found::Aborted => Verdict::Unknown,
+                Found::CutOff => continue
+            };
+
+            return Solution {
+                verdict,
+                nodes: search.nodes
+            };
+        }
+
+        Solution {
+            verdict: Verdict::Unknown,
+            nodes:   search.nodes
+        }
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self { Self::new() }
+}
+
+// What has been found searching below a state.
+enum Found {
+    // The winning line, with the last action first.
+    Win(Vec<GameAction>),
+    // There is no way to win, no matter how deep the search would go.
+    Lost,
+    // No win within the depth searched, but a deeper search might find one.
+    CutOff,
+    // The maximum number of nodes has been reached.
+    Aborted
+}
+
+// What is known about a state that has no win within the depth.
+#[derive(Clone, Copy)]
+enum Known {
+    Lost,
+    NoWinWithin(u32)
+}
+
+struct Search {
+    max_nodes: u64,
+    nodes:     u64,
+    seen:      HashMap<StateKey, Known>
+}
+
+impl Search {
+    fn search(&mut self, state: &GameState, depth: u32) -> Found {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes {
+            return Found::Aborted;
+        }
+
+        match state.outcome() {
+            Some(Outcome::Won) => return Found::Win(Vec::new()),
+            Some(Outcome::Lost(_)) => return Found::Lost,
+            None => {}
+        }
+        if depth == 0 {
+            return Found::CutOff;
+        }
+
+        let key = state.key();
+        match self.seen.get(&key) {
+            Some(Known::Lost) => return Found::Lost,
+            Some(Known::NoWinWithin(searched)) if *searched >= depth => return Found::CutOff,
+            _ => {}
+        }
+
+        let mut cut_off = false;
+        for action in state.legal_actions() {
+            let mut next = state.clone();
+            next.apply(&action)
+                .expect("Legal action could not be applied");
+
+            match self.search(&next, depth - 1) {
+                Found::Win(mut line) => {
+                    line.push(action);
+                    return Found::Win(line);
+                }
+                Found::CutOff => cut_off = true,
+                Found::Lost => {}
+                Found::Aborted => return Found::Aborted
+            }
+        }
+
+        if cut_off {
+            self.seen.insert(key, Known::NoWinWithin(depth));
+            Found::CutOff
+        }
+        else {
+            self.seen.insert(key, Known::Lost);
+            Found::Lost
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::adventurer::AdventurerType;
+    use crate::artefact_type::ArtefactType;
+    use crate::card_stack::CardStack;
+    use crate::difficulty::Difficulty;
+    use crate::map::{generate, IslandTileInfo};
+    use crate::positionable::Positionable;
+    use crate::treasure_card::TreasureCard;
+    use crate::water_level::{WaterLevel, LOOSING_WATER_LEVEL};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // A pilot with a helicopter card on Fools' Landing, who has collected all
+    // artefacts but the one provided.
+    fn endgame(missing: Option<ArtefactType>) -> GameState {
+        let map =
+            generate::filled_island_with(&generate::classic_shape(), &mut StdRng::seed_from_u64(0));
+        let mut state = GameState::with_seed(map, &[AdventurerType::Pilot], Difficulty::Novice, 0);
+        *state.artefacts_mut() = ArtefactType::ALL
+            .iter()
+            .cloned()
+            .filter(|&a| Some(a) != missing)
+            .collect();
+
+        let character = state.character_mut(0);
+        character.hand_mut().clear();
+        character.hand_mut().push(TreasureCard::Helicopter);
+        if let Some(artefact) = missing {
+            character
+                .hand_mut()
+                .extend(vec![TreasureCard::Artefact(artefact); 4]);
+        }
+
+        state
+    }
+
+    #[test]
+    fn lift_off() {
+        let state = endgame(None);
+
+        let solution = Solver::new().solve(&state);
+        assert_eq!(
+            Verdict::Win(vec![GameAction::LiftOff { player: 0 }]),
+            solution.verdict
+        );
+    }
+
+    #[test]
+    fn collect_and_return() {
+        let mut state = endgame(Some(ArtefactType::Fire));
+        let cave = state.tile_pos(IslandTileInfo::CaveOfShadows).unwrap();
+        state.character_mut(0).set_pos(cave);
+
+        let line = match Solver::new().max_depth(4).solve(&state).verdict {
+            Verdict::Win(line) => line,
+            other => panic!("No win found: {:?}", other)
+        };
+        assert_eq!(3, line.len());
+        assert_eq!(GameAction::CollectArtefact, line[0]);
+        assert_eq!(GameAction::LiftOff { player: 0 }, line[2]);
+
+        let mut replay = state.clone();
+        for action in &line {
+            replay.apply(action).unwrap();
+        }
+        assert_eq!(Some(Outcome::Won), replay.outcome());
+    }
+
+    #[test]
+    fn bounded_by_depth() {
+        let mut state = endgame(Some(ArtefactType::Fire));
+        let cave = state.tile_pos(IslandTileInfo::CaveOfShadows).unwrap();
+        state.character_mut(0).set_pos(cave);
+
+        assert_eq!(
+            Verdict::Unknown,
+            Solver::new().max_depth(2).solve(&state).verdict
+        );
+    }
+
+    #[test]
+    fn bounded_by_nodes() {
+        let state = endgame(Some(ArtefactType::Fire));
+
+        let solution = Solver::new().max_nodes(100).solve(&state);
+        assert_eq!(Verdict::Unknown, solution.verdict);
+        assert!(solution.nodes <= 101);
+    }
+
+    #[test]
+    fn lost_to_the_water() {
+        // The next treasure card lets the water rise above the limit, so the
+        // game is lost at the end of this turn, whatever the pilot does.
+        let mut state = endgame(Some(ArtefactType::Fire));
+        state.character_mut(0).hand_mut().clear();
+        state.set_water_level(WaterLevel::new(LOOSING_WATER_LEVEL - 1));
+        *state.treasure_cards_mut() = CardStack::new(vec![TreasureCard::WatersRise; 2]);
+
+        assert_eq!(Verdict::Lost, Solver::new().solve(&state).verdict);
+    }
+}
@@ -0,0 +1,656 @@
+//! The board of a game and how actions are chosen on it. The local
+//! [Game](super::Game) and the [RemoteGame](super::RemoteGame) share it: both
+//! show a [GameSnapshot] resource, and the actions chosen on it are put into
+//! the [ActionQueue], where the state takes them out to apply them or to send
+//! them to the server.
+
+use amethyst::{
+    assets::Handle,
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    renderer::{palette::Srgba, resources::Tint, SpriteSheet},
+    shrev::{EventChannel, ReaderId},
+    ui::Anchor
+};
+
+use super::game::{spawn_characters, ActionQueue};
+use crate::bot::Hint;
+use crate::character::{Character, SPRITE_SCALE};
+use crate::game_state::{GameAction, GameSnapshot, GameView, Outcome};
+use crate::map::{
+    full::{calculate_field_pos, TILE_WIDTH},
+    FieldPos, Full as MapFull
+};
+use crate::math::Vec2;
+use crate::system::{
+    DragArea, DragEvent, Draggable, Dragged, DropZone, TileEvent, TileOption, TileOptions
+};
+use crate::treasure_card::TreasureCard;
+use crate::ui;
+
+const HAND_PANEL_WIDTH: f32 = 520.;
+const HAND_PANEL_HEIGHT: f32 = 200.;
+const CARD_WIDTH: f32 = 160.;
+const CARD_HEIGHT: f32 = 40.;
+const CARD_GAP: f32 = 10.;
+const CARDS_PER_ROW: usize = 3;
+
+/// Whose actions can be chosen on the board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controls {
+    /// The players share the machine, so the actions of all of them.
+    Everyone,
+    /// Only the actions of the player in the seat.
+    Seat(usize),
+    /// None at all, for instance for a spectator.
+    Nobody
+}
+
+/// The entities that make up the board: the tiles, the characters standing on
+/// them and the cards in the hands of the players, which are shown on the
+/// right of the screen above the discard zone.
+#[derive(Default)]
+pub(crate) struct Pieces {
+    pub board: Vec<Entity>,
+    pub characters: Vec<Entity>,
+    pub hand_panels: Vec<Entity>,
+    /// The cards shown in the hands, with the players holding them.
+    pub cards: Vec<(Entity, usize, TreasureCard)>,
+    pub discard_zone: Option<Entity>
+}
+
+impl Pieces {
+    /// Show the board and the hands of the snapshot.
+    pub fn show(
+        &mut self,
+        world: &mut World,
+        snapshot: &GameSnapshot,
+        sprite_sheet: &Handle<SpriteSheet>
+    ) {
+        self.show_board(world, snapshot, sprite_sheet);
+        self.show_hands(world, snapshot);
+    }
+
+    // Show the tiles and characters of the snapshot. They are only spawned once
+    // and keep their entities afterwards, so the tiles change with the map
+    // resource and the characters can walk to their new tiles. The character of
+    // the player who has to decide is tinted, so everyone can see whose turn it
+    // is.
+    fn show_board(
+        &mut self,
+        world: &mut World,
+        snapshot: &GameSnapshot,
+        sprite_sheet: &Handle<SpriteSheet>
+    ) {
+        let mut map = snapshot.map.clone();
+        map.transform_mut().set_translation_xyz(64., 64., -1.);
+        if self.board.is_empty() {
+            self.board = map.create_tile_entities(world);
+        }
+
+        let characters: Vec<Character> = snapshot.characters.iter().map(Character::from).collect();
+        if self.characters.is_empty() {
+            self.characters = spawn_characters(&characters, &map, world, sprite_sheet.clone());
+            // The character sprites are as large as the tiles.
+            let size = TILE_WIDTH * SPRITE_SCALE;
+            let mut draggables = world.write_storage::<Draggable>();
+            for &entity in &self.characters {
+                let _ = draggables.insert(
+                    entity,
+                    Draggable(DragArea::World(Vec2::from_values(size, size)))
+                );
+            }
+        }
+        else {
+            let mut storage = world.write_storage::<Character>();
+            for (&entity, character) in self.characters.iter().zip(characters) {
+                let _ = storage.insert(entity, character);
+            }
+        }
+        {
+            let mut tints = world.write_storage::<Tint>();
+            for &entity in &self.characters {
+                tints.remove(entity);
+            }
+            if let Some(&deciding) = self.characters.get(snapshot.deciding_player) {
+                let _ = tints.insert(deciding, Tint(Srgba::new(1.0, 0.9, 0.4, 1.0)));
+            }
+        }
+        world.insert(map);
+    }
+
+    // Show who is holding which cards. The hands and the discard zone are only
+    // created once, the cards are replaced every time.
+    fn show_hands(&mut self, world: &mut World, snapshot: &GameSnapshot) {
+        if self.hand_panels.is_empty() {
+            self.hand_panels = (0..snapshot.characters.len())
+                .map(|player| {
+                    ui::create_label(
+                        world,
+                        &format!("hand_{}", player),
+                        Anchor::TopRight,
+                        (-ui::MARGIN, hand_panel_y(player)),
+                        (HAND_PANEL_WIDTH, HAND_PANEL_HEIGHT),
+                        String::new()
+                    )
+                })
+                .collect();
+            let discard_zone = ui::create_label(
+                world,
+                "discard_zone",
+                Anchor::BottomRight,
+                (-ui::MARGIN, ui::MARGIN),
+                (HAND_PANEL_WIDTH, CARD_HEIGHT * 2.),
+                "Drop cards here to discard them".to_string()
+            );
+            let mut zones = world.write_storage::<DropZone>();
+            for &entity in self.hand_panels.iter().chain(Some(&discard_zone)) {
+                let _ = zones.insert(entity, DropZone(DragArea::Ui));
+            }
+            self.discard_zone = Some(discard_zone);
+        }
+        for (player, &panel) in self.hand_panels.iter().enumerate() {
+            ui::set_text(world, panel, hand_text(snapshot, player));
+        }
+
+        let cards: Vec<Entity> = self.cards.iter().map(|&(entity, ..)| entity).collect();
+        let _ = world.delete_entities(&cards);
+        self.cards.clear();
+        for (player, character) in snapshot.characters.iter().enumerate() {
+            for (i, &card) in character.hand.iter().enumerate() {
+                let column = (i % CARDS_PER_ROW) as f32;
+                let row = (i / CARDS_PER_ROW) as f32;
+                let entity = ui::create_label(
+                    world,
+                    &format!("card_{}_{}", player, i),
+                    Anchor::TopRight,
+                    (
+                        -ui::MARGIN - HAND_PANEL_WIDTH
+                            + column * (CARD_WIDTH + CARD_GAP)
+                            + CARD_WIDTH,
+                        hand_panel_y(player) - 50. - row * (CARD_HEIGHT + CARD_GAP)
+                    ),
+                    (CARD_WIDTH, CARD_HEIGHT),
+                    card_name(card)
+                );
+                let _ = world
+                    .write_storage::<Draggable>()
+                    .insert(entity, Draggable(DragArea::Ui));
+                self.cards.push((entity, player, card));
+            }
+        }
+    }
+
+    /// All entities of the board.
+    pub fn entities(&self) -> Vec<Entity> {
+        let mut entities = self.board.clone();
+        entities.extend(self.characters.iter().copied());
+        entities.extend(self.hand_panels.iter().copied());
+        entities.extend(self.cards.iter().map(|&(entity, ..)| entity));
+        entities.extend(self.discard_zone);
+        entities
+    }
+
+    /// Remove all entities of the board from the world.
+    pub fn delete(&mut self, world: &mut World) {
+        let _ = world.delete_entities(&self.entities());
+        *self = Self::default();
+    }
+}
+
+/// Who has to decide now, or how the game has ended.
+pub(crate) fn indicator_text(snapshot: &GameSnapshot) -> String {
+    match snapshot.outcome {
+        Some(Outcome::Won) => "The adventurers have escaped the island!".to_string(),
+        Some(Outcome::Lost(reason)) => format!("The game is lost: {:?}", reason),
+        None => {
+            let player = snapshot.deciding_player;
+            format!(
+                "Turn {}: Player {} ({:?}) - {:?}",
+                snapshot.turns_played(),
+                player + 1,
+                snapshot.characters[player].adventurer,
+                snapshot.action_state
+            )
+        }
+    }
+}
+
+// The offset of the hand of the player from the top of the screen.
+fn hand_panel_y(player: usize) -> f32 { -ui::MARGIN - 80. - player as f32 * HAND_PANEL_HEIGHT }
+
+// What a card in a hand says.
+fn card_name(card: TreasureCard) -> String {
+    match card {
+        TreasureCard::Artefact(artefact) => format!("{:?}", artefact),
+        card => format!("{:?}", card)
+    }
+}
+
+// Who the hand belongs to, marking the one who has to decide. The cards are
+// shown on their own, so they can be dragged.
+fn hand_text(snapshot: &GameSnapshot, player: usize) -> String {
+    let character = &snapshot.characters[player];
+    let marker = if player == snapshot.deciding_player {
+        "> "
+    }
+    else {
+        ""
+    };
+    let empty = if character.hand.is_empty() {
+        "\nNo cards"
+    }
+    else {
+        ""
+    };
+
+    format!(
+        "{}Player {} ({:?}){}",
+        marker,
+        player + 1,
+        character.adventurer,
+        empty
+    )
+}
+
+/// Turns keys, clicks and drags on the board into actions in the
+/// [ActionQueue]. Only the legal actions of the [GameSnapshot] resource the
+/// [Controls] allow are offered.
+///
+/// `M`, `S`, `D` and `N` highlight the tiles the active adventurer can move,
+/// move specially, drain or navigate another adventurer to. Pressing `N` again
+/// navigates the next adventurer, and pressing the key of the highlighted
+/// action again removes the highlights. Clicking a highlighted tile takes the
+/// action there. With the keyboard, `Tab` goes to the next highlighted tile and
+/// `Enter` takes the action on it. Characters can also be dragged onto the
+/// tiles they can move or be navigated to, and cards onto the hand of another
+/// player to give them away or onto the discard zone to discard them. `Space`
+/// ends the actions of the turn or draws the next card.
+pub(crate) struct BoardInput {
+    controls: Controls,
+    /// The action the highlighted tiles are for.
+    selection: Option<TileOption>,
+    /// The player the navigator moves, if the selection is to navigate.
+    navigated: usize,
+    /// The index of the highlighted tile chosen with the keyboard, or the one
+    /// under the mouse.
+    cursor: usize,
+    /// Why the hinted tiles are highlighted, while they are.
+    hint: Option<String>,
+    tile_events: Option<ReaderId<TileEvent>>,
+    drag_events: Option<ReaderId<DragEvent>>
+}
+
+impl BoardInput {
+    pub fn new(controls: Controls) -> Self {
+        Self {
+            controls,
+            selection: None,
+            navigated: 0,
+            cursor: 0,
+            hint: None,
+            tile_events: None,
+            drag_events: None
+        }
+    }
+
+    /// Change whose actions can be chosen, for instance once the server has
+    /// assigned a seat.
+    pub fn set_controls(&mut self, world: &World, controls: Controls) {
+        self.controls = controls;
+        self.refresh(world);
+    }
+
+    /// Start listening to the clicks and drags on the board.
+    pub fn on_start(&mut self, world: &mut World) {
+        world.register::<Draggable>();
+        world.register::<DropZone>();
+        world.register::<Dragged>();
+        world.insert(ActionQueue::default());
+        world.insert(TileOptions::default());
+        self.tile_events = Some(
+            world
+                .write_resource::<EventChannel<TileEvent>>()
+                .register_reader()
+        );
+        self.drag_events = Some(
+            world
+                .write_resource::<EventChannel<DragEvent>>()
+                .register_reader()
+        );
+    }
+
+    /// Stop highlighting tiles.
+    pub fn on_stop(&mut self, world: &World) {
+        self.selection = None;
+        self.hint = None;
+        world.write_resource::<TileOptions>().clear();
+    }
+
+    /// Ignore the clicks and drags that happened while the board was covered.
+    pub fn skip_events(&mut self, world: &World) {
+        self.tile_events(world);
+        self.drag_events(world);
+    }
+
+    /// Offer the selected action again after the snapshot has changed.
+    pub fn refresh(&mut self, world: &World) {
+        self.cursor = 0;
+        self.offer_options(world);
+    }
+
+    /// Take the keys that choose actions.
+    ///
+    /// # Returns
+    /// Whether what [describe](Self::describe) says has changed.
+    pub fn handle_event(&mut self, world: &World, event: &StateEvent) -> bool {
+        let event = match event {
+            StateEvent::Window(event) => event,
+            _ => return false
+        };
+
+        let mut changed = false;
+        let keys = [
+            (VirtualKeyCode::M, TileOption::Move),
+            (VirtualKeyCode::S, TileOption::SpecialMove),
+            (VirtualKeyCode::D, TileOption::Drain),
+            (VirtualKeyCode::N, TileOption::Navigate)
+        ];
+        for &(key, option) in keys.iter() {
+            if is_key_down(event, key) {
+                self.select(option, world);
+                changed = true;
+            }
+        }
+        if is_key_down(event, VirtualKeyCode::Tab) && self.selection.is_some() {
+            self.cursor += 1;
+            changed = true;
+        }
+        if is_key_down(event, VirtualKeyCode::Return) {
+            if let Some(pos) = self.chosen_tile(world) {
+                self.choose_tile(world, pos);
+            }
+        }
+        if is_key_down(event, VirtualKeyCode::Space) {
+            let legal = self.legal_actions(world);
+            let next = [
+                GameAction::EndActions,
+                GameAction::DrawTreasureCard,
+                GameAction::DrawFloodCard
+            ]
+            .iter()
+            .find(|action| legal.contains(action));
+            if let Some(action) = next {
+                world.write_resource::<ActionQueue>().push(action.clone());
+            }
+        }
+
+        changed
+    }
+
+    /// Take the clicks on the tiles and the characters and cards that have
+    /// been dragged since the last time.
+    ///
+    /// # Returns
+    /// Whether what [describe](Self::describe) says has changed.
+    pub fn update(&mut self, world: &World, pieces: &Pieces) -> bool {
+        let mut changed = false;
+
+        // Pressing on a character or card picks it up, so the same press does
+        // not also click the tile below.
+        let drag_events = self.drag_events(world);
+        let picked_up = drag_events
+            .iter()
+            .any(|event| matches!(event, DragEvent::Started(_)));
+
+        for event in self.tile_events(world) {
+            match event {
+                TileEvent::Clicked(_) if picked_up => {}
+                TileEvent::Clicked(pos) => self.choose_tile(world, pos),
+                TileEvent::Hovered(Some(pos)) => {
+                    let positions = world.read_resource::<TileOptions>().positions();
+                    if let Some(index) = positions.iter().position(|&p| p == pos) {
+                        self.cursor = index;
+                        changed = true;
+                    }
+                }
+                TileEvent::Hovered(None) => {}
+            }
+        }
+        for event in drag_events {
+            match event {
+                DragEvent::Started(entity) => self.start_drag(world, pieces, entity),
+                DragEvent::Dropped {
+                    entity,
+                    zone,
+                    point
+                } => self.drop_dragged(world, pieces, entity, zone, point)
+            }
+            changed = true;
+        }
+
+        changed
+    }
+
+    /// Highlight the tiles the hint is about, or remove the highlights if there
+    /// is nothing to decide.
+    pub fn show_hint(&mut self, world: &World, hint: Option<&Hint>, view: &GameView) {
+        self.selection = None;
+        let mut options = world.write_resource::<TileOptions>();
+        match hint {
+            Some(hint) => options.show_hint(hint, view),
+            None => options.clear()
+        }
+        self.hint = hint.map(|hint| hint.reason.clone());
+    }
+
+    /// What is highlighted and how to choose a tile, one line each, for the
+    /// indicator of the state.
+    pub fn describe(&self, world: &World) -> String {
+        let mut text = String::new();
+        if let Some(option) = self.selection {
+            let tile = world.try_fetch::<GameSnapshot>().and_then(|snapshot| {
+                self.chosen_tile(world)
+                    .and_then(|pos| snapshot.map.get(pos).copied().flatten())
+                    .map(|tile| tile.info().name())
+            });
+            text.push_str(&format!(
+                "\n{:?}: [Tab] Next tile  [Enter] {}",
+                option,
+                tile.unwrap_or("")
+            ));
+        }
+        if let Some(hint) = &self.hint {
+            text.push_str(&format!("\nHint: {}", hint));
+        }
+        text
+    }
+
+    // The legal actions of the snapshot the controls allow to choose.
+    fn legal_actions(&self, world: &World) -> Vec<GameAction> {
+        let snapshot = match world.try_fetch::<GameSnapshot>() {
+            Some(snapshot) => snapshot,
+            None => return Vec::new()
+        };
+        match self.controls {
+            Controls::Everyone => snapshot.legal_actions.clone(),
+            Controls::Seat(seat) => snapshot.legal_actions_of(seat),
+            Controls::Nobody => Vec::new()
+        }
+    }
+
+    // Highlight the tiles the selected action can be taken on, or remove the
+    // highlights if it can not be taken anywhere.
+    fn offer_options(&mut self, world: &World) {
+        self.hint = None;
+        let option = match self.selection {
+            Some(option) => option,
+            None => {
+                world.write_resource::<TileOptions>().clear();
+                return;
+            }
+        };
+
+        let navigated = self.navigated;
+        let legal: Vec<GameAction> = self
+            .legal_actions(world)
+            .into_iter()
+            .filter(|action| match action {
+                GameAction::Navigate { player, .. } => *player == navigated,
+                _ => true
+            })
+            .collect();
+        let mut options = world.write_resource::<TileOptions>();
+        options.offer(option, &legal);
+        if options.is_empty() {
+            self.selection = None;
+        }
+    }
+
+    // Highlight the tiles for the option, or stop highlighting them if they
+    // already are. Navigating goes through the other adventurers first.
+    fn select(&mut self, option: TileOption, world: &World) {
+        let (players, active) = world
+            .try_fetch::<GameSnapshot>()
+            .map_or((0, 0), |snapshot| {
+                (snapshot.characters.len(), snapshot.active)
+            });
+        let next_navigated = |from: usize| (from..players).find(|&player| player != active);
+
+        match (self.selection, option) {
+            (Some(TileOption::Navigate), TileOption::Navigate) => {
+                match next_navigated(self.navigated + 1) {
+                    Some(player) => self.navigated = player,
+                    None => self.selection = None
+                }
+            }
+            (Some(selected), _) if selected == option => self.selection = None,
+            (_, TileOption::Navigate) => {
+                self.selection = Some(option);
+                self.navigated = next_navigated(0).unwrap_or(0);
+            }
+            _ => self.selection = Some(option)
+        }
+
+        self.refresh(world);
+    }
+
+    // The highlighted tile chosen with the keyboard.
+    fn chosen_tile(&self, world: &World) -> Option<FieldPos> {
+        let positions = world.read_resource::<TileOptions>().positions();
+        positions.get(self.cursor % positions.len().max(1)).copied()
+    }
+
+    // Take the action of a highlighted tile, if there is one at the position.
+    fn choose_tile(&self, world: &World, pos: FieldPos) {
+        let action = world.read_resource::<TileOptions>().action(pos).cloned();
+        if let Some(action) = action {
+            world.write_resource::<ActionQueue>().push(action);
+        }
+    }
+
+    // The clicks and hovers on the board since the last time.
+    fn tile_events(&mut self, world: &World) -> Vec<TileEvent> {
+        match &mut self.tile_events {
+            Some(reader) => world
+                .read_resource::<EventChannel<TileEvent>>()
+                .read(reader)
+                .copied()
+                .collect(),
+            None => Vec::new()
+        }
+    }
+
+    // The characters and cards picked up and dropped since the last time.
+    fn drag_events(&mut self, world: &World) -> Vec<DragEvent> {
+        match &mut self.drag_events {
+            Some(reader) => world
+                .read_resource::<EventChannel<DragEvent>>()
+                .read(reader)
+                .copied()
+                .collect(),
+            None => Vec::new()
+        }
+    }
+
+    // Highlight the tiles a character that has been picked up can be dropped
+    // onto. The active adventurer moves, the others are navigated if the
+    // active one can do that.
+    fn start_drag(&mut self, world: &World, pieces: &Pieces, entity: Entity) {
+        let player = match pieces
+            .characters
+            .iter()
+            .position(|&character| character == entity)
+        {
+            Some(player) => player,
+            None => return
+        };
+
+        let active = world
+            .try_fetch::<GameSnapshot>()
+            .map(|snapshot| snapshot.active);
+        let navigable = self.legal_actions(world).iter().any(|action| match action {
+            GameAction::Navigate {
+                player: navigated, ..
+            } => *navigated == player,
+            _ => false
+        });
+        self.selection = if active == Some(player) {
+            Some(TileOption::Move)
+        }
+        else if navigable {
+            self.navigated = player;
+            Some(TileOption::Navigate)
+        }
+        else {
+            None
+        };
+        self.refresh(world);
+    }
+
+    // Take the action the drop stands for, if it is allowed. A character moves
+    // to the highlighted tile it has been dropped onto. A card is discarded on
+    // the discard zone, or given away on the hand of another player.
+    fn drop_dragged(
+        &mut self,
+        world: &World,
+        pieces: &Pieces,
+        entity: Entity,
+        zone: Option<Entity>,
+        point: Vec2<f32>
+    ) {
+        if pieces.characters.contains(&entity) {
+            let pos = calculate_field_pos(world.read_resource::<MapFull>().transform(), point);
+            if let Some(pos) = pos {
+                self.choose_tile(world, pos);
+            }
+            self.selection = None;
+            self.offer_options(world);
+            return;
+        }
+
+        let (player, card) = match pieces.cards.iter().find(|(card, ..)| *card == entity) {
+            Some(&(_, player, card)) => (player, card),
+            None => return
+        };
+        let action = if zone.is_some() && zone == pieces.discard_zone {
+            GameAction::Discard { player, card }
+        }
+        else {
+            let to =
+                zone.and_then(|zone| pieces.hand_panels.iter().position(|&panel| panel == zone));
+            let active = world
+                .try_fetch::<GameSnapshot>()
+                .map(|snapshot| snapshot.active);
+            match to {
+                // Cards can only be given away by the active player.
+                Some(to) if active == Some(player) => GameAction::GiveCard { to, card },
+                _ => return
+            }
+        };
+        if self.legal_actions(world).contains(&action) {
+            world.write_resource::<ActionQueue>().push(action);
+        }
+    }
+}
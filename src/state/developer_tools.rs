@@ -0,0 +1,137 @@
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::Anchor,
+    winit::{Event, WindowEvent}
+};
+
+use super::game::{GAME_HEIGHT, GAME_WIDTH};
+use crate::game_state::{DebugCommand, GameState, DEBUG_COMMANDS};
+use crate::ui;
+use std::fs;
+
+/// The number of earlier lines shown above the input.
+const SHOWN_LINES: usize = 8;
+
+/// The state representing the developer console
+///
+/// It is opened over a game with `F12` once the developer tools have been
+/// enabled in the settings. Every line typed is a [DebugCommand], which changes
+/// the game right away, or `dump`, which writes the whole game state as JSON
+/// into the working directory. `Escape` or `F12` close the console again.
+#[derive(Default)]
+pub struct DeveloperTools {
+    input:   String,
+    /// The commands entered so far, each with its result.
+    history: Vec<String>,
+    text:    Option<Entity>
+}
+
+impl DeveloperTools {
+    fn describe(&self) -> String {
+        let mut text = format!("Developer tools\n{}\n\n", DEBUG_COMMANDS.join("\n"));
+        text.push_str("dump\n\n");
+
+        let skipped = self.history.len().saturating_sub(SHOWN_LINES);
+        for line in &self.history[skipped..] {
+            text.push_str(line);
+            text.push('\n');
+        }
+
+        text.push_str(&format!("> {}_", self.input));
+        text
+    }
+
+    fn refresh(&self, world: &World) {
+        if let Some(text) = self.text {
+            ui::set_text(world, text, self.describe());
+        }
+    }
+
+    // Carry out the line that has been typed and remember what happened.
+    fn execute(&mut self, world: &mut World) {
+        let line = self.input.trim().to_string();
+        self.input.clear();
+        if line.is_empty() {
+            return;
+        }
+
+        let result = if line == "dump" {
+            dump(&world.read_resource::<GameState>())
+        }
+        else {
+            line.parse::<DebugCommand>().and_then(|command| {
+                world
+                    .write_resource::<GameState>()
+                    .apply_debug(command)
+                    .map(|()| "Done".to_string())
+            })
+        };
+
+        let result = result.unwrap_or_else(|err| err);
+        self.history.push(format!("> {}\n  {}", line, result));
+    }
+}
+
+impl SimpleState for DeveloperTools {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.text = Some(ui::create_label(
+            data.world,
+            "developer_tools",
+            Anchor::BottomLeft,
+            (ui::MARGIN, ui::MARGIN),
+            (GAME_WIDTH * 0.5, GAME_HEIGHT * 0.6),
+            self.describe()
+        ));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(text) = self.text.take() {
+            let _ = data.world.delete_entity(text);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) || is_key_down(event, VirtualKeyCode::F12)
+            {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                self.execute(data.world);
+            }
+            if is_key_down(event, VirtualKeyCode::Back) {
+                self.input.pop();
+            }
+            if let Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } = event
+            {
+                if !c.is_control() {
+                    self.input.push(*c);
+                }
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+}
+
+// Write the state into a new file in the working directory.
+fn dump(state: &GameState) -> Result<String, String> {
+    let path = format!(
+        "game_state_{}.json",
+        chrono::Local::now().format("%Y%m%d_%H%M%S")
+    );
+    let json = serde_json::to_string_pretty(&state.dump()).map_err(|err| err.to_string())?;
+    fs::write(&path, json).map_err(|err| format!("Unable to write {}: {}", path, err))?;
+
+    Ok(format!("Written to {}", path))
+}
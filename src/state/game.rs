@@ -1,17 +1,276 @@
-use amethyst::{core::Transform, prelude::*, renderer::Camera};
+use amethyst::{
+    assets::Handle,
+    core::{Hidden, Transform},
+    ecs::{Entity, Join},
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    renderer::{Camera, SpriteSheet},
+    ui::Anchor
+};
 
+use super::board::{indicator_text, BoardInput, Controls, Pieces};
+use super::{DeveloperTools, HighScores, TurnHandoff};
 use crate::adventurer::AdventurerType;
 use crate::asset;
+use crate::bot;
 use crate::character::Character;
-use crate::map::{FieldPosComp, Full as MapFull, IslandTile, IslandTileInfo};
-use crate::math::Vec2;
-use std::mem;
+use crate::difficulty::Difficulty;
+use crate::game_state::{GameAction, GameSnapshot, GameState, GameView};
+use crate::high_score::{self, HighScore, HighScoreTable};
+use crate::map::{full::calculate_field_translation, FieldPosComp, Full as MapFull};
+use crate::positionable::Positionable;
+use crate::scenario::{Objective, ObjectiveStatus};
+use crate::settings::GameSettings;
+use crate::ui;
 
 pub const GAME_WIDTH: f32 = 1920.;
 pub const GAME_HEIGHT: f32 = 1080.;
 
+/// The least number of players sharing the machine in a local game.
+pub const MIN_LOCAL_PLAYERS: usize = 2;
+/// The most players sharing the machine in a local game.
+pub const MAX_LOCAL_PLAYERS: usize = 4;
+
+/// Actions the local player has chosen in the user interface. The state of the
+/// game takes them out every frame and either applies them or sends them to
+/// the server.
+#[derive(Default)]
+pub struct ActionQueue(Vec<GameAction>);
+
+impl ActionQueue {
+    pub fn push(&mut self, action: GameAction) { self.0.push(action); }
+
+    /// Take all actions out of the queue, in the order they have been chosen.
+    pub fn take(&mut self) -> Vec<GameAction> { self.0.drain(..).collect() }
+}
+
 /// The state the game is in while a game is being played
-pub struct Game;
+///
+/// The players share the machine and take turns at it. Whoever has to decide
+/// next is shown at the top of the screen, next to the hands of all players.
+/// Whenever someone else has to decide, the [TurnHandoff] screen asks the
+/// players to pass the machine on. `M`, `S`, `D` and `N` highlight the tiles
+/// the active adventurer can move, move specially, drain or navigate another
+/// adventurer to. Pressing `N` again navigates the next adventurer, and
+/// pressing the key of the highlighted action again removes the highlights.
+/// Clicking a highlighted tile takes the action there. With the keyboard, `Tab`
+/// goes to the next highlighted tile and `Enter` takes the action on it.
+/// Characters can also be dragged onto the tiles they can move or be navigated
+/// to, and cards onto the hand of another player to give them away or onto the
+/// discard zone to discard them.
+/// `Space` ends the actions of the turn or draws the next card, and `Escape`
+/// leaves the game. Unless the confirmations are turned off in the settings, it
+/// has to be pressed twice for that while the game is running. Once the game is
+/// over, `Enter` shows the high scores. If the developer tools are enabled in
+/// the settings, `F12` opens them. A game that has been changed with them is
+/// not recorded in the high scores. Games started from a scenario show its
+/// objective and end as soon as it has been achieved or has failed.
+pub struct Game {
+    state: Option<GameState>,
+    adventurers: Vec<AdventurerType>,
+    record: Option<Record>,
+    goal: Option<Goal>,
+    deciding: usize,
+    sprite_sheet: Option<Handle<SpriteSheet>>,
+    pieces: Pieces,
+    input: BoardInput,
+    indicator: Option<Entity>,
+    /// Escape has been pressed once and has to be pressed again to leave.
+    leaving: bool
+}
+
+impl Game {
+    /// Create the screen for a game that has been set up, for example in the
+    /// [GamePreparations](super::GamePreparations).
+    ///
+    /// # Panics
+    /// If the adventurers of the game can not play together at one machine,
+    /// see [check_local_players].
+    pub fn new(state: GameState) -> Self {
+        let game = Self::create(state);
+        if let Err(err) = check_local_players(&game.adventurers) {
+            panic!("{}", err);
+        }
+
+        game
+    }
+
+    /// Create the screen for a game that has been started from a scenario,
+    /// see [Scenario::start](crate::scenario::Scenario::start). Scenarios may
+    /// be played alone, and they are not recorded in the high scores.
+    ///
+    /// # Parameters
+    /// `name` - The name of the scenario
+    /// `objective` - What the players have to achieve. The game ends once it
+    /// has been achieved or can not be achieved anymore.
+    pub fn scenario(state: GameState, name: String, objective: Objective) -> Self {
+        Self {
+            goal: Some(Goal {
+                name,
+                objective,
+                status: ObjectiveStatus::Open
+            }),
+            ..Self::create(state)
+        }
+    }
+
+    fn create(state: GameState) -> Self {
+        let adventurers = state
+            .characters()
+            .iter()
+            .map(|character| character.adventurer_type())
+            .collect();
+
+        Self {
+            state: Some(state),
+            adventurers,
+            record: None,
+            goal: None,
+            deciding: 0,
+            sprite_sheet: None,
+            pieces: Pieces::default(),
+            input: BoardInput::new(Controls::Everyone),
+            indicator: None,
+            leaving: false
+        }
+    }
+
+    /// Record the game in the high scores once it is over.
+    ///
+    /// # Parameters
+    /// `players` - The names of the players, in the order of their turns
+    /// `difficulty` - The difficulty the game has been set up with
+    /// `map` - What the map is called, for instance `classic`
+    pub fn recorded_as(
+        mut self,
+        players: Vec<String>,
+        difficulty: Difficulty,
+        map: String
+    ) -> Self {
+        self.record = Some(Record {
+            players,
+            difficulty,
+            map
+        });
+        self
+    }
+
+    // Add the finished game to the high score table of the user.
+    fn record_score(&mut self, state: &GameState) {
+        let record = match self.record.take() {
+            Some(record) => record,
+            None => return
+        };
+        let score = match HighScore::new(
+            state,
+            record.players,
+            record.difficulty,
+            record.map,
+            chrono::Local::now().timestamp()
+        ) {
+            Some(score) => score,
+            None => return
+        };
+        let path = match high_score::default_path() {
+            Some(path) => path,
+            None => {
+                eprintln!("There is no data directory to keep the high scores in");
+                return;
+            }
+        };
+
+        let result = HighScoreTable::load(&path).and_then(|mut table| {
+            table.record(score);
+            table.save(&path)
+        });
+        if let Err(err) = result {
+            eprintln!("Unable to record the game in {}: {}", path.display(), err);
+        }
+    }
+
+    // Show the board of the game and the hands of the players.
+    fn show(&mut self, world: &mut World) {
+        let snapshot = world.read_resource::<GameState>().snapshot();
+        let sprite_sheet = self.sprite_sheet.clone().unwrap();
+        self.pieces.show(world, &snapshot, &sprite_sheet);
+        world.insert(snapshot);
+
+        self.input.refresh(world);
+        self.show_indicator(world);
+    }
+
+    // Show who has to decide, or how the game has ended.
+    fn show_indicator(&self, world: &World) {
+        let snapshot = world.read_resource::<GameSnapshot>();
+        if let Some(indicator) = self.indicator {
+            let mut text = indicator_text(&snapshot);
+            match &self.goal {
+                Some(goal) => {
+                    text.push_str(&format!("\n{}: {}", goal.name, goal.objective));
+                    match goal.status {
+                        ObjectiveStatus::Open => {}
+                        ObjectiveStatus::Achieved => {
+                            text.push_str("\nScenario solved!  [Esc] Leave")
+                        }
+                        ObjectiveStatus::Failed => text.push_str("\nScenario failed.  [Esc] Leave")
+                    }
+                }
+                None if snapshot.outcome.is_some() => text.push_str("\n[Enter] High scores"),
+                None => {}
+            }
+            text.push_str(&self.input.describe(world));
+            if self.leaving {
+                text.push_str("\nPress [Esc] again to leave the game");
+            }
+            ui::set_text(world, indicator, text);
+        }
+    }
+
+    // Ask the heuristic bot what the deciding player should do and highlight
+    // the tiles it is about.
+    fn show_hint(&mut self, world: &World) {
+        {
+            let state = world.read_resource::<GameState>();
+            let view = GameView::new(&state, state.deciding_player());
+            self.input
+                .show_hint(world, bot::hint(&view).as_ref(), &view);
+        }
+        self.show_indicator(world);
+    }
+
+    // Check whether the objective of the scenario has been decided. The
+    // players can not take any more actions once it has.
+    fn check_goal(&mut self, world: &World) {
+        let status = match &mut self.goal {
+            Some(goal) => {
+                goal.status = goal.objective.status(&world.read_resource::<GameState>());
+                goal.status
+            }
+            None => return
+        };
+        let controls = match status {
+            ObjectiveStatus::Open => Controls::Everyone,
+            _ => Controls::Nobody
+        };
+        self.input.set_controls(world, controls);
+    }
+
+    // Whether the game or the scenario is over.
+    fn is_over(&self, world: &World) -> bool {
+        world.read_resource::<GameState>().is_over()
+            || self
+                .goal
+                .as_ref()
+                .map_or(false, |goal| goal.status != ObjectiveStatus::Open)
+    }
+
+    // The entities that make up the screen of the game.
+    fn entities(&self) -> Vec<Entity> {
+        let mut entities = self.pieces.entities();
+        entities.extend(self.indicator);
+        entities
+    }
+}
 
 impl SimpleState for Game {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
@@ -19,27 +278,179 @@ impl SimpleState for Game {
 
         world.register::<FieldPosComp>();
         world.register::<Character>();
+        world.register::<Hidden>();
+        self.input.on_start(world);
+
+        self.sprite_sheet = Some(asset::load_sprite_sheet(
+            "characters.png",
+            "characters.ron",
+            world
+        ));
 
-        let character_sprite_sheet =
-            asset::load_sprite_sheet("characters.png", "characters.ron", world);
+        let state = self
+            .state
+            .take()
+            .expect("The game can only be started once");
+        self.deciding = state.deciding_player();
+        world.insert(state);
 
-        let mut map = MapFull::new(Vec2::from_values(6, 4), None);
-        map.transform_mut().set_translation_xyz(64., 64., -1.);
-        for y in 0..4 {
-            for x in 0..6 {
-                let tile_info: IslandTileInfo = unsafe { mem::transmute(y * 6 + x) };
-                map.set(Vec2::from_values(x, y), Some(IslandTile::new(tile_info)));
+        self.indicator = Some(ui::create_label(
+            world,
+            "active_player",
+            Anchor::TopMiddle,
+            (0., -ui::MARGIN),
+            (GAME_WIDTH * 0.5, 120.),
+            String::new()
+        ));
+        self.check_goal(world);
+        self.show(world);
+        init_camera(world);
+    }
+
+    // The developer tools may have changed anything about the game. Clicks and
+    // drags while it was covered are ignored.
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.input.skip_events(data.world);
+        self.deciding = data.world.read_resource::<GameState>().deciding_player();
+        self.check_goal(data.world);
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.pieces.delete(data.world);
+        if let Some(indicator) = self.indicator.take() {
+            let _ = data.world.delete_entity(indicator);
+        }
+        self.input.on_stop(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                let confirm = data.world.read_resource::<GameSettings>().confirmations
+                    && !self.is_over(data.world);
+                if self.leaving || !confirm {
+                    return Trans::Pop;
+                }
+                self.leaving = true;
+                self.show_indicator(data.world);
+                return Trans::None;
+            }
+            if is_key_down(event, VirtualKeyCode::F12)
+                && data.world.read_resource::<GameSettings>().developer_tools
+            {
+                self.record = None;
+                return Trans::Push(Box::new(DeveloperTools::default()));
+            }
+            if is_key_down(event, VirtualKeyCode::Return)
+                && self.goal.is_none()
+                && data.world.read_resource::<GameState>().is_over()
+            {
+                return Trans::Switch(Box::new(HighScores::default()));
+            }
+            if is_key_down(event, VirtualKeyCode::H) {
+                self.show_hint(data.world);
             }
         }
-        map.create_tile_entities(world);
+        if self.input.handle_event(data.world, &event) {
+            self.show_indicator(data.world);
+        }
 
-        let test_courier = Character::new(AdventurerType::Courier);
-        test_courier.spawn_entity(&map, world, character_sprite_sheet);
-        init_camera(world);
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let world = &mut data.world;
+
+        if self.input.update(world, &self.pieces) {
+            self.show_indicator(world);
+        }
+
+        let actions = world.write_resource::<ActionQueue>().take();
+        if actions.is_empty() {
+            return Trans::None;
+        }
+
+        let deciding = {
+            let mut state = world.write_resource::<GameState>();
+            for action in actions {
+                if let Err(err) = state.apply(&action) {
+                    eprintln!("Unable to {:?}: {}", action, err);
+                }
+            }
+            state.deciding_player()
+        };
+        self.check_goal(world);
+        let over = self.is_over(world);
+        if over {
+            self.record_score(&world.read_resource::<GameState>());
+        }
+        // Playing on means the players want to stay.
+        self.leaving = false;
+        self.show(world);
+
+        if deciding != self.deciding && !over {
+            self.deciding = deciding;
+            let adventurer = self.adventurers[deciding];
+            return Trans::Push(Box::new(TurnHandoff::new(
+                deciding,
+                adventurer,
+                self.entities()
+            )));
+        }
+
+        Trans::None
+    }
+}
+
+/// The objective of the scenario a game has been started from.
+struct Goal {
+    name:      String,
+    objective: Objective,
+    status:    ObjectiveStatus
+}
+
+/// What a game is recorded as in the high scores.
+struct Record {
+    players: Vec<String>,
+    difficulty: Difficulty,
+    map: String
+}
+
+/// Checks if the adventurers can play a local game together. There have to be
+/// [MIN_LOCAL_PLAYERS] to [MAX_LOCAL_PLAYERS] of them and each one can only be
+/// played once.
+///
+/// # Returns
+/// Why they can not play together, if they can't.
+pub fn check_local_players(adventurers: &[AdventurerType]) -> Result<(), String> {
+    if adventurers.len() < MIN_LOCAL_PLAYERS || adventurers.len() > MAX_LOCAL_PLAYERS {
+        return Err(format!(
+            "A local game needs {} to {} players",
+            MIN_LOCAL_PLAYERS, MAX_LOCAL_PLAYERS
+        ));
+    }
+
+    match adventurers
+        .iter()
+        .enumerate()
+        .find(|(i, adventurer)| adventurers[..*i].contains(adventurer))
+    {
+        Some((_, adventurer)) => Err(format!("The {:?} can only be played once", adventurer)),
+        None => Ok(())
     }
 }
 
-fn init_camera(world: &mut World) {
+pub(crate) fn init_camera(world: &mut World) {
+    // The camera stays when a state ends, so the next one can use it as well.
+    if (&world.read_storage::<Camera>()).join().next().is_some() {
+        return;
+    }
+
     let mut transform = Transform::default();
     transform.set_translation_xyz(GAME_WIDTH * 0.5, GAME_HEIGHT * 0.5, 1.0);
 
@@ -49,3 +460,46 @@ fn init_camera(world: &mut World) {
         .with(transform)
         .build();
 }
+
+/// Spawn the characters and place them where they are standing in the game
+/// right away, so they don't walk there from their spawn tiles.
+///
+/// # Returns
+/// The entities that have been created, so they can be removed again.
+pub(crate) fn spawn_characters(
+    characters: &[Character],
+    map: &MapFull,
+    world: &mut World,
+    sprite_sheet: Handle<SpriteSheet>
+) -> Vec<Entity> {
+    characters
+        .iter()
+        .map(|character| {
+            let entity = character
+                .clone()
+                .spawn_entity(map, world, sprite_sheet.clone());
+            if let Some(spawned) = world.write_storage::<Character>().get_mut(entity) {
+                spawned.set_pos(character.pos());
+            }
+            if let Some(transform) = world.write_storage::<Transform>().get_mut(entity) {
+                let translation = calculate_field_translation(map.transform(), character.pos());
+                transform.set_translation_xyz(translation.x, translation.y, 0.);
+            }
+            entity
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn local_players() {
+        assert!(check_local_players(&[AdventurerType::Pilot]).is_err());
+        assert!(check_local_players(&[AdventurerType::Pilot, AdventurerType::Diver]).is_ok());
+        assert!(check_local_players(&[AdventurerType::Pilot, AdventurerType::Pilot]).is_err());
+        assert!(check_local_players(&AdventurerType::ALL[..4]).is_ok());
+        assert!(check_local_players(&AdventurerType::ALL[..5]).is_err());
+    }
+}
@@ -1,9 +1,473 @@
-use amethyst::prelude::*;
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*
+};
 
-/// The state representing the game preparations settings screen, that is opened before starting a game.
-/// On this screen, a map and players can be selected
-pub struct GamePreparations {}
+use super::{Game, RemoteGame, MIN_LOCAL_PLAYERS};
+use crate::adventurer::AdventurerType;
+use crate::difficulty::Difficulty;
+use crate::map::{loader, Full as MapFull, MapChoice};
+use crate::net::{Client, ClientEvent, ClientMessage, Lobby, ServerMessage};
+use crate::ui;
+use rand::thread_rng;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+/// The keys to choose the adventurers with, in the order of
+/// [AdventurerType::ALL].
+const ADVENTURER_KEYS: [VirtualKeyCode; 6] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6
+];
+
+/// The state representing the game preparations settings screen, that is opened
+/// before starting a game. On this screen, a map and players can be selected
+///
+/// Every player chooses an adventurer with the number keys or `0` for a random
+/// one. `D` changes the difficulty and `M` switches between the classic island,
+/// a random one and the one from the map file, if there is one.
+///
+/// For a game at this machine, the arrow keys select the player to choose for,
+/// `A` adds a player and `Delete` removes the selected one. `Enter` starts the
+/// game. In the lobby of a server, `Enter` tells the others that the player is
+/// ready instead, and the game starts once everyone is ready.
+pub struct GamePreparations {
+    /// The server whose lobby is joined, or `None` for a game at this machine.
+    address: Option<String>,
+    name: String,
+    map_file: Option<PathBuf>,
+    custom_map: Option<MapFull>,
+    client: Option<Client>,
+    /// The seat of the player in the lobby of a server, or the player that is
+    /// selected in a game at this machine.
+    seat: Option<usize>,
+    lobby: Option<Lobby>,
+    status: String,
+    text: Option<Entity>
+}
+
+impl GamePreparations {
+    /// Prepare a game for two to four players sharing this machine. It starts
+    /// with two players on the classic island.
+    pub fn local() -> Self {
+        let mut lobby = Lobby::new(Difficulty::Normal, MapChoice::Classic);
+        for seat in 0..MIN_LOCAL_PLAYERS {
+            lobby.join(seat, local_name(seat));
+        }
+
+        Self {
+            address: None,
+            name: String::new(),
+            map_file: None,
+            custom_map: None,
+            client: None,
+            seat: Some(0),
+            lobby: Some(lobby),
+            status: String::new(),
+            text: None
+        }
+    }
+
+    /// Prepare a game in the lobby of the server at the address provided. The
+    /// connection is made once the state is started.
+    pub fn join(address: String, name: String) -> Self {
+        Self {
+            address: Some(address),
+            name,
+            map_file: None,
+            custom_map: None,
+            client: None,
+            seat: None,
+            lobby: None,
+            status: String::new(),
+            text: None
+        }
+    }
+
+    /// Offer the map of the file as an alternative to the classic and a random
+    /// island.
+    pub fn with_map_file(mut self, path: PathBuf) -> Self {
+        match loader::load_map(&path) {
+            Ok(map) => {
+                self.custom_map = Some(map);
+                self.map_file = Some(path);
+            }
+            Err(err) => self.status = format!("Unable to load {}: {}", path.display(), err)
+        }
+        self
+    }
+
+    fn is_local(&self) -> bool { self.address.is_none() }
+
+    // The message for the server the key stands for, if any. In a game at this
+    // machine, the message is applied to the lobby right away instead.
+    fn choice(&mut self, key: VirtualKeyCode) -> Option<ClientMessage> {
+        let lobby = self.lobby.as_ref()?;
+        let own = self.seat.and_then(|seat| lobby.seats.get(seat)?.as_ref());
+
+        if let Some(i) = ADVENTURER_KEYS.iter().position(|&k| k == key) {
+            return Some(ClientMessage::ChooseAdventurer(Some(
+                AdventurerType::ALL[i]
+            )));
+        }
+
+        match key {
+            VirtualKeyCode::Key0 => Some(ClientMessage::ChooseAdventurer(None)),
+            VirtualKeyCode::D => {
+                let current = Difficulty::ALL
+                    .iter()
+                    .position(|&d| d == lobby.difficulty)
+                    .unwrap_or(0);
+                let next = Difficulty::ALL[(current + 1) % Difficulty::ALL.len()];
+                Some(ClientMessage::ChooseDifficulty(next))
+            }
+            VirtualKeyCode::M => Some(ClientMessage::ChooseMap(
+                match (&lobby.map, &self.custom_map) {
+                    (MapChoice::Classic, _) => MapChoice::Random,
+                    (MapChoice::Random, Some(map)) => MapChoice::Custom(map.clone()),
+                    (MapChoice::Random, None) | (MapChoice::Custom(_), _) => MapChoice::Classic
+                }
+            )),
+            VirtualKeyCode::Return => own.map(|own| ClientMessage::Ready(!own.ready)),
+            _ => None
+        }
+    }
+
+    // Change the lobby of a game at this machine the way the server would.
+    fn arrange(&mut self, message: ClientMessage) {
+        let (lobby, seat) = match (&mut self.lobby, self.seat) {
+            (Some(lobby), Some(seat)) => (lobby, seat),
+            _ => return
+        };
+
+        let result = match message {
+            ClientMessage::ChooseAdventurer(adventurer) => {
+                lobby.choose_adventurer(seat, adventurer)
+            }
+            ClientMessage::ChooseDifficulty(difficulty) => {
+                lobby.set_difficulty(difficulty);
+                Ok(())
+            }
+            ClientMessage::ChooseMap(map) => {
+                lobby.set_map(map);
+                Ok(())
+            }
+            _ => Ok(())
+        };
+        if let Err(err) = result {
+            self.status = err.to_string();
+        }
+    }
+
+    // Select another player or change how many there are, in a game at this
+    // machine.
+    fn seat_players(&mut self, key: VirtualKeyCode) {
+        let (lobby, seat) = match (&mut self.lobby, self.seat) {
+            (Some(lobby), Some(seat)) => (lobby, seat),
+            _ => return
+        };
+        let players = lobby.players();
+
+        match key {
+            VirtualKeyCode::Up => self.seat = Some(seat.saturating_sub(1)),
+            VirtualKeyCode::Down => self.seat = Some((seat + 1).min(players - 1)),
+            VirtualKeyCode::A if players < lobby.seats.len() => {
+                lobby.join(players, local_name(players));
+                self.seat = Some(players);
+            }
+            VirtualKeyCode::A => self.status = "The table is full".to_string(),
+            VirtualKeyCode::Delete if players > MIN_LOCAL_PLAYERS => {
+                // The players stay next to each other at the table, so they
+                // are renamed after the one that left.
+                lobby.seats.remove(seat);
+                lobby.seats.push(None);
+                for (i, player) in lobby.seats.iter_mut().flatten().enumerate() {
+                    player.name = local_name(i);
+                }
+                self.seat = Some(seat.min(players - 2));
+            }
+            VirtualKeyCode::Delete => {
+                self.status = format!("At least {} players are needed", MIN_LOCAL_PLAYERS)
+            }
+            _ => {}
+        }
+    }
+
+    // Start the game at this machine with everything that has been chosen.
+    fn start_local(&mut self) -> SimpleTrans {
+        let lobby = match &self.lobby {
+            Some(lobby) => lobby,
+            None => return Trans::None
+        };
+
+        match lobby.start(&mut thread_rng()) {
+            Ok((_, state)) => {
+                let players = lobby
+                    .seats
+                    .iter()
+                    .flatten()
+                    .map(|seat| seat.name.clone())
+                    .collect();
+                let map = match (&lobby.map, &self.map_file) {
+                    (MapChoice::Classic, _) => "classic".to_string(),
+                    (MapChoice::Random, _) => "random".to_string(),
+                    (MapChoice::Custom(_), Some(path)) => map_name(path),
+                    (MapChoice::Custom(_), None) => "custom".to_string()
+                };
+                Trans::Switch(Box::new(Game::new(state).recorded_as(
+                    players,
+                    lobby.difficulty,
+                    map
+                )))
+            }
+            Err(err) => {
+                self.status = format!("The game can not start: {}", err);
+                Trans::None
+            }
+        }
+    }
+
+    // The text shown on the screen.
+    fn describe(&self) -> String {
+        let mut text = match &self.address {
+            Some(address) => format!("Lobby of {}\n{}\n\n", address, self.status),
+            None => format!("New game\n{}\n\n", self.status)
+        };
+
+        let lobby = match &self.lobby {
+            Some(lobby) => lobby,
+            None if self.client.is_none() => {
+                text.push_str("[Esc] Leave");
+                return text;
+            }
+            None => {
+                text.push_str("Waiting for the server...");
+                return text;
+            }
+        };
+
+        for (seat, taken) in lobby.seats.iter().enumerate() {
+            let selected = self.is_local() && self.seat == Some(seat);
+            let _ = write!(
+                text,
+                "{} Seat {}: ",
+                if selected { '>' } else { ' ' },
+                seat + 1
+            );
+            match taken {
+                Some(taken) => {
+                    let adventurer = taken
+                        .adventurer
+                        .map_or_else(|| "random adventurer".to_string(), |a| format!("{:?}", a));
+                    let _ = write!(text, "{} - {}", taken.name, adventurer);
+                    if !self.is_local() {
+                        text.push_str(if taken.ready {
+                            " - ready"
+                        }
+                        else {
+                            " - not ready"
+                        });
+                        if self.seat == Some(seat) {
+                            text.push_str(" (you)");
+                        }
+                    }
+                }
+                None => text.push_str("free")
+            }
+            text.push('\n');
+        }
+
+        let map = match lobby.map {
+            MapChoice::Classic => "classic",
+            MapChoice::Random => "random",
+            MapChoice::Custom(_) => "from file"
+        };
+        let _ = write!(
+            text,
+            "\nDifficulty: {:?}\nMap: {}\n\n",
+            lobby.difficulty, map
+        );
+
+        for (i, adventurer) in AdventurerType::ALL.iter().enumerate() {
+            let _ = write!(text, "[{}] {:?}  ", i + 1, adventurer);
+        }
+        text.push_str("[0] Random adventurer\n[D] Difficulty  [M] Map  ");
+        if self.is_local() {
+            text.push_str(
+                "[Up/Down] Select player  [A] Add player  [Del] Remove player  [Enter] Start  \
+                 [Esc] Back"
+            );
+        }
+        else {
+            text.push_str("[Enter] Ready  [Esc] Leave");
+        }
+
+        text
+    }
+
+    fn refresh(&self, world: &World) {
+        if let Some(text) = self.text {
+            ui::set_text(world, text, self.describe());
+        }
+    }
+
+    // Hand the connection over to the game.
+    fn start_game(&mut self, pending: Vec<ClientEvent>) -> SimpleTrans {
+        match (self.client.take(), &self.address) {
+            (Some(client), Some(address)) => Trans::Switch(Box::new(RemoteGame::joined(
+                address.clone(),
+                self.name.clone(),
+                client,
+                self.seat,
+                pending
+            ))),
+            _ => Trans::Pop
+        }
+    }
+}
 
 impl SimpleState for GamePreparations {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(address) = &self.address {
+            self.client = Some(Client::connect(address.clone(), self.name.clone()));
+        }
+        self.text = Some(ui::create_text(
+            data.world,
+            "game_preparations",
+            self.describe()
+        ));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(text) = self.text.take() {
+            let _ = data.world.delete_entity(text);
+        }
+        self.client = None;
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if self.is_local() {
+                if is_key_down(event, VirtualKeyCode::Return) {
+                    return self.start_local();
+                }
+                let key = [
+                    VirtualKeyCode::Up,
+                    VirtualKeyCode::Down,
+                    VirtualKeyCode::A,
+                    VirtualKeyCode::Delete
+                ]
+                .iter()
+                .copied()
+                .find(|&key| is_key_down(event, key));
+                if let Some(key) = key {
+                    self.status.clear();
+                    self.seat_players(key);
+                }
+            }
+
+            let key = ADVENTURER_KEYS
+                .iter()
+                .chain(&[
+                    VirtualKeyCode::Key0,
+                    VirtualKeyCode::D,
+                    VirtualKeyCode::M,
+                    VirtualKeyCode::Return
+                ])
+                .copied()
+                .find(|&key| is_key_down(event, key));
+            if let Some(message) = key.and_then(|key| self.choice(key)) {
+                if self.is_local() {
+                    self.status.clear();
+                    self.arrange(message);
+                }
+                else if let Some(Err(err)) =
+                    self.client.as_ref().map(|client| client.send(&message))
+                {
+                    self.status = format!("Unable to reach the server: {}", err);
+                }
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let mut events = match &self.client {
+            Some(client) => client.poll().into_iter(),
+            // The refusal stays on the screen until the player leaves.
+            None => return Trans::None
+        };
+        if events.len() == 0 {
+            return Trans::None;
+        }
+
+        while let Some(event) = events.next() {
+            match event {
+                ClientEvent::Message(ServerMessage::Welcome { seat, .. }) => {
+                    self.seat = Some(seat);
+                    self.status = format!("Joined as {}", self.name);
+                }
+                ClientEvent::Message(ServerMessage::Refused(refusal)) => {
+                    self.client = None;
+                    self.lobby = None;
+                    self.status = format!("The server refused to let us in: {:?}", refusal);
+                    break;
+                }
+                ClientEvent::Message(ServerMessage::Lobby(lobby)) => self.lobby = Some(lobby),
+                ClientEvent::Message(ServerMessage::Rejected { reason }) => self.status = reason,
+                ClientEvent::Message(ServerMessage::PlayerJoined { name, .. }) => {
+                    self.status = format!("{} has joined", name)
+                }
+                ClientEvent::Message(ServerMessage::PlayerLeft { seat }) => {
+                    self.status = format!("The player of seat {} has left", seat + 1)
+                }
+                ClientEvent::Message(ServerMessage::GameStarted { seat }) => {
+                    self.seat = Some(seat);
+                    return self.start_game(events.collect());
+                }
+                // The server hosts a game that has been set up beforehand.
+                ClientEvent::Message(ServerMessage::Snapshot(snapshot)) => {
+                    let mut pending = vec![ClientEvent::Message(ServerMessage::Snapshot(snapshot))];
+                    pending.extend(events);
+                    return self.start_game(pending);
+                }
+                ClientEvent::Message(ServerMessage::Applied { .. })
+                | ClientEvent::Message(ServerMessage::Spectating { .. }) => {}
+                ClientEvent::Disconnected => {
+                    self.status = format!(
+                        "Lost the connection to {}, reconnecting",
+                        self.address.as_deref().unwrap_or_default()
+                    )
+                }
+            }
+        }
+
+        self.refresh(data.world);
+        Trans::None
+    }
+}
+
+// The name of a player at this machine, counted from one.
+fn local_name(seat: usize) -> String { format!("Player {}", seat + 1) }
+
+/// What a map from a file is called in the high scores.
+pub fn map_name(path: &Path) -> String {
+    path.file_stem().map_or_else(
+        || path.display().to_string(),
+        |stem| stem.to_string_lossy().into_owned()
+    )
 }
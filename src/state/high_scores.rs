@@ -1,8 +1,147 @@
-use amethyst::prelude::*;
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*
+};
+
+use crate::difficulty::Difficulty;
+use crate::game_state::Outcome;
+use crate::high_score::{self, HighScore, HighScoreTable, SortBy};
+use crate::ui;
+use chrono::{Local, TimeZone};
+use std::fmt::Write;
+
+/// The number of games that fit on the screen.
+const SHOWN_SCORES: usize = 15;
 
 /// The state representing the High Scores window
-pub struct HighScores {}
+///
+/// The finished games are listed best first. `S` sorts them by another column
+/// and `F` shows only the games of one difficulty.
+#[derive(Default)]
+pub struct HighScores {
+    table:   HighScoreTable,
+    sort_by: usize,
+    /// The index of the difficulty in [Difficulty::ALL] plus one, or zero to
+    /// show all games.
+    filter:  usize,
+    status:  String,
+    text:    Option<Entity>
+}
+
+impl HighScores {
+    fn load(&mut self) {
+        let path = match high_score::default_path() {
+            Some(path) => path,
+            None => {
+                self.status = "There is no data directory to keep the high scores in".to_string();
+                return;
+            }
+        };
+
+        match HighScoreTable::load(&path) {
+            Ok(table) => self.table = table,
+            Err(err) => self.status = format!("Unable to load {}: {}", path.display(), err)
+        }
+    }
+
+    fn difficulty(&self) -> Option<Difficulty> {
+        self.filter
+            .checked_sub(1)
+            .map(|difficulty| Difficulty::ALL[difficulty])
+    }
+
+    fn describe(&self) -> String {
+        let sort_by = SortBy::ALL[self.sort_by];
+        let filter = self
+            .difficulty()
+            .map_or_else(|| "all".to_string(), |d| format!("{:?}", d));
+        let mut text = format!(
+            "High scores\n{}\nSorted by {:?}, difficulty: {}\n\n",
+            self.status, sort_by, filter
+        );
+
+        let scores = self.table.sorted(sort_by, self.difficulty());
+        if scores.is_empty() {
+            text.push_str("No games have been recorded yet.\n");
+        }
+        for (rank, score) in scores.iter().take(SHOWN_SCORES).enumerate() {
+            let _ = writeln!(text, "{:>2}. {}", rank + 1, describe_score(score));
+        }
+
+        text.push_str("\n[S] Sort  [F] Difficulty  [Esc] Back");
+        text
+    }
+
+    fn refresh(&self, world: &World) {
+        if let Some(text) = self.text {
+            ui::set_text(world, text, self.describe());
+        }
+    }
+}
 
 impl SimpleState for HighScores {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.load();
+        self.text = Some(ui::create_text(data.world, "high_scores", self.describe()));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(text) = self.text.take() {
+            let _ = data.world.delete_entity(text);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::S) {
+                self.sort_by = (self.sort_by + 1) % SortBy::ALL.len();
+            }
+            if is_key_down(event, VirtualKeyCode::F) {
+                self.filter = (self.filter + 1) % (Difficulty::ALL.len() + 1);
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+}
+
+// One line of the table.
+fn describe_score(score: &HighScore) -> String {
+    let outcome = match score.outcome {
+        Outcome::Won => "won".to_string(),
+        Outcome::Lost(reason) => format!("lost ({:?})", reason)
+    };
+    let date = Local
+        .timestamp_opt(score.played_at, 0)
+        .single()
+        .map_or_else(String::new, |date| {
+            date.format("%Y-%m-%d %H:%M").to_string()
+        });
+    let players: Vec<String> = score
+        .players
+        .iter()
+        .zip(&score.adventurers)
+        .map(|(name, adventurer)| format!("{} ({:?})", name, adventurer))
+        .collect();
+
+    format!(
+        "{:>5}  {}  {:?}  {}  {}  {} turns  water level {}  {}",
+        score.score(),
+        date,
+        score.difficulty,
+        score.map,
+        outcome,
+        score.turns,
+        score.water_level,
+        players.join(", ")
+    )
 }
@@ -0,0 +1,137 @@
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    winit::{Event, WindowEvent}
+};
+
+use super::GamePreparations;
+use crate::net::DEFAULT_PORT;
+use crate::ui;
+
+/// The state representing the screen to join a game hosted on another machine
+///
+/// The address of the server and the name of the player are typed in. `Tab` or
+/// the arrow keys switch between them and `Enter` joins the lobby of the
+/// server.
+pub struct JoinGame {
+    address: String,
+    name:    String,
+    /// Whether the name is being typed, instead of the address.
+    on_name: bool,
+    status:  String,
+    text:    Option<Entity>
+}
+
+impl Default for JoinGame {
+    fn default() -> Self {
+        Self {
+            address: format!("localhost:{}", DEFAULT_PORT),
+            name:    "Player".to_string(),
+            on_name: false,
+            status:  String::new(),
+            text:    None
+        }
+    }
+}
+
+impl JoinGame {
+    fn field(&mut self) -> &mut String {
+        if self.on_name {
+            &mut self.name
+        }
+        else {
+            &mut self.address
+        }
+    }
+
+    // Join the lobby of the server, if everything has been filled in.
+    fn join(&mut self) -> SimpleTrans {
+        let address = self.address.trim();
+        let name = self.name.trim();
+        if address.is_empty() {
+            self.status = "The address of the server is missing".to_string();
+            return Trans::None;
+        }
+        if name.is_empty() {
+            self.status = "The name is missing".to_string();
+            return Trans::None;
+        }
+
+        Trans::Switch(Box::new(GamePreparations::join(
+            address.to_string(),
+            name.to_string()
+        )))
+    }
+
+    fn describe(&self) -> String {
+        let cursor = |typing: bool| if typing { "_" } else { "" };
+        format!(
+            "Join a game\n{}\n\n{} Address: {}{}\n{} Name: {}{}\n\n[Tab] Next field  [Enter] \
+             Join  [Esc] Back",
+            self.status,
+            if self.on_name { ' ' } else { '>' },
+            self.address,
+            cursor(!self.on_name),
+            if self.on_name { '>' } else { ' ' },
+            self.name,
+            cursor(self.on_name)
+        )
+    }
+
+    fn refresh(&self, world: &World) {
+        if let Some(text) = self.text {
+            ui::set_text(world, text, self.describe());
+        }
+    }
+}
+
+impl SimpleState for JoinGame {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.text = Some(ui::create_text(data.world, "join_game", self.describe()));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(text) = self.text.take() {
+            let _ = data.world.delete_entity(text);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::Return) {
+                let trans = self.join();
+                self.refresh(data.world);
+                return trans;
+            }
+            if is_key_down(event, VirtualKeyCode::Tab)
+                || is_key_down(event, VirtualKeyCode::Up)
+                || is_key_down(event, VirtualKeyCode::Down)
+            {
+                self.on_name = !self.on_name;
+            }
+            if is_key_down(event, VirtualKeyCode::Back) {
+                self.field().pop();
+            }
+            if let Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } = event
+            {
+                if !c.is_control() {
+                    self.field().push(*c);
+                }
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+}
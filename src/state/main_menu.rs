@@ -1,8 +1,143 @@
-use amethyst::prelude::*;
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, UiEventType}
+};
+
+use super::{GamePreparations, HighScores, JoinGame, MapEditor, Settings, DEFAULT_MAP_FILE};
+use crate::ui;
+use std::path::PathBuf;
+
+const BUTTON_WIDTH: f32 = 480.;
+const BUTTON_HEIGHT: f32 = 64.;
+
+/// The entries of the menu, from top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Entry {
+    NewGame,
+    JoinGame,
+    MapEditor,
+    HighScores,
+    Settings,
+    Quit
+}
+
+impl Entry {
+    const ALL: [Entry; 6] = [
+        Entry::NewGame,
+        Entry::JoinGame,
+        Entry::MapEditor,
+        Entry::HighScores,
+        Entry::Settings,
+        Entry::Quit
+    ];
+
+    const KEYS: [VirtualKeyCode; 6] = [
+        VirtualKeyCode::Key1,
+        VirtualKeyCode::Key2,
+        VirtualKeyCode::Key3,
+        VirtualKeyCode::Key4,
+        VirtualKeyCode::Key5,
+        VirtualKeyCode::Key6
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::NewGame => "New game",
+            Self::JoinGame => "Join a game",
+            Self::MapEditor => "Map editor",
+            Self::HighScores => "High scores",
+            Self::Settings => "Settings",
+            Self::Quit => "Quit"
+        }
+    }
+
+    // The screen the entry leads to. Leaving that screen returns to the menu.
+    fn open(self) -> SimpleTrans {
+        match self {
+            Self::NewGame => {
+                let mut preparations = GamePreparations::local();
+                let map_file = PathBuf::from(DEFAULT_MAP_FILE);
+                if map_file.exists() {
+                    preparations = preparations.with_map_file(map_file);
+                }
+                Trans::Push(Box::new(preparations))
+            }
+            Self::JoinGame => Trans::Push(Box::new(JoinGame::default())),
+            Self::MapEditor => Trans::Push(Box::new(MapEditor::default())),
+            Self::HighScores => Trans::Push(Box::new(HighScores::default())),
+            Self::Settings => Trans::Push(Box::new(Settings::default())),
+            Self::Quit => Trans::Quit
+        }
+    }
+}
 
 /// The state representing the main menu
-pub struct MainMenu {}
+///
+/// The game starts here. Every entry is a button, which can also be chosen with
+/// the number in front of it. The screens opened from the menu go back to it
+/// with `Escape`.
+#[derive(Default)]
+pub struct MainMenu {
+    buttons: Vec<Entity>
+}
+
+impl MainMenu {
+    fn show(&mut self, world: &mut World) {
+        self.buttons = Entry::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                ui::create_button(
+                    world,
+                    &format!("main_menu_{:?}", entry),
+                    Anchor::Middle,
+                    (
+                        0.,
+                        (Entry::ALL.len() as f32 / 2. - i as f32) * BUTTON_HEIGHT
+                    ),
+                    (BUTTON_WIDTH, BUTTON_HEIGHT),
+                    format!("[{}] {}", i + 1, entry.label())
+                )
+            })
+            .collect();
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        let _ = world.delete_entities(&self.buttons);
+        self.buttons.clear();
+    }
+}
 
 impl SimpleState for MainMenu {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) { self.show(data.world); }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) { self.hide(data.world); }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) { self.hide(data.world); }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) { self.show(data.world); }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        let chosen = match &event {
+            StateEvent::Window(event) => {
+                Entry::KEYS.iter().position(|&key| is_key_down(event, key))
+            }
+            StateEvent::Ui(event) if event.event_type == UiEventType::Click => self
+                .buttons
+                .iter()
+                .position(|&button| button == event.target),
+            _ => None
+        };
+
+        match chosen {
+            Some(i) => Entry::ALL[i].open(),
+            None => Trans::None
+        }
+    }
 }
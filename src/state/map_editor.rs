@@ -1,8 +1,664 @@
-use amethyst::prelude::*;
+use amethyst::{
+    assets::Handle,
+    core::Transform,
+    ecs::{Entity, EntityBuilder, Join, ReaderId},
+    input::{is_key_down, InputHandler, MouseButton, StringBindings, VirtualKeyCode},
+    prelude::*,
+    renderer::{palette::Srgba, resources::Tint, Camera, SpriteRender, SpriteSheet},
+    shrev::EventChannel,
+    ui::Anchor,
+    window::ScreenDimensions
+};
+use nalgebra::Vector3;
+
+use super::game::{init_camera, GAME_HEIGHT, GAME_WIDTH};
+use crate::asset;
+use crate::map::{
+    loader, Draft, FieldPos, IslandTileInfo, IslandTileState, MAX_DRAFT_SIZE, TILE_SCALE,
+    TILE_WIDTH
+};
+use crate::math::Vec2;
+use crate::system::{screen_to_world, DragArea, DragEvent, Draggable, Dragged, DropZone};
+use crate::ui::{self, MARGIN};
+use std::fmt::Write;
+use std::mem;
+use std::path::PathBuf;
+
+/// The file maps are saved to and loaded from, unless another one is chosen.
+pub const DEFAULT_MAP_FILE: &str = "map.json";
+
+/// The number of problems listed at once, so the list fits on the screen.
+const SHOWN_PROBLEMS: usize = 8;
+
+/// How long the side of the grid is in the world, however many cells it has.
+const GRID_LENGTH: f32 = GAME_HEIGHT - 2. * MARGIN;
+
+/// Where the palette starts in the world, to the right of the grid.
+const PALETTE_LEFT: f32 = GRID_LENGTH + 2. * MARGIN;
+
+/// The number of tiles next to each other in the palette.
+const PALETTE_COLUMNS: usize = 3;
+
+/// How long the side of a cell of the palette is in the world.
+const PALETTE_CELL: f32 = 96.;
+
+/// How much of its cell a tile covers, so there is a gap between them.
+const TILE_FILL: f32 = 0.94;
+
+/// The sprite that stands in for cells without a tile, tinted as water or
+/// island.
+const CELL_SPRITE: usize = 0;
+
+/// The keys of the editor, which are checked for every event.
+const KEYS: [VirtualKeyCode; 14] = [
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Tab,
+    VirtualKeyCode::Return,
+    VirtualKeyCode::Delete,
+    VirtualKeyCode::F,
+    VirtualKeyCode::W,
+    VirtualKeyCode::N,
+    VirtualKeyCode::T,
+    VirtualKeyCode::S,
+    VirtualKeyCode::F5
+];
+
+// What a sprite of the editor shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Piece {
+    /// The cell of the grid at the position, which tiles are dropped onto.
+    Cell(FieldPos),
+    /// The tile placed on the cell at the position.
+    Tile(FieldPos),
+    /// The tile in the palette, which has not been placed yet.
+    Palette(IslandTileInfo)
+}
 
 /// The state representing the map editor
-pub struct MapEditor {}
+///
+/// The grid is shown on the left and the tiles that have not been placed yet
+/// form the palette next to it. Tiles are dragged from the palette onto the
+/// cells, from one cell to another or off the grid back to the palette.
+/// Clicking a cell turns it into island or water, and holding the button while
+/// moving the mouse paints the cells it passes over the same way.
+///
+/// The keyboard does the same: The cursor is moved over the grid with the arrow
+/// keys. `Space` turns the cell into island or water, `Tab` picks the next tile
+/// of the palette and `Enter` puts it on the cell. `Enter` on a tile picks it
+/// up again, so it can be put somewhere else. `Delete` takes a tile back to the
+/// palette and `F` changes the state it is in when the game starts. `W`, `N`,
+/// `T` and `S` make the grid wider, narrower, taller and shorter. `F5` saves
+/// the map, which is only possible once it is valid, and `F9` loads the map
+/// from the file again.
+pub struct MapEditor {
+    path: PathBuf,
+    draft: Draft,
+    cursor: FieldPos,
+    selected: Option<IslandTileInfo>,
+    status: String,
+    sidebar: Option<Entity>,
+    sprite_sheet: Option<Handle<SpriteSheet>>,
+    pieces: Vec<(Entity, Piece)>,
+    drag_events: Option<ReaderId<DragEvent>>,
+    /// Whether the left mouse button has been held the last time.
+    pressed: bool,
+    /// What the cells become while the mouse paints over them.
+    painting: Option<bool>
+}
+
+impl MapEditor {
+    /// Create the editor for the map file at the path. If it exists, its map
+    /// is edited, otherwise the editor starts with water only.
+    pub fn new(path: PathBuf) -> Self {
+        let mut editor = Self {
+            path,
+            draft: Draft::default(),
+            cursor: Vec2::from_values(0, 0),
+            selected: None,
+            status: String::new(),
+            sidebar: None,
+            sprite_sheet: None,
+            pieces: Vec::new(),
+            drag_events: None,
+            pressed: false,
+            painting: None
+        };
+        if editor.path.exists() {
+            editor.load();
+        }
+        else {
+            editor.status = format!("New map {}", editor.path.display());
+        }
+
+        editor
+    }
+
+    fn load(&mut self) {
+        match loader::load_map(&self.path) {
+            Ok(map) => match Draft::from_map(&map) {
+                Ok(draft) => {
+                    self.draft = draft;
+                    self.cursor = Vec2::from_values(0, 0);
+                    self.select_next();
+                    self.status = format!("Loaded {}", self.path.display());
+                }
+                Err(problem) => {
+                    self.status = format!("Unable to edit {}: {}", self.path.display(), problem)
+                }
+            },
+            Err(err) => self.status = format!("Unable to load {}: {}", self.path.display(), err)
+        }
+    }
+
+    fn save(&mut self) {
+        self.status = match self.draft.to_map() {
+            Ok(map) => match loader::save_map(&self.path, &map) {
+                Ok(()) => format!("Saved {}", self.path.display()),
+                Err(err) => format!("Unable to save {}: {}", self.path.display(), err)
+            },
+            Err(problems) => format!(
+                "The map can not be saved while it has {} problems",
+                problems.len()
+            )
+        };
+    }
+
+    // Pick the tile of the palette after the one selected right now.
+    fn select_next(&mut self) {
+        let unplaced = self.draft.unplaced();
+        let next = self
+            .selected
+            .and_then(|selected| unplaced.iter().position(|&info| info == selected))
+            .map_or(0, |current| current + 1);
+
+        self.selected = unplaced.get(next).or_else(|| unplaced.first()).copied();
+    }
+
+    // Pick another tile of the palette if the selected one has been placed, or
+    // the first one that is back in the palette if none has been selected.
+    fn keep_selection(&mut self) {
+        let placed = self
+            .selected
+            .map_or(true, |selected| self.draft.position_of(selected).is_some());
+        if placed {
+            self.select_next();
+        }
+    }
+
+    // Resize the grid by the amount of columns and rows, keeping the cursor on
+    // it.
+    fn resize(&mut self, dx: i8, dy: i8) {
+        let size = self.draft.size();
+        let new_size = Vec2::from_values(
+            (i16::from(size.x) + i16::from(dx))
+                .max(1)
+                .min(i16::from(MAX_DRAFT_SIZE)) as u8,
+            (i16::from(size.y) + i16::from(dy))
+                .max(1)
+                .min(i16::from(MAX_DRAFT_SIZE)) as u8
+        );
+
+        self.draft.resize(new_size);
+        self.cursor = Vec2::from_values(
+            self.cursor.x.min(new_size.x - 1),
+            self.cursor.y.min(new_size.y - 1)
+        );
+        self.keep_selection();
+    }
+
+    fn press(&mut self, key: VirtualKeyCode) {
+        let size = self.draft.size();
+        let cursor = self.cursor;
+        self.status.clear();
+
+        // The first row is at the bottom, as on the board of the game.
+        match key {
+            VirtualKeyCode::Left => self.cursor.x = cursor.x.saturating_sub(1),
+            VirtualKeyCode::Right => self.cursor.x = (cursor.x + 1).min(size.x - 1),
+            VirtualKeyCode::Up => self.cursor.y = (cursor.y + 1).min(size.y - 1),
+            VirtualKeyCode::Down => self.cursor.y = cursor.y.saturating_sub(1),
+            VirtualKeyCode::Space => {
+                self.draft.set_island(cursor, !self.draft.is_island(cursor));
+                self.keep_selection();
+            }
+            VirtualKeyCode::Tab => self.select_next(),
+            VirtualKeyCode::Return => match (self.draft.tile(cursor), self.selected) {
+                (Some(tile), _) => {
+                    self.draft.remove(cursor);
+                    self.selected = Some(*tile.info());
+                }
+                (None, Some(selected)) => {
+                    self.draft.place(cursor, selected);
+                    self.select_next();
+                }
+                (None, None) => self.status = "All tiles have been placed".to_string()
+            },
+            VirtualKeyCode::Delete => {
+                self.draft.remove(cursor);
+                self.keep_selection();
+            }
+            VirtualKeyCode::F => {
+                if let Some(tile) = self.draft.tile(cursor) {
+                    let next = match tile.state() {
+                        IslandTileState::Dry => IslandTileState::Flooded,
+                        IslandTileState::Flooded => IslandTileState::Gone,
+                        IslandTileState::Gone => IslandTileState::Dry
+                    };
+                    self.draft.set_state(cursor, next);
+                }
+            }
+            VirtualKeyCode::W => self.resize(1, 0),
+            VirtualKeyCode::N => self.resize(-1, 0),
+            VirtualKeyCode::T => self.resize(0, 1),
+            VirtualKeyCode::S => self.resize(0, -1),
+            VirtualKeyCode::F5 => self.save(),
+            _ => {}
+        }
+    }
+
+    fn piece(&self, entity: Entity) -> Option<Piece> {
+        self.pieces
+            .iter()
+            .find(|(piece_entity, _)| *piece_entity == entity)
+            .map(|(_, piece)| *piece)
+    }
+
+    // Put the tile that has been dropped onto the cell of the zone, or back
+    // into the palette if it has been dropped off the grid.
+    //
+    // # Returns
+    // Whether the draft has changed.
+    fn drop(&mut self, entity: Entity, zone: Option<Entity>) -> bool {
+        let cell = match zone.and_then(|zone| self.piece(zone)) {
+            Some(Piece::Cell(pos)) => Some(pos),
+            _ => None
+        };
+
+        match (self.piece(entity), cell) {
+            (Some(Piece::Palette(info)), Some(to)) => {
+                self.draft.place(to, info);
+                self.cursor = to;
+            }
+            (Some(Piece::Tile(from)), Some(to)) => match self.draft.tile(from) {
+                Some(tile) => {
+                    self.draft.place(to, *tile.info());
+                    self.cursor = to;
+                }
+                None => return false
+            },
+            (Some(Piece::Tile(from)), None) => {
+                self.draft.remove(from);
+            }
+            _ => return false
+        }
+        self.status.clear();
+        self.keep_selection();
+        true
+    }
+
+    // Paint the cell under the mouse while the left button is held, unless a
+    // tile has been picked up with it. The first cell decides whether the
+    // cells become island or water.
+    //
+    // # Returns
+    // Whether the draft has changed.
+    fn paint(&mut self, world: &World, picked_up: bool) -> bool {
+        let (pressed, point) = mouse(world);
+        let was_pressed = mem::replace(&mut self.pressed, pressed);
+        if !pressed || picked_up {
+            self.painting = None;
+            return false;
+        }
+
+        let cell = point.and_then(|point| cell_at(self.draft.size(), point));
+        if !was_pressed {
+            self.painting = cell.map(|cell| !self.draft.is_island(cell));
+        }
+        match (self.painting, cell) {
+            (Some(island), Some(cell)) if self.draft.is_island(cell) != island => {
+                self.draft.set_island(cell, island);
+                self.cursor = cell;
+                self.status.clear();
+                self.keep_selection();
+                true
+            }
+            _ => false
+        }
+    }
+
+    // The status, the selected tile, the cell under the cursor, the problems
+    // of the map and the keys.
+    fn describe_sidebar(&self) -> String {
+        let mut text = format!("{}\n\n{}\n\n", self.path.display(), self.status);
+
+        match self.selected {
+            Some(info) => {
+                let _ = writeln!(text, "Selected: {}", info.name());
+            }
+            None => text.push_str("All tiles have been placed\n")
+        }
+        let _ = write!(text, "Cell {}, {}: ", self.cursor.x, self.cursor.y);
+        match self.draft.tile(self.cursor) {
+            Some(tile) => {
+                let _ = writeln!(text, "{} ({:?})", tile.info().name(), tile.state());
+            }
+            None if self.draft.is_island(self.cursor) => text.push_str("Island\n"),
+            None => text.push_str("Water\n")
+        }
+
+        let problems = self.draft.problems();
+        if problems.is_empty() {
+            text.push_str("\nThe map is valid\n");
+        }
+        else {
+            let _ = writeln!(text, "\n{} problems:", problems.len());
+            for problem in problems.iter().take(SHOWN_PROBLEMS) {
+                let _ = writeln!(text, "- {}", problem);
+            }
+            if problems.len() > SHOWN_PROBLEMS {
+                text.push_str("- ...\n");
+            }
+        }
+
+        text.push_str(
+            "\n[Click] Island/water  [Drag] Place/move/remove tile  [Arrows] Move  \
+             [Space] Island/water  [Tab] Next tile  [Enter] Place/pick up  [Del] Remove  \
+             [F] Flood state  [W/N/T/S] Resize  [F5] Save  [F9] Load  [Esc] Leave"
+        );
+        text
+    }
+
+    // Replace the sprites of the grid and the palette with ones showing the
+    // draft as it is now.
+    fn show(&mut self, world: &mut World) {
+        for (entity, _) in self.pieces.drain(..) {
+            let _ = world.delete_entity(entity);
+        }
+        let sprite_sheet = match &self.sprite_sheet {
+            Some(sprite_sheet) => sprite_sheet.clone(),
+            None => return
+        };
+
+        let size = self.draft.size();
+        let length = cell_length(size);
+        let cell_area = DragArea::World(Vec2::from_values(length, length));
+        for x in 0..size.x {
+            for y in 0..size.y {
+                let pos = Vec2::from_values(x, y);
+                let middle = cell_middle(size, pos);
+                let highlighted = pos == self.cursor;
+
+                // Cells without a tile stand out when they are part of the
+                // island, those with one are covered by it.
+                let tint = match (highlighted, self.draft.is_island(pos)) {
+                    (true, _) => Srgba::new(0.6, 0.55, 0.25, 1.0),
+                    (false, true) => Srgba::new(0.45, 0.4, 0.3, 1.0),
+                    (false, false) => Srgba::new(0.05, 0.1, 0.25, 1.0)
+                };
+                let entity = create_sprite(world, &sprite_sheet, middle, -1., length, CELL_SPRITE)
+                    .with(Tint(tint))
+                    .with(DropZone(cell_area))
+                    .build();
+                self.pieces.push((entity, Piece::Cell(pos)));
+
+                if let Some(tile) = self.draft.tile(pos) {
+                    // A gone tile is shown with its dry side, but greyed out.
+                    let sprite_number = tile.sprite_number().unwrap_or(*tile.info() as usize);
+                    let mut builder =
+                        create_sprite(world, &sprite_sheet, middle, -0.5, length, sprite_number)
+                            .with(Draggable(cell_area));
+                    if highlighted {
+                        builder = builder.with(Tint(Srgba::new(1.0, 0.9, 0.4, 1.0)));
+                    }
+                    else if tile.state() == IslandTileState::Gone {
+                        builder = builder.with(Tint(Srgba::new(0.4, 0.4, 0.4, 1.0)));
+                    }
+                    self.pieces.push((builder.build(), Piece::Tile(pos)));
+                }
+            }
+        }
+
+        let palette_area = DragArea::World(Vec2::from_values(PALETTE_CELL, PALETTE_CELL));
+        for info in self.draft.unplaced() {
+            let mut builder = create_sprite(
+                world,
+                &sprite_sheet,
+                palette_middle(info),
+                -0.5,
+                PALETTE_CELL,
+                info as usize
+            )
+            .with(Draggable(palette_area));
+            if Some(info) == self.selected {
+                builder = builder.with(Tint(Srgba::new(1.0, 0.9, 0.4, 1.0)));
+            }
+            self.pieces.push((builder.build(), Piece::Palette(info)));
+        }
+    }
+
+    fn refresh(&mut self, world: &mut World) {
+        self.show(world);
+        if let Some(sidebar) = self.sidebar {
+            ui::set_text(world, sidebar, self.describe_sidebar());
+        }
+    }
+}
+
+impl Default for MapEditor {
+    fn default() -> Self { Self::new(PathBuf::from(DEFAULT_MAP_FILE)) }
+}
 
 impl SimpleState for MapEditor {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        world.register::<Draggable>();
+        world.register::<DropZone>();
+        world.register::<Dragged>();
+        init_camera(world);
+
+        self.sprite_sheet = Some(asset::load_sprite_sheet(
+            "tiles.png",
+            "tiles_sheet.ron",
+            world
+        ));
+        self.drag_events = Some(
+            world
+                .write_resource::<EventChannel<DragEvent>>()
+                .register_reader()
+        );
+
+        let palette_right = PALETTE_LEFT + PALETTE_COLUMNS as f32 * PALETTE_CELL;
+        self.sidebar = Some(ui::create_label(
+            world,
+            "map_editor_sidebar",
+            Anchor::TopRight,
+            (-MARGIN, -MARGIN),
+            (
+                GAME_WIDTH - palette_right - 2. * MARGIN,
+                GAME_HEIGHT - 2. * MARGIN
+            ),
+            self.describe_sidebar()
+        ));
+        self.refresh(world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let entities = self.pieces.drain(..).map(|(entity, _)| entity);
+        for entity in entities.chain(self.sidebar.take()) {
+            let _ = data.world.delete_entity(entity);
+        }
+        self.drag_events = None;
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+
+            if is_key_down(event, VirtualKeyCode::F9) {
+                self.load();
+            }
+            else if let Some(key) = KEYS.iter().copied().find(|&key| is_key_down(event, key)) {
+                self.press(key);
+            }
+            else {
+                return Trans::None;
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let events: Vec<DragEvent> = match &mut self.drag_events {
+            Some(reader) => data
+                .world
+                .read_resource::<EventChannel<DragEvent>>()
+                .read(reader)
+                .copied()
+                .collect(),
+            None => Vec::new()
+        };
+
+        let mut picked_up = false;
+        let mut changed = false;
+        for event in events {
+            match event {
+                DragEvent::Started(_) => picked_up = true,
+                DragEvent::Dropped { entity, zone, .. } => changed |= self.drop(entity, zone)
+            }
+        }
+        changed |= self.paint(data.world, picked_up);
+
+        if changed {
+            self.refresh(data.world);
+        }
+        Trans::None
+    }
+}
+
+// Whether the left mouse button is held and where the cursor is in the world.
+fn mouse(world: &World) -> (bool, Option<Vec2<f32>>) {
+    let input = world.read_resource::<InputHandler<StringBindings>>();
+    let pressed = input.mouse_button_is_down(MouseButton::Left);
+
+    let screen = world.read_resource::<ScreenDimensions>();
+    let cameras = world.read_storage::<Camera>();
+    let transforms = world.read_storage::<Transform>();
+    let point = (&cameras, &transforms)
+        .join()
+        .next()
+        .and_then(|(_, camera)| {
+            input
+                .mouse_position()
+                .map(|cursor| screen_to_world(cursor, (screen.width(), screen.height()), camera))
+        });
+    (pressed, point)
+}
+
+// A sprite of the tile sheet covering most of a square with the side length.
+fn create_sprite<'a>(
+    world: &'a mut World,
+    sprite_sheet: &Handle<SpriteSheet>,
+    middle: Vec2<f32>,
+    z: f32,
+    length: f32,
+    sprite_number: usize
+) -> EntityBuilder<'a> {
+    let scale = length * TILE_FILL / TILE_WIDTH;
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(middle.x, middle.y, z);
+    transform.set_scale(Vector3::new(scale, scale, scale));
+
+    world
+        .create_entity()
+        .with(SpriteRender {
+            sprite_sheet: sprite_sheet.clone(),
+            sprite_number
+        })
+        .with(transform)
+}
+
+// How long the side of a cell is, so the grid fits into its space but the
+// tiles are never bigger than in the game.
+fn cell_length(size: Vec2<u8>) -> f32 {
+    (GRID_LENGTH / f32::from(size.x.max(size.y))).min(TILE_WIDTH * TILE_SCALE)
+}
+
+// The middle of the cell at the position in the world. The first row is at
+// the bottom, as on the board of the game.
+fn cell_middle(size: Vec2<u8>, pos: FieldPos) -> Vec2<f32> {
+    let length = cell_length(size);
+    Vec2::from_values(
+        MARGIN + length * (f32::from(pos.x) + 0.5),
+        MARGIN + length * (f32::from(pos.y) + 0.5)
+    )
+}
+
+// The cell of the grid at the point in the world, if it is on the grid.
+fn cell_at(size: Vec2<u8>, point: Vec2<f32>) -> Option<FieldPos> {
+    let length = cell_length(size);
+    let x = ((point.x - MARGIN) / length).floor();
+    let y = ((point.y - MARGIN) / length).floor();
+
+    if x < 0. || y < 0. || x >= f32::from(size.x) || y >= f32::from(size.y) {
+        None
+    }
+    else {
+        Some(Vec2::from_values(x as u8, y as u8))
+    }
+}
+
+// The middle of the place of the tile in the palette, which it keeps even while
+// other tiles are placed.
+fn palette_middle(info: IslandTileInfo) -> Vec2<f32> {
+    let column = info as usize % PALETTE_COLUMNS;
+    let row = info as usize / PALETTE_COLUMNS;
+    Vec2::from_values(
+        PALETTE_LEFT + PALETTE_CELL * (column as f32 + 0.5),
+        GAME_HEIGHT - MARGIN - PALETTE_CELL * (row as f32 + 0.5)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cells_under_points() {
+        let size = Vec2::from_values(16, 10);
+        for &pos in &[
+            Vec2::from_values(0, 0),
+            Vec2::from_values(15, 9),
+            Vec2::from_values(7, 3)
+        ] {
+            assert_eq!(Some(pos), cell_at(size, cell_middle(size, pos)));
+        }
+
+        assert_eq!(None, cell_at(size, Vec2::from_values(MARGIN - 1., MARGIN)));
+        assert_eq!(
+            None,
+            cell_at(size, cell_middle(size, Vec2::from_values(0, 10)))
+        );
+        assert_eq!(None, cell_at(size, palette_middle(IslandTileInfo::ALL[0])));
+    }
+
+    #[test]
+    fn small_grids_keep_the_tile_size() {
+        assert_eq!(
+            TILE_WIDTH * TILE_SCALE,
+            cell_length(Vec2::from_values(6, 6))
+        );
+        assert!(cell_length(Vec2::from_values(MAX_DRAFT_SIZE, 1)) * 16. <= GRID_LENGTH);
+    }
 }
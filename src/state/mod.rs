@@ -1,5 +1,11 @@
 //! The module containing all states, the game can be in
 
+pub mod board;
+pub use self::board::*;
+
+pub mod developer_tools;
+pub use self::developer_tools::*;
+
 pub mod game;
 pub use self::game::*;
 
@@ -9,11 +15,23 @@ pub use self::game_preparations::*;
 pub mod high_scores;
 pub use self::high_scores::*;
 
+pub mod join_game;
+pub use self::join_game::*;
+
 pub mod main_menu;
 pub use self::main_menu::*;
 
 pub mod map_editor;
 pub use self::map_editor::*;
 
+pub mod remote_game;
+pub use self::remote_game::*;
+
+pub mod scenario;
+pub use self::scenario::*;
+
 pub mod settings;
 pub use self::settings::*;
+
+pub mod turn_handoff;
+pub use self::turn_handoff::*;
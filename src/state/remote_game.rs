@@ -0,0 +1,296 @@
+use amethyst::{
+    assets::Handle,
+    core::Hidden,
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*,
+    renderer::SpriteSheet,
+    ui::Anchor
+};
+
+use super::board::{indicator_text, BoardInput, Controls, Pieces};
+use super::game::{init_camera, ActionQueue, GAME_WIDTH};
+use crate::asset;
+use crate::character::Character;
+use crate::game_state::GameSnapshot;
+use crate::map::FieldPosComp;
+use crate::net::{Client, ClientEvent, ServerMessage};
+use crate::ui;
+
+/// The state of a game that is hosted on another machine. The board mirrors
+/// the snapshots sent by the server and the actions of the local player are
+/// chosen the same way as in a local game, see [BoardInput], but sent to the
+/// server instead of being applied here. Only the actions of the seat of the
+/// player are offered. Spectators only watch the board, so nothing they do is
+/// sent. Whose turn it is and the hands are shown like in a local game, what
+/// happens at the table, like players losing their connection, below the
+/// board. `Escape` leaves the game.
+pub struct RemoteGame {
+    address: String,
+    /// The name of the player, or `None` for a spectator.
+    name: Option<String>,
+    client: Option<Client>,
+    seat: Option<usize>,
+    pending: Vec<ClientEvent>,
+    sprite_sheet: Option<Handle<SpriteSheet>>,
+    pieces: Pieces,
+    input: BoardInput,
+    indicator: Option<Entity>,
+    status_label: Option<Entity>,
+    /// What has happened at the table last.
+    status: String
+}
+
+impl RemoteGame {
+    /// Create the state for joining the server at the address with the name
+    /// provided. The connection is made once the state is started.
+    pub fn new(address: String, name: String) -> Self {
+        Self {
+            address,
+            name: Some(name),
+            client: None,
+            seat: None,
+            pending: Vec::new(),
+            sprite_sheet: None,
+            pieces: Pieces::default(),
+            input: BoardInput::new(Controls::Nobody),
+            indicator: None,
+            status_label: None,
+            status: String::new()
+        }
+    }
+
+    /// Create the state for watching the game hosted by the server at the
+    /// address as a spectator.
+    pub fn watch(address: String) -> Self {
+        Self {
+            name: None,
+            ..Self::new(address, String::new())
+        }
+    }
+
+    /// Create the state for a game the client has already joined, for instance
+    /// in the lobby.
+    ///
+    /// # Parameters
+    /// `seat` - The seat of the player in the game, if it is known yet
+    /// `pending` - Events the client has received, which still have to be
+    /// handled by the game
+    pub fn joined(
+        address: String,
+        name: String,
+        client: Client,
+        seat: Option<usize>,
+        pending: Vec<ClientEvent>
+    ) -> Self {
+        Self {
+            client: Some(client),
+            seat,
+            pending,
+            ..Self::new(address, name)
+        }
+    }
+
+    /// The seat of the local player, once the server has assigned one.
+    pub fn seat(&self) -> Option<usize> { self.seat }
+
+    // The actions of the seat of the player can be chosen, once the server
+    // has assigned one.
+    fn controls(&self) -> Controls {
+        match (&self.name, self.seat) {
+            (Some(_), Some(seat)) => Controls::Seat(seat),
+            _ => Controls::Nobody
+        }
+    }
+
+    // Show the board of the snapshot and offer the actions it allows.
+    fn show(&mut self, world: &mut World, snapshot: GameSnapshot) {
+        let sprite_sheet = self.sprite_sheet.clone().unwrap();
+        self.pieces.show(world, &snapshot, &sprite_sheet);
+        world.insert(snapshot);
+        self.input.refresh(world);
+        self.show_indicator(world);
+    }
+
+    // Show whose turn it is and who the local player is.
+    fn show_indicator(&self, world: &World) {
+        let indicator = match self.indicator {
+            Some(indicator) => indicator,
+            None => return
+        };
+        let mut text = match world.try_fetch::<GameSnapshot>() {
+            Some(snapshot) => indicator_text(&snapshot),
+            None => format!("Waiting for the game at {}", self.address)
+        };
+        match (&self.name, self.seat) {
+            (Some(name), Some(seat)) => {
+                text.push_str(&format!("\n{} plays player {}", name, seat + 1))
+            }
+            (Some(name), None) => text.push_str(&format!("\n{} is waiting for a seat", name)),
+            (None, _) => text.push_str("\nWatching the game")
+        }
+        text.push_str(&self.input.describe(world));
+        ui::set_text(world, indicator, text);
+    }
+
+    // Tell the players what has happened at the table.
+    fn set_status(&mut self, world: &World, status: String) {
+        self.status = status;
+        if let Some(label) = self.status_label {
+            ui::set_text(world, label, self.status.clone());
+        }
+    }
+}
+
+impl SimpleState for RemoteGame {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        world.register::<FieldPosComp>();
+        world.register::<Character>();
+        world.register::<Hidden>();
+        self.input = BoardInput::new(self.controls());
+        self.input.on_start(world);
+
+        self.sprite_sheet = Some(asset::load_sprite_sheet(
+            "characters.png",
+            "characters.ron",
+            world
+        ));
+        init_camera(world);
+
+        self.indicator = Some(ui::create_label(
+            world,
+            "active_player",
+            Anchor::TopMiddle,
+            (0., -ui::MARGIN),
+            (GAME_WIDTH * 0.5, 120.),
+            String::new()
+        ));
+        self.status_label = Some(ui::create_label(
+            world,
+            "table_status",
+            Anchor::BottomMiddle,
+            (0., ui::MARGIN),
+            (GAME_WIDTH * 0.5, 80.),
+            self.status.clone()
+        ));
+        self.show_indicator(world);
+
+        if self.client.is_none() {
+            let address = self.address.clone();
+            self.client = Some(match &self.name {
+                Some(name) => Client::connect(address, name.clone()),
+                None => Client::watch(address)
+            });
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.client = None;
+        self.pieces.delete(data.world);
+        let labels: Vec<Entity> = self
+            .indicator
+            .take()
+            .into_iter()
+            .chain(self.status_label.take())
+            .collect();
+        let _ = data.world.delete_entities(&labels);
+        self.input.on_stop(data.world);
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        match &event {
+            StateEvent::Window(event) if is_key_down(event, VirtualKeyCode::Escape) => Trans::Pop,
+            _ => {
+                if self.input.handle_event(data.world, &event) {
+                    self.show_indicator(data.world);
+                }
+                Trans::None
+            }
+        }
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        let mut events: Vec<ClientEvent> = self.pending.drain(..).collect();
+        match &self.client {
+            Some(client) => events.extend(client.poll()),
+            // The refusal stays on the screen until the player leaves.
+            None => return Trans::None
+        }
+
+        for event in events {
+            match event {
+                ClientEvent::Message(ServerMessage::Welcome { seat, .. })
+                | ClientEvent::Message(ServerMessage::GameStarted { seat }) => {
+                    self.seat = Some(seat);
+                    let controls = self.controls();
+                    self.input.set_controls(data.world, controls);
+                    self.show_indicator(data.world);
+                }
+                ClientEvent::Message(ServerMessage::Spectating { delay, .. }) => {
+                    let status = match delay {
+                        0 => "Watching the game".to_string(),
+                        delay => format!("Watching the game {} turns behind", delay)
+                    };
+                    self.set_status(data.world, status);
+                }
+                ClientEvent::Message(ServerMessage::Refused(refusal)) => {
+                    self.client = None;
+                    self.set_status(
+                        data.world,
+                        format!(
+                            "The server refused to let us in: {:?}\n[Esc] Leave",
+                            refusal
+                        )
+                    );
+                    return Trans::None;
+                }
+                ClientEvent::Message(ServerMessage::Snapshot(snapshot)) => {
+                    self.show(data.world, snapshot)
+                }
+                ClientEvent::Message(ServerMessage::Rejected { reason }) => {
+                    self.set_status(data.world, format!("Action rejected: {}", reason))
+                }
+                ClientEvent::Message(ServerMessage::PlayerJoined { seat, name }) => {
+                    self.set_status(data.world, format!("{} plays player {}", name, seat + 1))
+                }
+                ClientEvent::Message(ServerMessage::PlayerLeft { seat }) => self.set_status(
+                    data.world,
+                    format!("Player {} has lost the connection", seat + 1)
+                ),
+                ClientEvent::Message(ServerMessage::Applied { .. })
+                | ClientEvent::Message(ServerMessage::Lobby(_)) => {}
+                ClientEvent::Disconnected => {
+                    let status = format!("Lost the connection to {}, reconnecting", self.address);
+                    self.set_status(data.world, status);
+                }
+            }
+        }
+
+        if self.input.update(data.world, &self.pieces) {
+            self.show_indicator(data.world);
+        }
+
+        // The server decides whether the actions are allowed, so they are
+        // passed on as they are.
+        let actions = data.world.write_resource::<ActionQueue>().take();
+        if let (Some(client), Some(_)) = (&self.client, &self.name) {
+            let mut failed = None;
+            for action in actions {
+                if let Err(err) = client.send_action(action) {
+                    failed = Some(err);
+                }
+            }
+            if let Some(err) = failed {
+                self.set_status(data.world, format!("Unable to send the action: {}", err));
+            }
+        }
+
+        Trans::None
+    }
+}
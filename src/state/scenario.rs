@@ -0,0 +1,78 @@
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*
+};
+
+use super::Game;
+use crate::scenario;
+use crate::ui;
+use std::path::PathBuf;
+
+/// The state starting a game from a scenario file. Once the scenario has been
+/// loaded, it is played as a [Game] showing the objective, which ends as soon
+/// as the objective has been achieved or can not be achieved anymore. If the
+/// scenario can not be started, the reason is shown until `Escape` is pressed.
+pub struct ScenarioGame {
+    path:  PathBuf,
+    game:  Option<Game>,
+    error: Option<Entity>
+}
+
+impl ScenarioGame {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path:  path.into(),
+            game:  None,
+            error: None
+        }
+    }
+}
+
+impl SimpleState for ScenarioGame {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let started = scenario::load_scenario(&self.path).and_then(|scenario| {
+            let state = scenario.start(rand::random())?;
+            Ok(Game::scenario(state, scenario.name, scenario.objective))
+        });
+
+        match started {
+            Ok(game) => self.game = Some(game),
+            Err(err) => {
+                self.error = Some(ui::create_text(
+                    data.world,
+                    "scenario_error",
+                    format!(
+                        "Unable to start scenario {}: {}\n\n[Esc] Back",
+                        self.path.display(),
+                        err
+                    )
+                ))
+            }
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(error) = self.error.take() {
+            let _ = data.world.delete_entity(error);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        match &event {
+            StateEvent::Window(event) if is_key_down(event, VirtualKeyCode::Escape) => Trans::Pop,
+            _ => Trans::None
+        }
+    }
+
+    fn update(&mut self, _data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        match self.game.take() {
+            Some(game) => Trans::Switch(Box::new(game)),
+            None => Trans::None
+        }
+    }
+}
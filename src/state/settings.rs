@@ -1,9 +1,185 @@
-use amethyst::prelude::*;
+use amethyst::{
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*
+};
+
+use crate::settings::{self, step_volume, AnimationSpeed, GameSettings, RESOLUTIONS};
+use crate::ui;
+use std::fmt::Write;
+
+/// The settings that can be chosen, from top to bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Entry {
+    MusicVolume,
+    EffectsVolume,
+    Fullscreen,
+    Resolution,
+    AnimationSpeed,
+    Confirmations,
+    DeveloperTools
+}
+
+impl Entry {
+    const ALL: [Entry; 7] = [
+        Entry::MusicVolume,
+        Entry::EffectsVolume,
+        Entry::Fullscreen,
+        Entry::Resolution,
+        Entry::AnimationSpeed,
+        Entry::Confirmations,
+        Entry::DeveloperTools
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::MusicVolume => "Music volume",
+            Self::EffectsVolume => "Effects volume",
+            Self::Fullscreen => "Fullscreen",
+            Self::Resolution => "Resolution",
+            Self::AnimationSpeed => "Animation speed",
+            Self::Confirmations => "Confirmation prompts",
+            Self::DeveloperTools => "Developer tools"
+        }
+    }
+
+    fn value(self, settings: &GameSettings) -> String {
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        match self {
+            Self::MusicVolume => format!("{}%", settings.music_volume),
+            Self::EffectsVolume => format!("{}%", settings.effects_volume),
+            Self::Fullscreen => on_off(settings.fullscreen),
+            Self::Resolution => format!("{}x{}", settings.resolution.0, settings.resolution.1),
+            Self::AnimationSpeed => format!("{:?}", settings.animation_speed),
+            Self::Confirmations => on_off(settings.confirmations),
+            Self::DeveloperTools => on_off(settings.developer_tools)
+        }
+    }
+
+    // Choose the next value of the setting in the direction, which is `1` or
+    // `-1`. Switches are turned on or off in both directions.
+    fn change(self, settings: &mut GameSettings, direction: i32) {
+        let cycle =
+            |index: usize, len: usize| (index as i32 + direction).rem_euclid(len as i32) as usize;
+        match self {
+            Self::MusicVolume => {
+                settings.music_volume = step_volume(settings.music_volume, direction)
+            }
+            Self::EffectsVolume => {
+                settings.effects_volume = step_volume(settings.effects_volume, direction)
+            }
+            Self::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Self::Resolution => {
+                settings.resolution =
+                    RESOLUTIONS[cycle(settings.resolution_index(), RESOLUTIONS.len())]
+            }
+            Self::AnimationSpeed => {
+                let index = AnimationSpeed::ALL
+                    .iter()
+                    .position(|&speed| speed == settings.animation_speed)
+                    .unwrap_or(0);
+                settings.animation_speed =
+                    AnimationSpeed::ALL[cycle(index, AnimationSpeed::ALL.len())]
+            }
+            Self::Confirmations => settings.confirmations = !settings.confirmations,
+            Self::DeveloperTools => settings.developer_tools = !settings.developer_tools
+        }
+    }
+}
 
 /// The state representing the settings window
-/// Music and Effect volume can be changed, and Developer Tools disabled or enabled here
-pub struct Settings {}
+///
+/// `Up` and `Down` choose a setting and `Left` and `Right` change it. Every
+/// change takes effect right away and is saved, so it is kept for the next time
+/// the game is started.
+#[derive(Default)]
+pub struct Settings {
+    selected: usize,
+    status:   String,
+    text:     Option<Entity>
+}
+
+impl Settings {
+    fn describe(&self, settings: &GameSettings) -> String {
+        let mut text = format!("Settings\n{}\n\n", self.status);
+        for (i, entry) in Entry::ALL.iter().enumerate() {
+            let marker = if i == self.selected { "> " } else { "  " };
+            let _ = writeln!(
+                text,
+                "{}{}: {}",
+                marker,
+                entry.label(),
+                entry.value(settings)
+            );
+        }
+
+        text.push_str("\n[Up/Down] Choose  [Left/Right] Change  [Esc] Back");
+        text
+    }
+
+    fn refresh(&self, world: &World) {
+        if let Some(text) = self.text {
+            let settings = world.read_resource::<GameSettings>();
+            ui::set_text(world, text, self.describe(&settings));
+        }
+    }
+
+    // Change the selected setting for everyone using the resource and keep it
+    // in the settings file.
+    fn change(&mut self, world: &mut World, direction: i32) {
+        let mut changed = GameSettings::clone(&world.read_resource());
+        Entry::ALL[self.selected].change(&mut changed, direction);
+
+        self.status = match settings::default_path() {
+            Ok(path) => match changed.save(&path) {
+                Ok(()) => String::new(),
+                Err(err) => format!("Unable to save {}: {}", path.display(), err)
+            },
+            Err(err) => format!("Unable to find the settings file: {}", err)
+        };
+        world.insert(changed);
+    }
+}
 
 impl SimpleState for Settings {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {}
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let text = {
+            let settings = data.world.read_resource::<GameSettings>();
+            self.describe(&settings)
+        };
+        self.text = Some(ui::create_text(data.world, "settings", text));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(text) = self.text.take() {
+            let _ = data.world.delete_entity(text);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_key_down(event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + Entry::ALL.len() - 1) % Entry::ALL.len();
+            }
+            if is_key_down(event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % Entry::ALL.len();
+            }
+            if is_key_down(event, VirtualKeyCode::Left) {
+                self.change(data.world, -1);
+            }
+            if is_key_down(event, VirtualKeyCode::Right) {
+                self.change(data.world, 1);
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
 }
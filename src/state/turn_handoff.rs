@@ -0,0 +1,92 @@
+use amethyst::{
+    core::Hidden,
+    ecs::Entity,
+    input::{is_key_down, VirtualKeyCode},
+    prelude::*
+};
+
+use crate::adventurer::AdventurerType;
+use crate::system::BoardHidden;
+use crate::ui;
+
+/// The screen between two turns of a game several players play at the same
+/// machine. The board is hidden until the next player confirms that they have
+/// taken over.
+pub struct TurnHandoff {
+    player: usize,
+    adventurer: AdventurerType,
+    hidden: Vec<Entity>,
+    text: Option<Entity>
+}
+
+impl TurnHandoff {
+    /// Create the screen asking the players to pass the machine on.
+    ///
+    /// # Parameters
+    /// `player` - The index of the player who has to decide next
+    /// `adventurer` - The adventurer of that player
+    /// `hidden` - The entities of the board, which are hidden while the
+    /// screen is shown
+    pub fn new(player: usize, adventurer: AdventurerType, hidden: Vec<Entity>) -> Self {
+        Self {
+            player,
+            adventurer,
+            hidden,
+            text: None
+        }
+    }
+}
+
+impl SimpleState for TurnHandoff {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        {
+            let mut hidden = world.write_storage::<Hidden>();
+            for &entity in &self.hidden {
+                let _ = hidden.insert(entity, Hidden);
+            }
+        }
+        world.insert(BoardHidden);
+
+        let text = format!(
+            "Player {} ({:?}), it is your turn.\n\nPress Enter when you are ready.",
+            self.player + 1,
+            self.adventurer
+        );
+        self.text = Some(ui::create_text(world, "turn_handoff", text));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        {
+            let mut hidden = world.write_storage::<Hidden>();
+            for &entity in &self.hidden {
+                hidden.remove(entity);
+            }
+        }
+        // Tiles that are gone are hidden again by the TileSpriteUpdate system.
+        world.remove::<BoardHidden>();
+
+        if let Some(text) = self.text.take() {
+            let _ = world.delete_entity(text);
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        _data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent
+    ) -> SimpleTrans {
+        match &event {
+            StateEvent::Window(event)
+                if is_key_down(event, VirtualKeyCode::Return)
+                    || is_key_down(event, VirtualKeyCode::Space) =>
+            {
+                Trans::Pop
+            }
+            _ => Trans::None
+        }
+    }
+}
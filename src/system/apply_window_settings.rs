@@ -0,0 +1,41 @@
+use amethyst::{
+    ecs::{Read, System},
+    window::Window,
+    winit::dpi::LogicalSize
+};
+
+use crate::settings::GameSettings;
+
+/// Resizes the window whenever the resolution or the fullscreen mode in the
+/// [GameSettings] have changed, so a new choice on the settings screen is
+/// visible right away. The settings the game has been started with are applied
+/// in the first frame.
+#[derive(Default)]
+pub struct ApplyWindowSettings {
+    applied: Option<(bool, (u32, u32))>
+}
+
+impl<'a> System<'a> for ApplyWindowSettings {
+    type SystemData = (Read<'a, GameSettings>, Option<Read<'a, Window>>);
+
+    fn run(&mut self, (settings, window): Self::SystemData) {
+        let window = match window {
+            Some(window) => window,
+            None => return
+        };
+        let wanted = (settings.fullscreen, settings.resolution);
+        if self.applied == Some(wanted) {
+            return;
+        }
+
+        if settings.fullscreen {
+            window.set_fullscreen(Some(window.get_current_monitor()));
+        }
+        else {
+            window.set_fullscreen(None);
+            let (width, height) = settings.resolution;
+            window.set_inner_size(LogicalSize::new(f64::from(width), f64::from(height)));
+        }
+        self.applied = Some(wanted);
+    }
+}
@@ -1,29 +1,57 @@
 use amethyst::{
-    core::Transform,
+    core::{math::Vector3, Time, Transform},
     ecs::{Join, Read, ReadStorage, System, WriteStorage}
 };
 
+use super::Dragged;
 use crate::character::Character;
-use crate::map::{full::calculate_field_translation, Full as MapFull};
+use crate::map::{
+    full::{calculate_field_translation, TILE_SCALE, TILE_WIDTH},
+    Full as MapFull
+};
+use crate::settings::GameSettings;
 
 /// Responsible for updating the transformations of the player characters based
 /// on the current position of them and the information available of the map
-/// they are playing on.
+/// they are playing on. Unless the animations are turned off in the
+/// [GameSettings], a character that has moved walks to its new tile instead of
+/// jumping there. A character that is being [Dragged] stays under the mouse.
 pub struct CharacterTransformUpdate;
 
 impl<'a> System<'a> for CharacterTransformUpdate {
     type SystemData = (
         Read<'a, MapFull>,
+        Read<'a, GameSettings>,
+        Read<'a, Time>,
         ReadStorage<'a, Character>,
+        ReadStorage<'a, Dragged>,
         WriteStorage<'a, Transform>
     );
 
-    fn run(&mut self, (map, characters, mut transforms): Self::SystemData) {
+    fn run(
+        &mut self,
+        (map, settings, time, characters, dragged, mut transforms): Self::SystemData
+    ) {
         let map_transform = map.transform();
+        let step = settings
+            .animation_speed
+            .tiles_per_second()
+            .map(|speed| speed * TILE_WIDTH * TILE_SCALE * time.delta_seconds());
+
+        for (character, _, transform) in (&characters, !&dragged, &mut transforms).join() {
+            let target = calculate_field_translation(&map_transform, character.pos());
+            let target = Vector3::new(target.x, target.y, 0.);
 
-        for (character, transform) in (&characters, &mut transforms).join() {
-            let translation = calculate_field_translation(&map_transform, character.pos());
-            transform.set_translation_xyz(translation.x, translation.y, 0.);
+            let distance = target - transform.translation();
+            match step {
+                Some(step) if distance.norm() > step => {
+                    let translation = transform.translation() + distance.normalize() * step;
+                    transform.set_translation(translation);
+                }
+                _ => {
+                    transform.set_translation(target);
+                }
+            }
         }
     }
 }
@@ -1 +1,296 @@
+//! Dragging entities with the mouse and dropping them onto others. Characters
+//! are dragged across the board in the world, cards between the hands on the
+//! screen.
 
+use amethyst::{
+    core::Transform,
+    ecs::{
+        Component, DenseVecStorage, Entities, Entity, Join, NullStorage, Read, ReadExpect,
+        ReadStorage, System, Write, WriteStorage
+    },
+    input::{InputHandler, MouseButton, StringBindings},
+    renderer::Camera,
+    shrev::EventChannel,
+    ui::UiTransform,
+    window::ScreenDimensions
+};
+
+use super::screen_to_world;
+use crate::math::{Rect, Vec2};
+use std::mem;
+
+/// Where an entity taking part in drag and drop is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragArea {
+    /// In the world, covering the size around the translation of its
+    /// [Transform].
+    World(Vec2<f32>),
+    /// On the screen, covering its [UiTransform].
+    Ui
+}
+
+impl DragArea {
+    // Entities can only be dropped onto zones shown the same way.
+    fn matches(self, other: DragArea) -> bool {
+        mem::discriminant(&self) == mem::discriminant(&other)
+    }
+}
+
+/// An entity that can be picked up with the left mouse button and dragged
+/// around until the button is released.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct Draggable(pub DragArea);
+
+/// An entity that [Draggable] entities of the same [DragArea] can be dropped
+/// onto.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct DropZone(pub DragArea);
+
+/// Marks the entity that is being dragged. Systems that position entities, like
+/// the [CharacterTransformUpdate](super::CharacterTransformUpdate), leave it to
+/// the mouse.
+#[derive(Clone, Copy, Debug, Default, Component)]
+#[storage(NullStorage)]
+pub struct Dragged;
+
+/// What has been dragged where.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DragEvent {
+    /// The entity has been picked up.
+    Started(Entity),
+    /// The entity has been let go of. It is back where it has been picked up,
+    /// whatever the drop means is up to the state.
+    Dropped {
+        entity: Entity,
+        /// The drop zone the entity has been dropped onto, if any.
+        zone:   Option<Entity>,
+        /// Where the cursor has been in the world.
+        point:  Vec2<f32>
+    }
+}
+
+/// Find the zone a rectangle has been dropped onto. If it overlaps several
+/// ones, it is the one it is the deepest inside of, so it would have to be
+/// moved the furthest to get out of it.
+///
+/// # Parameters
+/// `rect` - The area of what has been dropped
+/// `zones` - The zones it could have been dropped onto, with their areas
+///
+/// # Returns
+/// The zone the rectangle has been dropped onto, or `None` if it does not
+/// overlap any of them.
+pub fn deepest_overlap<K>(
+    rect: &Rect<f32>,
+    zones: impl IntoIterator<Item = (K, Rect<f32>)>
+) -> Option<K> {
+    zones
+        .into_iter()
+        .filter(|(_, zone)| Rect::intersect(rect, zone))
+        .map(|(zone, area)| (zone, rect.shortest_way_out(&area).length()))
+        .filter(|&(_, depth)| depth > 0.)
+        .fold(
+            None,
+            |deepest: Option<(K, f32)>, (zone, depth)| match deepest {
+                Some((_, deepest_depth)) if deepest_depth >= depth => deepest,
+                _ => Some((zone, depth))
+            }
+        )
+        .map(|(zone, _)| zone)
+}
+
+// The area a ui element covers, measured from the bottom left of the screen.
+fn ui_rect(transform: &UiTransform) -> Rect<f32> {
+    Rect {
+        x: transform.pixel_x() - transform.pixel_width() / 2.,
+        y: transform.pixel_y() - transform.pixel_height() / 2.,
+        w: transform.pixel_width(),
+        h: transform.pixel_height()
+    }
+}
+
+// The entity being dragged and where it has been picked up, as well as where
+// the cursor has been the last time, in the space of its area.
+struct Dragging {
+    entity: Entity,
+    area:   DragArea,
+    origin: Vec2<f32>,
+    cursor: Vec2<f32>
+}
+
+/// Moves [Draggable] entities along with the mouse while the left button is
+/// held and sends a [DragEvent] through the `EventChannel<DragEvent>` resource
+/// when they are picked up and dropped. Elements of the user interface are
+/// picked up before anything in the world.
+#[derive(Default)]
+pub struct DragAndDrop {
+    dragging: Option<Dragging>,
+    pressed:  bool
+}
+
+impl<'a> System<'a> for DragAndDrop {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InputHandler<StringBindings>>,
+        ReadExpect<'a, ScreenDimensions>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Draggable>,
+        ReadStorage<'a, DropZone>,
+        WriteStorage<'a, Dragged>,
+        WriteStorage<'a, Transform>,
+        WriteStorage<'a, UiTransform>,
+        Write<'a, EventChannel<DragEvent>>
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            input,
+            screen,
+            cameras,
+            draggables,
+            zones,
+            mut dragged,
+            mut transforms,
+            mut ui_transforms,
+            mut events
+        ): Self::SystemData
+    ) {
+        let camera = match (&cameras, &transforms).join().next() {
+            Some((_, camera)) => camera.clone(),
+            None => return
+        };
+        // The drop waits for the cursor to come back into the window.
+        let cursor = match input.mouse_position() {
+            Some(cursor) => cursor,
+            None => return
+        };
+        let world_point = screen_to_world(cursor, (screen.width(), screen.height()), &camera);
+        let ui_point = Vec2::from_values(cursor.0, screen.height() - cursor.1);
+        let point_in = |area: DragArea| match area {
+            DragArea::World(_) => world_point,
+            DragArea::Ui => ui_point
+        };
+        let rect_of =
+            |entity: Entity,
+             area: DragArea,
+             transforms: &WriteStorage<'a, Transform>,
+             ui_transforms: &WriteStorage<'a, UiTransform>| match area {
+                DragArea::World(size) => transforms
+                    .get(entity)
+                    .map(|transform| Rect::from_transform_as_middle(transform, size)),
+                DragArea::Ui => ui_transforms.get(entity).map(ui_rect)
+            };
+
+        let pressed = input.mouse_button_is_down(MouseButton::Left);
+        if pressed && !self.pressed {
+            let picked = (&entities, &draggables)
+                .join()
+                .filter(|(entity, draggable)| {
+                    rect_of(*entity, draggable.0, &transforms, &ui_transforms)
+                        .map_or(false, |rect| rect.contains(point_in(draggable.0)))
+                })
+                .min_by_key(|(_, draggable)| draggable.0 != DragArea::Ui)
+                .map(|(entity, draggable)| (entity, draggable.0));
+
+            self.dragging = picked.and_then(|(entity, area)| {
+                let origin = match area {
+                    DragArea::World(_) => transforms
+                        .get(entity)
+                        .map(|t| Vec2::from_values(t.translation().x, t.translation().y)),
+                    DragArea::Ui => ui_transforms
+                        .get(entity)
+                        .map(|t| Vec2::from_values(t.local_x, t.local_y))
+                }?;
+                Some(Dragging {
+                    entity,
+                    area,
+                    origin,
+                    cursor: point_in(area)
+                })
+            });
+            if let Some(dragging) = &self.dragging {
+                let _ = dragged.insert(dragging.entity, Dragged);
+                events.single_write(DragEvent::Started(dragging.entity));
+            }
+        }
+
+        if let Some(dragging) = &mut self.dragging {
+            let cursor = point_in(dragging.area);
+            let by = cursor - dragging.cursor;
+            dragging.cursor = cursor;
+            match dragging.area {
+                DragArea::World(_) => {
+                    if let Some(transform) = transforms.get_mut(dragging.entity) {
+                        transform.prepend_translation_x(by.x);
+                        transform.prepend_translation_y(by.y);
+                    }
+                }
+                DragArea::Ui => {
+                    if let Some(transform) = ui_transforms.get_mut(dragging.entity) {
+                        transform.local_x += by.x;
+                        transform.local_y += by.y;
+                    }
+                }
+            }
+        }
+
+        if !pressed {
+            if let Some(dragging) = self.dragging.take() {
+                let zone = rect_of(dragging.entity, dragging.area, &transforms, &ui_transforms)
+                    .and_then(|rect| {
+                        let areas = (&entities, &zones)
+                            .join()
+                            .filter(|(_, zone)| zone.0.matches(dragging.area))
+                            .filter_map(|(zone, area)| {
+                                rect_of(zone, area.0, &transforms, &ui_transforms)
+                                    .map(|rect| (zone, rect))
+                            });
+                        deepest_overlap(&rect, areas)
+                    });
+
+                match dragging.area {
+                    DragArea::World(_) => {
+                        if let Some(transform) = transforms.get_mut(dragging.entity) {
+                            let z = transform.translation().z;
+                            transform.set_translation_xyz(dragging.origin.x, dragging.origin.y, z);
+                        }
+                    }
+                    DragArea::Ui => {
+                        if let Some(transform) = ui_transforms.get_mut(dragging.entity) {
+                            transform.local_x = dragging.origin.x;
+                            transform.local_y = dragging.origin.y;
+                        }
+                    }
+                }
+                dragged.remove(dragging.entity);
+                events.single_write(DragEvent::Dropped {
+                    entity: dragging.entity,
+                    zone,
+                    point: world_point
+                });
+            }
+        }
+        self.pressed = pressed;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn deepest_zone() {
+        let card = Rect::from_slice([10., 10., 4., 2.]);
+        let zones = vec![
+            ("far", Rect::from_slice([100., 100., 10., 10.])),
+            ("edge", Rect::from_slice([0., 0., 11., 20.])),
+            ("around", Rect::from_slice([8., 8., 10., 10.])),
+        ];
+
+        assert_eq!(Some("around"), deepest_overlap(&card, zones.clone()));
+        assert_eq!(Some("edge"), deepest_overlap(&card, zones[..2].to_vec()));
+        assert_eq!(None, deepest_overlap(&card, zones[..1].to_vec()));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod apply_window_settings;
+pub use self::apply_window_settings::*;
+
 pub mod character_transform_update;
 pub use character_transform_update::*;
 
@@ -24,3 +27,9 @@ pub use self::rescue::*;
 
 pub mod show_tile_options;
 pub use self::show_tile_options::*;
+
+pub mod tile_picking;
+pub use self::tile_picking::*;
+
+pub mod tile_sprite_update;
+pub use self::tile_sprite_update::*;
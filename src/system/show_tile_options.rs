@@ -0,0 +1,200 @@
+//! Highlighting of tiles on the board, for instance to show where the active
+//! adventurer can go or which tile a hint is about.
+
+use amethyst::{
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::{palette::Srgba, resources::Tint}
+};
+
+use crate::bot::Hint;
+use crate::game_state::{GameAction, GameView};
+use crate::map::{FieldPos, FieldPosComp};
+
+/// The reason a tile is highlighted. Every option has its own colour.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileOption {
+    Move,
+    SpecialMove,
+    Drain,
+    Navigate,
+    /// The tile is part of the hint the player asked for.
+    Hint
+}
+
+impl TileOption {
+    /// The colour the tile sprite is tinted with.
+    pub fn colour(self) -> Srgba {
+        match self {
+            TileOption::Move => Srgba::new(0.6, 1.0, 0.6, 1.0),
+            TileOption::SpecialMove => Srgba::new(0.6, 0.8, 1.0, 1.0),
+            TileOption::Drain => Srgba::new(1.0, 0.9, 0.5, 1.0),
+            TileOption::Navigate => Srgba::new(1.0, 0.6, 1.0, 1.0),
+            TileOption::Hint => Srgba::new(1.0, 0.5, 0.4, 1.0)
+        }
+    }
+
+    /// The tile the action leads to, if it is one of the actions the option
+    /// highlights tiles for.
+    pub fn target(self, action: &GameAction) -> Option<FieldPos> {
+        match (self, action) {
+            (TileOption::Move, GameAction::Move(pos))
+            | (TileOption::SpecialMove, GameAction::SpecialMove(pos))
+            | (TileOption::Drain, GameAction::Drain(pos))
+            | (TileOption::Navigate, GameAction::Navigate { to: pos, .. }) => Some(*pos),
+            _ => None
+        }
+    }
+}
+
+/// Resource containing the tiles that should currently be highlighted.
+#[derive(Clone, Debug, Default)]
+pub struct TileOptions {
+    options: Vec<(FieldPos, TileOption)>,
+    /// The action taken when a highlighted tile is chosen.
+    actions: Vec<(FieldPos, GameAction)>
+}
+
+impl TileOptions {
+    /// Remove all highlights.
+    pub fn clear(&mut self) {
+        self.options.clear();
+        self.actions.clear();
+    }
+
+    /// Highlight the positions provided with the option, replacing all other
+    /// highlights.
+    pub fn set<I: IntoIterator<Item = FieldPos>>(&mut self, option: TileOption, positions: I) {
+        self.options = positions.into_iter().map(|pos| (pos, option)).collect();
+        self.actions.clear();
+    }
+
+    /// Highlight the tiles the actions lead to, replacing all other
+    /// highlights. Choosing one of the tiles takes its action.
+    ///
+    /// # Parameters
+    /// `option` - What the tiles are highlighted for. Actions that do not
+    /// belong to it, see [TileOption::target], are left out.
+    /// `actions` - The actions the player may take, for instance the legal
+    /// actions of the game
+    pub fn offer(&mut self, option: TileOption, actions: &[GameAction]) {
+        self.actions = actions
+            .iter()
+            .filter_map(|action| option.target(action).map(|pos| (pos, action.clone())))
+            .collect();
+        self.options = self.actions.iter().map(|(pos, _)| (*pos, option)).collect();
+    }
+
+    /// The action that is taken when the tile at the position is chosen.
+    pub fn action(&self, pos: FieldPos) -> Option<&GameAction> {
+        self.actions
+            .iter()
+            .find(|(action_pos, _)| *action_pos == pos)
+            .map(|(_, action)| action)
+    }
+
+    /// The highlighted positions, in the order they have been provided.
+    pub fn positions(&self) -> Vec<FieldPos> { self.options.iter().map(|(pos, _)| *pos).collect() }
+
+    /// Highlight the tiles the hint is about, replacing all other highlights.
+    pub fn show_hint(&mut self, hint: &Hint, view: &GameView) {
+        self.set(TileOption::Hint, hint.tiles(view));
+    }
+
+    /// The option the tile at the position is highlighted for, if any.
+    pub fn get(&self, pos: FieldPos) -> Option<TileOption> {
+        self.options
+            .iter()
+            .find(|(option_pos, _)| *option_pos == pos)
+            .map(|(_, option)| *option)
+    }
+
+    pub fn is_empty(&self) -> bool { self.options.is_empty() }
+}
+
+/// Tints the tiles according to the [TileOptions] resource.
+pub struct ShowTileOptions;
+
+impl<'a> System<'a> for ShowTileOptions {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, TileOptions>,
+        ReadStorage<'a, FieldPosComp>,
+        WriteStorage<'a, Tint>
+    );
+
+    fn run(&mut self, (entities, options, positions, mut tints): Self::SystemData) {
+        for (entity, pos) in (&entities, &positions).join() {
+            match options.get(pos.pos()) {
+                Some(option) => {
+                    tints
+                        .insert(entity, Tint(option.colour()))
+                        .expect("Unable to tint tile");
+                }
+                None => {
+                    tints.remove(entity);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn options_replace_each_other() {
+        let mut options = TileOptions::default();
+        options.set(
+            TileOption::Move,
+            vec![FieldPos::from_values(1, 1), FieldPos::from_values(1, 2)]
+        );
+        assert_eq!(
+            Some(TileOption::Move),
+            options.get(FieldPos::from_values(1, 2))
+        );
+
+        options.set(TileOption::Drain, vec![FieldPos::from_values(3, 3)]);
+        assert_eq!(None, options.get(FieldPos::from_values(1, 2)));
+        assert_eq!(
+            Some(TileOption::Drain),
+            options.get(FieldPos::from_values(3, 3))
+        );
+
+        options.clear();
+        assert!(options.is_empty());
+    }
+
+    #[test]
+    fn offered_actions() {
+        let (a, b) = (FieldPos::from_values(2, 3), FieldPos::from_values(4, 1));
+        let legal = vec![
+            GameAction::Move(a),
+            GameAction::Drain(b),
+            GameAction::Navigate {
+                player: 1,
+                to:     b
+            },
+            GameAction::EndActions,
+        ];
+
+        let mut options = TileOptions::default();
+        options.offer(TileOption::Drain, &legal);
+        assert_eq!(vec![b], options.positions());
+        assert_eq!(Some(&GameAction::Drain(b)), options.action(b));
+        assert_eq!(None, options.action(a));
+
+        options.offer(TileOption::Navigate, &legal);
+        assert_eq!(
+            Some(&GameAction::Navigate {
+                player: 1,
+                to:     b
+            }),
+            options.action(b)
+        );
+
+        options.set(TileOption::Hint, vec![a]);
+        assert_eq!(Some(TileOption::Hint), options.get(a));
+        assert_eq!(None, options.action(a));
+    }
+}
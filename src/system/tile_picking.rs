@@ -0,0 +1,113 @@
+//! Picking of tiles with the mouse. The cursor is followed from the screen
+//! through the camera onto the map, and hovering or clicking a field becomes a
+//! [TileEvent].
+
+use amethyst::{
+    core::Transform,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, Write},
+    input::{InputHandler, MouseButton, StringBindings},
+    renderer::Camera,
+    shrev::EventChannel,
+    window::ScreenDimensions
+};
+
+use crate::map::{full::calculate_field_pos, FieldPos, Full as MapFull};
+use crate::math::Vec2;
+use crate::state::{GAME_HEIGHT, GAME_WIDTH};
+
+/// What the mouse has done with the fields of the map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileEvent {
+    /// The cursor has moved onto another field of the map, or off the map if
+    /// it is `None`.
+    Hovered(Option<FieldPos>),
+    /// The left mouse button has been pressed on the field.
+    Clicked(FieldPos)
+}
+
+/// Convert a position on the screen into the world the 2D camera looks at. The
+/// camera sees [GAME_WIDTH] times [GAME_HEIGHT] of the world, centred on its
+/// translation.
+///
+/// # Parameters
+/// `screen` - The position in pixels, starting at the top left of the window
+/// `screen_size` - The size of the window in pixels
+/// `camera` - The transform of the camera
+pub fn screen_to_world(
+    screen: (f32, f32),
+    screen_size: (f32, f32),
+    camera: &Transform
+) -> Vec2<f32> {
+    let centre = camera.translation();
+    Vec2::from_values(
+        centre.x + (screen.0 / screen_size.0 - 0.5) * GAME_WIDTH,
+        centre.y + (0.5 - screen.1 / screen_size.1) * GAME_HEIGHT
+    )
+}
+
+/// Sends a [TileEvent] through the `EventChannel<TileEvent>` resource whenever
+/// the cursor moves onto another field of the [MapFull] resource, or the left
+/// mouse button is pressed on one.
+#[derive(Default)]
+pub struct TilePicking {
+    hovered: Option<FieldPos>,
+    pressed: bool
+}
+
+impl<'a> System<'a> for TilePicking {
+    type SystemData = (
+        Read<'a, InputHandler<StringBindings>>,
+        ReadExpect<'a, ScreenDimensions>,
+        Read<'a, MapFull>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Transform>,
+        Write<'a, EventChannel<TileEvent>>
+    );
+
+    fn run(&mut self, (input, screen, map, cameras, transforms, mut events): Self::SystemData) {
+        let camera = match (&cameras, &transforms).join().next() {
+            Some((_, camera)) => camera,
+            None => return
+        };
+
+        let hovered = input.mouse_position().and_then(|cursor| {
+            let point = screen_to_world(cursor, (screen.width(), screen.height()), camera);
+            calculate_field_pos(map.transform(), point).filter(|&pos| map.get(pos).is_some())
+        });
+        if hovered != self.hovered {
+            self.hovered = hovered;
+            events.single_write(TileEvent::Hovered(hovered));
+        }
+
+        let pressed = input.mouse_button_is_down(MouseButton::Left);
+        if pressed && !self.pressed {
+            if let Some(pos) = hovered {
+                events.single_write(TileEvent::Clicked(pos));
+            }
+        }
+        self.pressed = pressed;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn screen_corners() {
+        let mut camera = Transform::default();
+        camera.set_translation_xyz(GAME_WIDTH * 0.5, GAME_HEIGHT * 0.5, 1.);
+
+        let top_left = screen_to_world((0., 0.), (960., 540.), &camera);
+        assert_eq!(Vec2::from_values(0., GAME_HEIGHT), top_left);
+
+        let middle = screen_to_world((480., 270.), (960., 540.), &camera);
+        assert_eq!(
+            Vec2::from_values(GAME_WIDTH * 0.5, GAME_HEIGHT * 0.5),
+            middle
+        );
+
+        let bottom_right = screen_to_world((960., 540.), (960., 540.), &camera);
+        assert_eq!(Vec2::from_values(GAME_WIDTH, 0.), bottom_right);
+    }
+}
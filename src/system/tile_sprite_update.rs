@@ -0,0 +1,93 @@
+//! Keeping the tiles on the board in line with the map while it floods.
+
+use amethyst::{
+    core::Hidden,
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
+    renderer::SpriteRender
+};
+
+use crate::map::{FieldPosComp, Full as MapFull};
+
+/// Present as a resource while the whole board is hidden, for instance while
+/// the machine is passed on to the next player. The tiles are neither shown
+/// nor hidden by [TileSpriteUpdate] then.
+pub struct BoardHidden;
+
+/// Shows every tile entity with the sprite of the tile at its position in the
+/// [MapFull] resource, so a tile that is flooded or drained changes right away.
+/// Tiles that are gone are hidden, leaving the water underneath.
+pub struct TileSpriteUpdate;
+
+impl<'a> System<'a> for TileSpriteUpdate {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, MapFull>,
+        Option<Read<'a, BoardHidden>>,
+        ReadStorage<'a, FieldPosComp>,
+        WriteStorage<'a, SpriteRender>,
+        WriteStorage<'a, Hidden>
+    );
+
+    fn run(
+        &mut self,
+        (entities, map, board_hidden, positions, mut sprites, mut hidden): Self::SystemData
+    ) {
+        for (entity, pos) in (&entities, &positions).join() {
+            let tile = match map.get(pos.pos()) {
+                Some(Some(tile)) => *tile,
+                _ => continue
+            };
+
+            match tile.sprite_number() {
+                Some(sprite_number) => {
+                    if let Some(sprite) = sprites.get_mut(entity) {
+                        sprite.sprite_number = sprite_number;
+                    }
+                    if board_hidden.is_none() {
+                        hidden.remove(entity);
+                    }
+                }
+                None => {
+                    let _ = hidden.insert(entity, Hidden);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::map::{FieldPos, IslandTile, IslandTileInfo};
+    use amethyst::ecs::{Builder, RunNow, World, WorldExt};
+
+    #[test]
+    fn board_stays_hidden() {
+        let pos = FieldPos::from_values(0, 0);
+        let map = MapFull::new(
+            FieldPos::from_values(1, 1),
+            Some(IslandTile::new(IslandTileInfo::FoolsLanding))
+        );
+
+        let mut world = World::new();
+        world.register::<FieldPosComp>();
+        world.register::<SpriteRender>();
+        world.register::<Hidden>();
+        world.insert(map);
+        world.insert(BoardHidden);
+        let tile = world
+            .create_entity()
+            .with(FieldPosComp::new(pos))
+            .with(Hidden)
+            .build();
+
+        TileSpriteUpdate.run_now(&world);
+        world.maintain();
+        assert!(world.read_storage::<Hidden>().contains(tile));
+
+        world.remove::<BoardHidden>();
+        TileSpriteUpdate.run_now(&world);
+        world.maintain();
+        assert!(!world.read_storage::<Hidden>().contains(tile));
+    }
+}
@@ -0,0 +1,115 @@
+//! The cards of the treasure deck. Besides the artefact cards, which are needed
+//! to collect the artefacts, there are special action cards and the waters
+//! rise cards, which make the water level go up when they are drawn.
+
+use crate::artefact_type::ArtefactType;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// The maximum amount of cards a player may hold. If they have more, they must
+/// discard or play cards until they are back at the limit.
+pub const HAND_LIMIT: usize = 5;
+
+/// The amount of cards of the same artefact a player must hand in to collect
+/// the artefact.
+pub const CARDS_PER_ARTEFACT: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TreasureCard {
+    /// Card showing one of the artefacts. Four of them can be exchanged for
+    /// the artefact on one of its tiles.
+    Artefact(ArtefactType),
+    /// Flies any amount of players from one tile to any other tile. If all
+    /// artefacts have been collected, it can be used to leave the island from
+    /// Fools' Landing.
+    Helicopter,
+    /// Drains any tile on the island.
+    Sandbags,
+    /// Raises the water level when drawn and is discarded immediately.
+    WatersRise
+}
+
+impl TreasureCard {
+    /// Create the 28 cards of the standard treasure deck, not shuffled.
+    pub fn standard_deck() -> Vec<Self> {
+        let mut deck = Vec::with_capacity(28);
+        for &artefact in ArtefactType::ALL.iter() {
+            deck.extend(vec![Self::Artefact(artefact); 5]);
+        }
+        deck.extend(vec![Self::Helicopter; 3]);
+        deck.extend(vec![Self::Sandbags; 2]);
+        deck.extend(vec![Self::WatersRise; 3]);
+
+        deck
+    }
+
+    /// Special action cards can be played at any time, even during the turn
+    /// of another player, and do not cost an action point.
+    pub fn is_special(self) -> bool {
+        match self {
+            Self::Helicopter | Self::Sandbags => true,
+            _ => false
+        }
+    }
+
+    /// Only artefact cards can be given to other players.
+    pub fn is_transferable(self) -> bool {
+        match self {
+            Self::Artefact(_) => true,
+            _ => false
+        }
+    }
+}
+
+impl FromStr for TreasureCard {
+    type Err = String;
+
+    /// Parse the card from its name, ignoring the case. Artefact cards are
+    /// named after their artefact, like `fire`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let special = [Self::Helicopter, Self::Sandbags, Self::WatersRise];
+        ArtefactType::ALL
+            .iter()
+            .map(|&artefact| Self::Artefact(artefact))
+            .chain(special.iter().copied())
+            .find(|card| {
+                let name = match card {
+                    Self::Artefact(artefact) => format!("{:?}", artefact),
+                    card => format!("{:?}", card)
+                };
+                name.eq_ignore_ascii_case(s.trim())
+            })
+            .ok_or_else(|| format!("Unknown treasure card: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(TreasureCard::Artefact(ArtefactType::Air)), "air".parse());
+        assert_eq!(Ok(TreasureCard::WatersRise), "WatersRise".parse());
+        assert!("sun".parse::<TreasureCard>().is_err());
+    }
+
+    #[test]
+    fn standard_deck() {
+        let deck = TreasureCard::standard_deck();
+
+        assert_eq!(28, deck.len());
+        assert_eq!(
+            5,
+            deck.iter()
+                .filter(|&&c| c == TreasureCard::Artefact(ArtefactType::Fire))
+                .count()
+        );
+        assert_eq!(
+            3,
+            deck.iter()
+                .filter(|&&c| c == TreasureCard::WatersRise)
+                .count()
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Helpers for the screens of the game that consist of text and buttons.
+
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    prelude::*,
+    ui::{get_default_font, Anchor, FontAsset, Interactable, LineMode, UiText, UiTransform}
+};
+
+use crate::state::{GAME_HEIGHT, GAME_WIDTH};
+
+/// The margin between the text and the border of the screen.
+pub const MARGIN: f32 = 32.;
+
+const TEXT_COLOUR: [f32; 4] = [1., 1., 1., 1.];
+const FONT_SIZE: f32 = 28.;
+
+/// Create a block of text, that fills the screen from the top left.
+///
+/// # Parameters
+/// `id` - The id of the ui element
+/// `text` - The text to be shown. Lines are wrapped if they are too long.
+pub fn create_text(world: &mut World, id: &str, text: String) -> Entity {
+    create_label(
+        world,
+        id,
+        Anchor::TopLeft,
+        (MARGIN, -MARGIN),
+        (GAME_WIDTH - 2. * MARGIN, GAME_HEIGHT - 2. * MARGIN),
+        text
+    )
+}
+
+/// Create a block of text somewhere on the screen.
+///
+/// # Parameters
+/// `id` - The id of the ui element
+/// `anchor` - The point of the screen and of the text the position is
+/// relative to
+/// `pos` - The offset of the text from the anchor
+/// `size` - The width and height of the text. Lines are wrapped if they are
+/// too long.
+/// `text` - The text to be shown
+pub fn create_label(
+    world: &mut World,
+    id: &str,
+    anchor: Anchor,
+    pos: (f32, f32),
+    size: (f32, f32),
+    text: String
+) -> Entity {
+    let font = {
+        let loader = world.read_resource::<Loader>();
+        let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+        get_default_font(&loader, &font_storage)
+    };
+
+    let transform = UiTransform::new(
+        id.to_string(),
+        anchor,
+        anchor,
+        pos.0,
+        pos.1,
+        1.,
+        size.0,
+        size.1
+    );
+    let mut text = UiText::new(font, text, TEXT_COLOUR, FONT_SIZE);
+    text.line_mode = LineMode::Wrap;
+    text.align = anchor;
+
+    world.create_entity().with(transform).with(text).build()
+}
+
+/// Create a text that can be clicked. Clicks are sent to the state as
+/// [UiEvent](amethyst::ui::UiEvent)s with the entity returned as their target.
+///
+/// # Parameters
+/// See [create_label].
+pub fn create_button(
+    world: &mut World,
+    id: &str,
+    anchor: Anchor,
+    pos: (f32, f32),
+    size: (f32, f32),
+    text: String
+) -> Entity {
+    let button = create_label(world, id, anchor, pos, size, text);
+    let _ = world
+        .write_storage::<Interactable>()
+        .insert(button, Interactable);
+    button
+}
+
+/// Replace the text of an entity created with [create_text], [create_label] or
+/// [create_button].
+pub fn set_text(world: &World, entity: Entity, text: String) {
+    if let Some(ui_text) = world.write_storage::<UiText>().get_mut(entity) {
+        ui_text.text = text;
+    }
+}
@@ -3,6 +3,7 @@
 //! difficulty of the game.
 
 use amethyst::ecs::{Entity, World};
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign};
 use std::u8;
 
@@ -16,7 +17,7 @@ pub const LOOSING_WATER_LEVEL: u8 = 9;
 /// The water or flooding level the players are currently facing. This
 /// determines the amount of flood cards the players have to draw in the flood
 /// card phase.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct WaterLevel {
     level: u8
 }
@@ -37,6 +38,9 @@ impl WaterLevel {
         Self::new(level)
     }
 
+    /// The current level of the water, starting at zero.
+    pub fn level(self) -> u8 { self.level }
+
     /// Create a new flood state. Can be used at the beginning of the flood
     /// phase to create the starting state for it. It can not be used to
     /// create the next state when a card has been taken from the flood card
@@ -76,7 +80,7 @@ impl WaterLevel {
 /// two floods rise cards you may want to use it like this:
 ///
 /// ```
-/// use water_level::WaterLevel;
+/// use java_game::water_level::WaterLevel;
 ///
 /// // Create a new WaterLevel with the flood in state 6
 /// let level = WaterLevel::new(6);
@@ -99,7 +103,7 @@ impl Add<u8> for WaterLevel {
 /// If two flood rise cards have been drawn, you might want to use it like this:
 ///
 /// ```
-/// use water_level::WaterLevel;
+/// use java_game::water_level::WaterLevel;
 ///
 /// // Create a new level with the flood in state two
 /// let mut level = WaterLevel::new(2);